@@ -0,0 +1,423 @@
+//! Operator-triggered actions that fall outside the normal planner-driven flow,
+//! such as monitoring a special train the HZPP planner API doesn't know about.
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use serde_json::json;
+use sqlx::{query, query_as, Pool, Postgres};
+
+use crate::model::db_model::{BikesAllowed, RouteDb, RouteType, StopDb, WheelchairAccessible};
+
+#[derive(Debug, Deserialize)]
+pub struct AdhocRouteRequest {
+    pub route_number: i32,
+    pub source: String,
+    pub destination: String,
+    pub expected_start_time: DateTime<Utc>,
+    pub expected_end_time: DateTime<Utc>,
+}
+
+/// Inserts a synthetic route for `request` and returns it ready to be handed to
+/// the delay checker. Used for special trains (events, seasonal services) that
+/// are missing from the planner API.
+#[tracing::instrument(err, skip(pool))]
+pub async fn create_adhoc_route(
+    pool: &Pool<Postgres>,
+    request: AdhocRouteRequest,
+) -> anyhow::Result<RouteDb> {
+    let id = format!("adhoc-{}-{}", request.route_number, Utc::now().timestamp());
+    let slug = crate::utils::slugify(&[
+        "adhoc",
+        &request.route_number.to_string(),
+        &request.source,
+        &request.destination,
+    ]);
+
+    query(
+        "INSERT INTO routes (
+            id,
+            slug,
+            route_number,
+            source,
+            destination,
+            bikes_allowed,
+            wheelchair_accessible,
+            route_type,
+            expected_start_time,
+            expected_end_time
+        ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)",
+    )
+    .bind(&id)
+    .bind(&slug)
+    .bind(request.route_number)
+    .bind(&request.source)
+    .bind(&request.destination)
+    .bind(BikesAllowed::NotAllowed as i16)
+    .bind(WheelchairAccessible::NotAccessible as i16)
+    .bind(RouteType::Train as i16)
+    .bind(request.expected_start_time)
+    .bind(request.expected_end_time)
+    .execute(pool)
+    .await?;
+
+    Ok(RouteDb {
+        id,
+        numeric_id: None,
+        slug,
+        route_number: request.route_number,
+        source: request.source,
+        destination: request.destination,
+        bikes_allowed: BikesAllowed::NotAllowed,
+        wheelchair_accessible: WheelchairAccessible::NotAccessible,
+        route_type: RouteType::Train,
+        real_start_time: None,
+        real_start_time_inferred: true,
+        expected_start_time: request.expected_start_time,
+        real_end_time: None,
+        expected_end_time: request.expected_end_time,
+        max_delay_minutes: None,
+        final_delay_minutes: None,
+        schedule_only: false,
+        narrative_summary: None,
+        stops: vec![],
+    })
+}
+
+/// A planner-data fix for one stop of a not-yet-started run: either a field
+/// correction (wrong sequence, wrong times, wrong station) or removal of a
+/// phantom stop that shouldn't exist at all.
+#[derive(Debug, Deserialize)]
+pub struct StopCorrectionRequest {
+    pub station_id: Option<String>,
+    pub expected_arrival: Option<DateTime<Utc>>,
+    pub expected_departure: Option<DateTime<Utc>>,
+    /// Required so corrections show up in `stop_corrections` with context for
+    /// whoever reviews them later.
+    pub reason: String,
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum StopCorrectionError {
+    #[error("route not found")]
+    RouteNotFound,
+    #[error("stop not found")]
+    StopNotFound,
+    #[error("route has already started, corrections are no longer accepted")]
+    RouteAlreadyStarted,
+    #[error(transparent)]
+    Sqlx(#[from] sqlx::Error),
+}
+
+/// Looks up the DB `id`/`expected_start_time` a run's `numeric_id` maps to,
+/// and rejects the correction outright once the run has started (once
+/// `real_start_time` is set the delay checker's own records are the source of
+/// truth, and it never re-reads corrections made after that point).
+async fn lookup_unstarted_run(
+    executor: impl sqlx::PgExecutor<'_>,
+    numeric_id: i64,
+) -> Result<(String, DateTime<Utc>), StopCorrectionError> {
+    let route: Option<(String, DateTime<Utc>, Option<DateTime<Utc>>)> = query_as(
+        "SELECT id, expected_start_time, real_start_time FROM routes WHERE numeric_id = $1",
+    )
+    .bind(numeric_id)
+    .fetch_optional(executor)
+    .await?;
+
+    let Some((route_id, expected_start_time, real_start_time)) = route else {
+        return Err(StopCorrectionError::RouteNotFound);
+    };
+
+    if real_start_time.is_some() {
+        return Err(StopCorrectionError::RouteAlreadyStarted);
+    }
+
+    Ok((route_id, expected_start_time))
+}
+
+/// Applies `request` to the stop at `sequence` on `numeric_id`'s run, and
+/// records the stop's prior state in `stop_corrections`. The delay checker
+/// re-reads a run's stops from the DB right before it starts monitoring it,
+/// so a correction made while the run is still pending is picked up
+/// automatically.
+#[tracing::instrument(err, skip(pool))]
+pub async fn correct_stop(
+    pool: &Pool<Postgres>,
+    numeric_id: i64,
+    sequence: i16,
+    request: StopCorrectionRequest,
+) -> Result<(), StopCorrectionError> {
+    let mut transaction = pool.begin().await?;
+
+    let (route_id, expected_start_time) = lookup_unstarted_run(&mut *transaction, numeric_id).await?;
+
+    let previous: Option<StopDb> = query_as(
+        "SELECT station_id, route_id, route_expected_start_time, sequence,
+                real_arrival, expected_arrival, real_departure, expected_departure
+         FROM stops
+         WHERE route_id = $1 AND route_expected_start_time = $2 AND sequence = $3",
+    )
+    .bind(&route_id)
+    .bind(expected_start_time)
+    .bind(sequence)
+    .fetch_optional(&mut *transaction)
+    .await?;
+
+    let Some(previous) = previous else {
+        return Err(StopCorrectionError::StopNotFound);
+    };
+
+    query(
+        "UPDATE stops
+         SET station_id = COALESCE($1, station_id),
+             expected_arrival = COALESCE($2, expected_arrival),
+             expected_departure = COALESCE($3, expected_departure)
+         WHERE route_id = $4 AND route_expected_start_time = $5 AND sequence = $6",
+    )
+    .bind(&request.station_id)
+    .bind(request.expected_arrival)
+    .bind(request.expected_departure)
+    .bind(&route_id)
+    .bind(expected_start_time)
+    .bind(sequence)
+    .execute(&mut *transaction)
+    .await?;
+
+    record_correction(&mut transaction, &previous, "edit", &request.reason).await?;
+
+    transaction.commit().await?;
+
+    Ok(())
+}
+
+/// A fix for a single stop's *observed* arrival/departure time, for after the
+/// run has already started or finished — unlike [`StopCorrectionRequest`],
+/// which only ever touches planner data on a run that hasn't started yet.
+#[derive(Debug, Deserialize)]
+pub struct RealTimeCorrectionRequest {
+    pub numeric_id: i64,
+    pub sequence: i16,
+    pub real_arrival: Option<DateTime<Utc>>,
+    pub real_departure: Option<DateTime<Utc>>,
+    /// Required so corrections show up in `stop_corrections` with context for
+    /// whoever reviews them later.
+    pub reason: String,
+}
+
+/// Looks up the DB `id`/`expected_start_time` a run's `numeric_id` maps to,
+/// with no restriction on whether the run has started — unlike
+/// [`lookup_unstarted_run`], a real-time correction is only ever needed once
+/// there's an observation to fix.
+async fn lookup_run(
+    executor: impl sqlx::PgExecutor<'_>,
+    numeric_id: i64,
+) -> Result<(String, DateTime<Utc>), StopCorrectionError> {
+    let route: Option<(String, DateTime<Utc>)> =
+        query_as("SELECT id, expected_start_time FROM routes WHERE numeric_id = $1")
+            .bind(numeric_id)
+            .fetch_optional(executor)
+            .await?;
+
+    route.ok_or(StopCorrectionError::RouteNotFound)
+}
+
+/// Fixes a known-wrong observed arrival/departure time on a stop, recording
+/// the stop's prior state in `stop_corrections` the same way [`correct_stop`]
+/// does.
+#[tracing::instrument(err, skip(pool))]
+pub async fn correct_real_time(
+    pool: &Pool<Postgres>,
+    request: RealTimeCorrectionRequest,
+) -> Result<(), StopCorrectionError> {
+    let mut transaction = pool.begin().await?;
+
+    let (route_id, expected_start_time) = lookup_run(&mut *transaction, request.numeric_id).await?;
+
+    let previous: Option<StopDb> = query_as(
+        "SELECT station_id, route_id, route_expected_start_time, sequence,
+                real_arrival, expected_arrival, real_departure, expected_departure
+         FROM stops
+         WHERE route_id = $1 AND route_expected_start_time = $2 AND sequence = $3",
+    )
+    .bind(&route_id)
+    .bind(expected_start_time)
+    .bind(request.sequence)
+    .fetch_optional(&mut *transaction)
+    .await?;
+
+    let Some(previous) = previous else {
+        return Err(StopCorrectionError::StopNotFound);
+    };
+
+    query(
+        "UPDATE stops
+         SET real_arrival = COALESCE($1, real_arrival),
+             real_departure = COALESCE($2, real_departure)
+         WHERE route_id = $3 AND route_expected_start_time = $4 AND sequence = $5",
+    )
+    .bind(request.real_arrival)
+    .bind(request.real_departure)
+    .bind(&route_id)
+    .bind(expected_start_time)
+    .bind(request.sequence)
+    .execute(&mut *transaction)
+    .await?;
+
+    record_correction(&mut transaction, &previous, "real_time_edit", &request.reason).await?;
+
+    transaction.commit().await?;
+
+    Ok(())
+}
+
+/// A fix for a run's *observed* start/end time, for the same kind of scraper
+/// glitch [`RealTimeCorrectionRequest`] fixes at the stop level, but applied
+/// to the run as a whole.
+#[derive(Debug, Deserialize)]
+pub struct RouteTimeCorrectionRequest {
+    pub real_start_time: Option<DateTime<Utc>>,
+    pub real_end_time: Option<DateTime<Utc>>,
+    /// Required so corrections show up in `stop_corrections` with context for
+    /// whoever reviews them later.
+    pub reason: String,
+}
+
+/// Fixes a known-wrong observed start/end time on a run, recording the run's
+/// prior state in `stop_corrections` with no `sequence` (it isn't a per-stop
+/// edit) the same way [`correct_real_time`] does for a single stop.
+#[tracing::instrument(err, skip(pool))]
+pub async fn correct_route_real_time(
+    pool: &Pool<Postgres>,
+    numeric_id: i64,
+    request: RouteTimeCorrectionRequest,
+) -> Result<(), StopCorrectionError> {
+    let mut transaction = pool.begin().await?;
+
+    #[derive(sqlx::FromRow)]
+    struct PreviousRouteTimes {
+        id: String,
+        expected_start_time: DateTime<Utc>,
+        real_start_time: Option<DateTime<Utc>>,
+        real_end_time: Option<DateTime<Utc>>,
+    }
+
+    let previous: Option<PreviousRouteTimes> = query_as(
+        "SELECT id, expected_start_time, real_start_time, real_end_time FROM routes WHERE numeric_id = $1",
+    )
+    .bind(numeric_id)
+    .fetch_optional(&mut *transaction)
+    .await?;
+
+    let Some(PreviousRouteTimes { id: route_id, expected_start_time, real_start_time, real_end_time }) = previous
+    else {
+        return Err(StopCorrectionError::RouteNotFound);
+    };
+
+    query(
+        "UPDATE routes
+         SET real_start_time = COALESCE($1, real_start_time),
+             real_end_time = COALESCE($2, real_end_time)
+         WHERE id = $3 AND expected_start_time = $4",
+    )
+    .bind(request.real_start_time)
+    .bind(request.real_end_time)
+    .bind(&route_id)
+    .bind(expected_start_time)
+    .execute(&mut *transaction)
+    .await?;
+
+    let previous_state = json!({
+        "real_start_time": real_start_time,
+        "real_end_time": real_end_time,
+    });
+
+    query(
+        "INSERT INTO stop_corrections (
+            route_id, route_expected_start_time, sequence, action, previous_state, reason
+        ) VALUES ($1, $2, NULL, $3, $4, $5)",
+    )
+    .bind(&route_id)
+    .bind(expected_start_time)
+    .bind("route_real_time_edit")
+    .bind(previous_state)
+    .bind(&request.reason)
+    .execute(&mut *transaction)
+    .await?;
+
+    transaction.commit().await?;
+
+    Ok(())
+}
+
+/// Removes a phantom stop (one the planner included that doesn't actually
+/// exist on the run) from a not-yet-started run.
+#[tracing::instrument(err, skip(pool))]
+pub async fn delete_stop(
+    pool: &Pool<Postgres>,
+    numeric_id: i64,
+    sequence: i16,
+    reason: String,
+) -> Result<(), StopCorrectionError> {
+    let mut transaction = pool.begin().await?;
+
+    let (route_id, expected_start_time) = lookup_unstarted_run(&mut *transaction, numeric_id).await?;
+
+    let previous: Option<StopDb> = query_as(
+        "SELECT station_id, route_id, route_expected_start_time, sequence,
+                real_arrival, expected_arrival, real_departure, expected_departure
+         FROM stops
+         WHERE route_id = $1 AND route_expected_start_time = $2 AND sequence = $3",
+    )
+    .bind(&route_id)
+    .bind(expected_start_time)
+    .bind(sequence)
+    .fetch_optional(&mut *transaction)
+    .await?;
+
+    let Some(previous) = previous else {
+        return Err(StopCorrectionError::StopNotFound);
+    };
+
+    query("DELETE FROM stops WHERE route_id = $1 AND route_expected_start_time = $2 AND sequence = $3")
+        .bind(&route_id)
+        .bind(expected_start_time)
+        .bind(sequence)
+        .execute(&mut *transaction)
+        .await?;
+
+    record_correction(&mut transaction, &previous, "delete", &reason).await?;
+
+    transaction.commit().await?;
+
+    Ok(())
+}
+
+async fn record_correction(
+    transaction: &mut sqlx::Transaction<'_, Postgres>,
+    previous: &StopDb,
+    action: &str,
+    reason: &str,
+) -> Result<(), sqlx::Error> {
+    let previous_state = json!({
+        "station_id": previous.station_id,
+        "sequence": previous.sequence,
+        "real_arrival": previous.real_arrival,
+        "expected_arrival": previous.expected_arrival,
+        "real_departure": previous.real_departure,
+        "expected_departure": previous.expected_departure,
+    });
+
+    query(
+        "INSERT INTO stop_corrections (
+            route_id, route_expected_start_time, sequence, action, previous_state, reason
+        ) VALUES ($1, $2, $3, $4, $5, $6)",
+    )
+    .bind(&previous.route_id)
+    .bind(previous.route_expected_start_time)
+    .bind(previous.sequence)
+    .bind(action)
+    .bind(previous_state)
+    .bind(reason)
+    .execute(&mut **transaction)
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,324 @@
+//! HTTP API served alongside the background fetcher/checker tasks: one
+//! module per resource (`routes`, `stations`, `stats`, `admin`, ...), nested
+//! under a versioned `/api/v1` router (see [`api_v1_router`]) and sharing one
+//! [`AppState`] for the pool and every in-process broadcast channel.
+pub mod admin;
+pub mod admin_ui;
+pub mod bundle;
+pub mod cache;
+pub mod connections;
+pub mod db_health;
+pub mod delays;
+pub mod diagnostics;
+pub mod early_departures;
+pub mod embed;
+pub mod etag;
+pub mod export;
+pub mod feeds;
+pub mod geo;
+pub mod kpis;
+pub mod localities;
+pub mod meta;
+pub mod pagination;
+pub mod position;
+pub mod problem;
+pub mod rate_limit;
+pub mod routes;
+pub mod snapshots;
+pub mod stations;
+pub mod stats;
+pub mod status;
+pub mod stop_skips;
+pub mod traces;
+pub mod travel_time;
+pub mod usage_metrics;
+pub mod versioning;
+pub mod ws;
+
+use axum::{
+    http::{HeaderName, HeaderValue},
+    routing::{delete, get, patch, post},
+    Router,
+};
+use sqlx::{Pool, Postgres};
+use tower_http::{
+    cors::{AllowOrigin, Any, CorsLayer},
+    request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer},
+    trace::TraceLayer,
+};
+use tracing::warn;
+
+use self::cache::ResponseCache;
+use self::rate_limit::{RateLimitConfig, RateLimiter};
+use crate::background_services::{
+    active_monitors::ActiveMonitors, delay_broadcast::DelayUpdates,
+    delay_checker::DelayResponseCache, live_comparison::LiveComparisons,
+    monitor_control::MonitorControl, readiness::Readiness, watchlist::WatchList,
+};
+
+/// Echoed onto every response so an error reported by a user can be matched
+/// back to the request's tracing span/OpenTelemetry trace in the logs.
+const REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
+
+/// Shared state handed to every API handler.
+#[derive(Clone)]
+pub struct AppState {
+    pub pool: Pool<Postgres>,
+    pub cache: ResponseCache,
+    pub monitor_control: MonitorControl,
+    /// Bearer token gating `/admin/*`. The panel 404s while this is `None`.
+    pub admin_token: Option<String>,
+    pub live_comparisons: LiveComparisons,
+    pub delay_response_cache: DelayResponseCache,
+    pub delay_updates: DelayUpdates,
+    pub watch_list: WatchList,
+    /// Handle for [`status::report`] to read how many routes the delay
+    /// checker is actively polling right now.
+    pub active_monitors: ActiveMonitors,
+    pub rate_limiter: RateLimiter,
+    pub rate_limit_config: RateLimitConfig,
+    /// Origins the Svelte client may call the API from. Empty disables CORS
+    /// entirely, which is fine when the client is served same-origin.
+    pub cors_allowed_origins: Vec<String>,
+    pub readiness: Readiness,
+    /// Whether the admin panel is additionally (or instead) reachable via
+    /// the mutual-TLS listener in [`crate::mtls`]. Purely informational here
+    /// — the bearer-token gate is unaffected either way.
+    pub admin_mtls_enabled: bool,
+    /// Gates [`usage_metrics::track`]; see [`crate::config::Config::usage_metrics_enabled`].
+    pub usage_metrics_enabled: bool,
+    /// Signs `/embed/route/:n` widget URLs. The endpoint 404s while this is
+    /// `None`, same as `admin_token` for the admin panel.
+    pub embed_signing_secret: Option<String>,
+}
+
+impl AppState {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        pool: Pool<Postgres>,
+        monitor_control: MonitorControl,
+        admin_token: Option<String>,
+        live_comparisons: LiveComparisons,
+        delay_response_cache: DelayResponseCache,
+        delay_updates: DelayUpdates,
+        watch_list: WatchList,
+        active_monitors: ActiveMonitors,
+        rate_limit_config: RateLimitConfig,
+        cors_allowed_origins: Vec<String>,
+        readiness: Readiness,
+        admin_mtls_enabled: bool,
+        usage_metrics_enabled: bool,
+        embed_signing_secret: Option<String>,
+    ) -> Self {
+        AppState {
+            pool,
+            cache: ResponseCache::default(),
+            monitor_control,
+            admin_token,
+            live_comparisons,
+            delay_response_cache,
+            delay_updates,
+            watch_list,
+            active_monitors,
+            rate_limiter: RateLimiter::new(),
+            rate_limit_config,
+            cors_allowed_origins,
+            readiness,
+            admin_mtls_enabled,
+            usage_metrics_enabled,
+            embed_signing_secret,
+        }
+    }
+}
+
+/// The actual route table, free of any version prefix so it can be nested
+/// under both the canonical `/api/v1` and the legacy unprefixed `/api` mount.
+/// A future `/api/v2` would nest its own differently-behaving router
+/// alongside this one rather than branching inside it.
+/// Every JSON admin endpoint, nested at `/admin` inside [`api_v1_router`] and
+/// gated with [`admin_ui::require_admin_token`] by [`router`] — the same gate
+/// [`admin_panel_router`] gets, so an `ADMIN_TOKEN`/`X-Admin-Token` is
+/// required here too, not just for the HTML dashboard.
+fn admin_api_router() -> Router<AppState> {
+    Router::new()
+        .route("/snapshots", get(snapshots::list))
+        .route("/snapshots/:tag/:file", get(snapshots::download))
+        .route("/monitor/adhoc", post(admin::monitor_adhoc))
+        .route("/slow-queries", get(admin::slow_queries))
+        .route("/diagnostics", get(diagnostics::report))
+        .route("/usage-metrics", get(admin::usage_metrics))
+        .route("/recheck/:route_number", post(admin::recheck))
+        .route("/wake-schedule-report", get(admin::wake_schedule_report))
+        .route(
+            "/routes/:numeric_id/stops/:sequence",
+            patch(admin::correct_stop).delete(admin::delete_stop),
+        )
+        .route(
+            "/routes/:numeric_id/stops/:sequence/times",
+            patch(admin::correct_stop_real_time),
+        )
+        .route(
+            "/routes/:numeric_id/times",
+            patch(admin::correct_route_real_time),
+        )
+        .route("/data-issues", get(admin::data_issues))
+        .route(
+            "/corrections/real-time/bulk",
+            post(admin::bulk_correct_real_time),
+        )
+        .route("/weather-events", post(admin::record_weather_event))
+        .route(
+            "/routes/:route_number/tags",
+            get(admin::list_route_tags).post(admin::tag_route),
+        )
+        .route(
+            "/routes/:route_number/tags/:tag",
+            delete(admin::untag_route),
+        )
+        .route(
+            "/backfill/route-narratives",
+            post(admin::backfill_narrative_summaries),
+        )
+        .route(
+            "/backfill/route-narratives/status",
+            get(admin::backfill_narrative_summaries_status),
+        )
+}
+
+fn api_v1_router(admin_api_router: Router<AppState>) -> Router<AppState> {
+    Router::new()
+        .route("/summary", get(db_health::summary))
+        .route("/live", get(db_health::live))
+        .route(
+            "/observation-lag-histogram",
+            get(db_health::observation_lag_histogram),
+        )
+        .route("/delays/live", get(delays::live))
+        .route("/delays", get(delays::history))
+        .route("/delays/stream", get(delays::stream))
+        .route("/trains/status", post(delays::bulk_status))
+        .route("/trains/:route_number/position", get(position::estimated_position))
+        .route("/early-departures", get(early_departures::recent))
+        .route("/early-departures/counts", get(early_departures::counts))
+        .route("/export/routes.csv", get(export::routes_csv))
+        .route("/export/stops.csv", get(export::stops_csv))
+        .route("/geo/stations.geojson", get(geo::stations_geojson))
+        .route("/geo/routes/:id", get(geo::route_geojson))
+        .route("/leaderboard", get(db_health::leaderboard))
+        .route("/heatmap", get(db_health::cached_payload))
+        .nest("/admin", admin_api_router)
+        .route("/kpis", get(kpis::kpis))
+        .route("/routes", get(routes::list))
+        .route("/routes/:id", get(routes::get_route))
+        .route(
+            "/routes/:id/:expected_start_time",
+            get(routes::get_route_detail),
+        )
+        .route("/routes/:route_number/watch", post(routes::watch))
+        .route("/routes/:route_number/calendar.ics", get(routes::calendar_ics))
+        .route("/routes/:route_number/yoy", get(routes::yoy))
+        .route("/stats/slots", get(stats::slots))
+        .route("/stats/heatmap", get(stats::heatmap))
+        .route("/stats/on-time", get(stats::on_time_percentage))
+        .route("/stats/od", get(stats::od))
+        .route("/stats/routes/:route_number", get(stats::route_stats))
+        .route("/stats/stock-class", get(stats::stock_class))
+        .route("/stats/accessibility", get(stats::accessibility))
+        .route("/stats/stations/:id", get(stats::station_stats))
+        .route("/stations", get(stations::list))
+        .route("/stations/autocomplete", get(stations::autocomplete))
+        .route("/stations/nearby", get(stations::nearby))
+        .route("/stations/:id", get(stations::get))
+        .route("/stations/:id/history", get(stations::history))
+        .route("/stations/:id/calendar", get(stations::calendar))
+        .route("/stations/:id/timetable", get(stations::timetable))
+        .route("/stations/:id/recent-platforms", get(stations::recent_platforms))
+        .route("/status", get(status::report))
+        .route("/stop-skips/counts", get(stop_skips::counts))
+        .route("/localities/:id/stats", get(localities::stats))
+        .route("/meta/dictionary", get(meta::dictionary))
+        .route("/runs/:trip_id/trace.geojson", get(traces::trace_geojson))
+        .route("/runs/:trip_id/bundle.zip", get(bundle::bundle_zip))
+        .route("/travel-time", get(travel_time::travel_time))
+        .route("/connections", get(connections::connections))
+}
+
+/// The admin panel's routes, free of any auth layer. [`router`] gates these
+/// with [`admin_ui::require_admin_token`] for the default plain-HTTP
+/// deployment; [`crate::mtls`] serves this same router unlayered over a
+/// client-cert-verifying TLS listener instead, for deployments that prefer
+/// mutual TLS to a shared bearer token.
+pub fn admin_panel_router() -> Router<AppState> {
+    Router::new()
+        .route("/admin", get(admin_ui::dashboard))
+        .route("/admin/status", get(admin_ui::status))
+        .route("/admin/pause", post(admin_ui::pause))
+        .route("/admin/resume", post(admin_ui::resume))
+        .route("/admin/refetch", post(admin_ui::refetch))
+}
+
+pub fn router(state: AppState) -> Router {
+    let admin_router = admin_panel_router().layer(axum::middleware::from_fn_with_state(
+        state.clone(),
+        admin_ui::require_admin_token,
+    ));
+
+    let gated_admin_api_router = admin_api_router().layer(axum::middleware::from_fn_with_state(
+        state.clone(),
+        admin_ui::require_admin_token,
+    ));
+
+    let allowed_origins: Vec<HeaderValue> = state
+        .cors_allowed_origins
+        .iter()
+        .filter_map(|origin| match HeaderValue::from_str(origin) {
+            Ok(value) => Some(value),
+            Err(e) => {
+                warn!("ignoring invalid CORS_ALLOWED_ORIGINS entry {origin:?}: {e}");
+                None
+            }
+        })
+        .collect();
+
+    let api_router = Router::new()
+        .nest("/api/v1", api_v1_router(gated_admin_api_router.clone()))
+        .nest(
+            "/api",
+            api_v1_router(gated_admin_api_router).layer(axum::middleware::from_fn(versioning::mark_deprecated)),
+        )
+        .layer(axum::middleware::from_fn(versioning::negotiate_version))
+        .layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            rate_limit::enforce,
+        ))
+        .layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            usage_metrics::track,
+        ))
+        .layer(axum::middleware::from_fn(etag::add_etag))
+        .layer(
+            CorsLayer::new()
+                .allow_origin(AllowOrigin::list(allowed_origins))
+                .allow_methods(Any)
+                .allow_headers(Any),
+        );
+
+    Router::new()
+        .merge(api_router)
+        .route("/readyz", get(db_health::readyz))
+        .route("/feeds/today.json", get(feeds::today))
+        .route("/ws/delays", get(ws::delays))
+        .route("/embed/route/:route_number", get(embed::widget))
+        .layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            db_health::serve_stale_on_db_outage,
+        ))
+        .merge(admin_router)
+        .layer(PropagateRequestIdLayer::new(REQUEST_ID_HEADER.clone()))
+        .layer(TraceLayer::new_for_http())
+        .layer(SetRequestIdLayer::new(
+            REQUEST_ID_HEADER.clone(),
+            MakeRequestUuid,
+        ))
+        .with_state(state)
+}
@@ -0,0 +1,405 @@
+//! Admin-only endpoints for actions that fall outside the normal planner-driven flow.
+use axum::{
+    extract::{OriginalUri, Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Deserialize;
+use serde_json::json;
+use sqlx::prelude::FromRow;
+use tokio::spawn;
+use tracing::error;
+
+use crate::{
+    admin,
+    background_services::{
+        delay_checker::{monitor_adhoc_route, recheck_route_now, RecheckError, RecheckOutcome},
+        wake_schedule_stats,
+    },
+    backfill,
+    corrections_upload::{self, RowError, UploadSummary},
+    query_stats, route_tags,
+};
+#[cfg(feature = "weather")]
+use crate::weather;
+
+use super::{
+    pagination::{paginate, PageParams},
+    usage_metrics, AppState,
+};
+
+/// Creates a synthetic run for a train missing from the planner API (special
+/// events, seasonal services) and immediately starts monitoring it.
+pub async fn monitor_adhoc(
+    State(state): State<AppState>,
+    Json(request): Json<admin::AdhocRouteRequest>,
+) -> Response {
+    match admin::create_adhoc_route(&state.pool, request).await {
+        Ok(route) => {
+            let route_id = route.id.clone();
+            spawn(monitor_adhoc_route(
+                route,
+                state.pool.clone(),
+                state.live_comparisons.clone(),
+                state.delay_response_cache.clone(),
+                state.delay_updates.clone(),
+                state.watch_list.clone(),
+                state.active_monitors.clone(),
+            ));
+            (StatusCode::ACCEPTED, Json(json!({ "route_id": route_id }))).into_response()
+        }
+        Err(e) => {
+            error!("error creating adhoc route: {e:?}");
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct WeatherEventRequest {
+    date: chrono::NaiveDate,
+    bad_weather: bool,
+}
+
+/// Records whether `date` had bad weather, for [`crate::api::stats::route_stats`]
+/// to split punctuality by. There's no live weather feed to enrich runs
+/// automatically, so this is operator-curated the same way `data_issues` are.
+#[cfg(feature = "weather")]
+pub async fn record_weather_event(
+    State(state): State<AppState>,
+    Json(request): Json<WeatherEventRequest>,
+) -> Response {
+    match weather::record_weather_event(&state.pool, request.date, request.bad_weather).await {
+        Ok(()) => StatusCode::NO_CONTENT.into_response(),
+        Err(e) => {
+            error!("error recording weather event: {e:?}");
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+/// Stub for builds without the `weather` feature, so the route table doesn't
+/// have to change shape depending on what's compiled in.
+#[cfg(not(feature = "weather"))]
+pub async fn record_weather_event(Json(_request): Json<WeatherEventRequest>) -> Response {
+    StatusCode::NOT_FOUND.into_response()
+}
+
+#[derive(Deserialize)]
+pub struct TagRequest {
+    tag: String,
+}
+
+/// Tags a route number (e.g. "Zagreb commuter") for [`crate::api::stats`] and
+/// the leaderboard to filter/group by, instead of hardcoding corridors.
+pub async fn tag_route(
+    State(state): State<AppState>,
+    Path(route_number): Path<i32>,
+    Json(request): Json<TagRequest>,
+) -> Response {
+    match route_tags::tag_route(&state.pool, route_number, &request.tag).await {
+        Ok(()) => StatusCode::NO_CONTENT.into_response(),
+        Err(e) => {
+            error!("error tagging route {route_number}: {e:?}");
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+pub async fn untag_route(State(state): State<AppState>, Path((route_number, tag)): Path<(i32, String)>) -> Response {
+    match route_tags::untag_route(&state.pool, route_number, &tag).await {
+        Ok(()) => StatusCode::NO_CONTENT.into_response(),
+        Err(e) => {
+            error!("error untagging route {route_number}: {e:?}");
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+pub async fn list_route_tags(State(state): State<AppState>, Path(route_number): Path<i32>) -> Response {
+    match route_tags::list_tags(&state.pool, route_number).await {
+        Ok(tags) => Json(tags).into_response(),
+        Err(e) => {
+            error!("error listing tags for route {route_number}: {e:?}");
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+/// Per-named-query timing collected since the process started, slowest
+/// average first, so a hotspot introduced by a new endpoint is visible
+/// without reaching for an external metrics stack.
+pub async fn slow_queries() -> Response {
+    Json(query_stats::snapshot()).into_response()
+}
+
+/// Per-endpoint request counts/latencies collected since the process
+/// started, busiest first. Empty unless `USAGE_METRICS_ENABLED` is set.
+pub async fn usage_metrics() -> Response {
+    Json(usage_metrics::snapshot()).into_response()
+}
+
+/// Per-route average/max delay between a monitor task waking up and it
+/// getting its first useful delay reading, worst routes first, with a
+/// suggested extra lead time that would have closed the gap. Empty until
+/// routes with at least one completed wake-up have been monitored.
+pub async fn wake_schedule_report() -> Response {
+    Json(wake_schedule_stats::snapshot()).into_response()
+}
+
+/// Immediately polls and persists a status update for `route_number`'s
+/// current run, bypassing the delay checker's regular 60-second cadence.
+/// Useful for debugging the scraper against a train that's running right now.
+pub async fn recheck(State(state): State<AppState>, Path(route_number): Path<i32>) -> Response {
+    match recheck_route_now(
+        route_number,
+        &state.pool,
+        &state.live_comparisons,
+        &state.delay_response_cache,
+        &state.delay_updates,
+    )
+    .await
+    {
+        Ok(RecheckOutcome::Updated { minutes_late }) => Json(json!({ "minutes_late": minutes_late })).into_response(),
+        Ok(RecheckOutcome::TrainNotEvidented) => (StatusCode::OK, Json(json!({ "train_not_evidented": true }))).into_response(),
+        Ok(RecheckOutcome::NoDelayData) => (StatusCode::OK, Json(json!({ "no_delay_data": true }))).into_response(),
+        Err(RecheckError::RouteNotFound) => StatusCode::NOT_FOUND.into_response(),
+        Err(e @ RecheckError::UnisysError) => {
+            error!("error rechecking route {route_number}: {e:?}");
+            StatusCode::BAD_GATEWAY.into_response()
+        }
+        Err(RecheckError::Other(e)) => {
+            error!("error rechecking route {route_number}: {e:?}");
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+fn stop_correction_error_response(e: admin::StopCorrectionError) -> Response {
+    match e {
+        admin::StopCorrectionError::RouteNotFound | admin::StopCorrectionError::StopNotFound => {
+            StatusCode::NOT_FOUND.into_response()
+        }
+        admin::StopCorrectionError::RouteAlreadyStarted => StatusCode::CONFLICT.into_response(),
+        admin::StopCorrectionError::Sqlx(e) => {
+            error!("error applying stop correction: {e:?}");
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct StopTimeCorrectionRequest {
+    real_arrival: Option<chrono::DateTime<chrono::Utc>>,
+    real_departure: Option<chrono::DateTime<chrono::Utc>>,
+    reason: String,
+}
+
+/// Fixes a known-wrong observed arrival/departure time on a stop, after the
+/// run has already started or finished.
+pub async fn correct_stop_real_time(
+    State(state): State<AppState>,
+    Path((numeric_id, sequence)): Path<(i64, i16)>,
+    Json(request): Json<StopTimeCorrectionRequest>,
+) -> Response {
+    let request = admin::RealTimeCorrectionRequest {
+        numeric_id,
+        sequence,
+        real_arrival: request.real_arrival,
+        real_departure: request.real_departure,
+        reason: request.reason,
+    };
+    match admin::correct_real_time(&state.pool, request).await {
+        Ok(()) => StatusCode::NO_CONTENT.into_response(),
+        Err(e) => stop_correction_error_response(e),
+    }
+}
+
+/// Fixes a known-wrong observed start/end time on a run, after it has
+/// already started or finished.
+pub async fn correct_route_real_time(
+    State(state): State<AppState>,
+    Path(numeric_id): Path<i64>,
+    Json(request): Json<admin::RouteTimeCorrectionRequest>,
+) -> Response {
+    match admin::correct_route_real_time(&state.pool, numeric_id, request).await {
+        Ok(()) => StatusCode::NO_CONTENT.into_response(),
+        Err(e) => stop_correction_error_response(e),
+    }
+}
+
+/// Fixes a wrong stop sequence/station/time on a run that hasn't started yet.
+pub async fn correct_stop(
+    State(state): State<AppState>,
+    Path((numeric_id, sequence)): Path<(i64, i16)>,
+    Json(request): Json<admin::StopCorrectionRequest>,
+) -> Response {
+    match admin::correct_stop(&state.pool, numeric_id, sequence, request).await {
+        Ok(()) => StatusCode::NO_CONTENT.into_response(),
+        Err(e) => stop_correction_error_response(e),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct DeleteStopRequest {
+    reason: String,
+}
+
+/// Removes a phantom stop the planner shouldn't have included, from a run
+/// that hasn't started yet.
+pub async fn delete_stop(
+    State(state): State<AppState>,
+    Path((numeric_id, sequence)): Path<(i64, i16)>,
+    Json(request): Json<DeleteStopRequest>,
+) -> Response {
+    match admin::delete_stop(&state.pool, numeric_id, sequence, request.reason).await {
+        Ok(()) => StatusCode::NO_CONTENT.into_response(),
+        Err(e) => stop_correction_error_response(e),
+    }
+}
+
+/// Bulk-applies known-wrong observed arrival/departure times from an
+/// uploaded file: JSON (a `CorrectionRow` array) when `Content-Type` is
+/// `application/json`, otherwise CSV. Every row is applied independently
+/// through [`admin::correct_real_time`], so one bad row doesn't roll back
+/// the rest of the batch.
+pub async fn bulk_correct_real_time(State(state): State<AppState>, headers: HeaderMap, body: String) -> Response {
+    let is_json = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|ct| ct.starts_with("application/json"));
+
+    let rows = if is_json {
+        serde_json::from_str::<Vec<corrections_upload::CorrectionRow>>(&body).map_err(|e| e.to_string())
+    } else {
+        corrections_upload::parse_csv(&body).map_err(|e| e.to_string())
+    };
+
+    let rows = match rows {
+        Ok(rows) => rows,
+        Err(e) => return (StatusCode::BAD_REQUEST, e).into_response(),
+    };
+
+    let mut summary = UploadSummary { applied: 0, errors: vec![] };
+
+    for row in rows {
+        let numeric_id = row.numeric_id;
+        let sequence = row.sequence;
+
+        match admin::correct_real_time(&state.pool, row.into()).await {
+            Ok(()) => summary.applied += 1,
+            Err(e) => summary.errors.push(RowError { numeric_id, sequence, error: e.to_string() }),
+        }
+    }
+
+    Json(summary).into_response()
+}
+
+#[derive(FromRow, serde::Serialize)]
+struct DataIssue {
+    kind: String,
+    detail: serde_json::Value,
+    detected_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Findings from the last nightly data-integrity sweep.
+pub async fn data_issues(
+    State(state): State<AppState>,
+    Query(page_params): Query<PageParams>,
+    OriginalUri(uri): OriginalUri,
+) -> Response {
+    let issues: Result<Vec<DataIssue>, _> = query_stats::timed(
+        "data_issues",
+        sqlx::query_as("SELECT kind, detail, detected_at FROM data_issues ORDER BY detected_at DESC")
+            .fetch_all(&state.pool),
+    )
+    .await;
+
+    match issues {
+        Ok(issues) => paginate(issues, &page_params, &uri),
+        Err(e) => {
+            error!("error reading data issues: {e:?}");
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+/// Env var gating [`backfill_narrative_summaries`], so the backfill can be
+/// rolled out deliberately rather than accidentally triggered by whoever
+/// finds the endpoint.
+const BACKFILL_ROUTE_NARRATIVES_FLAG: &str = "BACKFILL_ROUTE_NARRATIVES_ENABLED";
+
+/// Caps [`backfill_narrative_summaries`] to this many 500-row batches per
+/// HTTP request, so a large backlog is worked off over several calls instead
+/// of one request blocking until the whole thing is done.
+const BACKFILL_ROUTE_NARRATIVES_MAX_BATCHES_PER_CALL: u32 = 20;
+
+/// Fills in `narrative_summary` for runs that finished before that column
+/// existed, using [`backfill::run_backfill`] so it catches up in small
+/// batches instead of locking the whole table at once. Per-stop detail
+/// (which stop lost the most time) isn't recoverable this way once the
+/// in-memory run state is gone, so the backfilled text is the coarser
+/// "Arrived N min late" summary derived from the stored route totals.
+///
+/// Stops after [`BACKFILL_ROUTE_NARRATIVES_MAX_BATCHES_PER_CALL`] batches
+/// even if rows remain — `complete: false` in the response means call this
+/// again (see [`backfill_narrative_summaries_status`] to check how much is
+/// left).
+pub async fn backfill_narrative_summaries(State(state): State<AppState>) -> Response {
+    if !backfill::migration_flag_enabled(BACKFILL_ROUTE_NARRATIVES_FLAG) {
+        return (
+            StatusCode::CONFLICT,
+            Json(json!({ "error": format!("set {BACKFILL_ROUTE_NARRATIVES_FLAG}=true to run this backfill") })),
+        )
+            .into_response();
+    }
+
+    let result = backfill::run_backfill(
+        &state.pool,
+        "UPDATE routes
+         SET narrative_summary = CASE
+             WHEN final_delay_minutes IS NULL OR final_delay_minutes <= 0 THEN 'Arrived on time'
+             ELSE 'Arrived ' || final_delay_minutes || ' min late'
+         END
+         WHERE (id, expected_start_time) IN (
+             SELECT id, expected_start_time FROM routes
+             WHERE real_end_time IS NOT NULL AND narrative_summary IS NULL
+             LIMIT 500
+         )",
+        BACKFILL_ROUTE_NARRATIVES_MAX_BATCHES_PER_CALL,
+    )
+    .await;
+
+    match result {
+        Ok(progress) => Json(json!({
+            "batches_run": progress.batches_run,
+            "rows_updated": progress.rows_updated,
+            "complete": progress.complete,
+        }))
+        .into_response(),
+        Err(e) => {
+            error!("error backfilling narrative summaries: {e:?}");
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+/// Reports how many completed runs still lack a `narrative_summary`, so an
+/// operator can confirm [`backfill_narrative_summaries`] has actually caught
+/// up before relying on the column being fully populated.
+pub async fn backfill_narrative_summaries_status(State(state): State<AppState>) -> Response {
+    let remaining = backfill::count_mismatches(
+        &state.pool,
+        "SELECT count(*) FROM routes WHERE real_end_time IS NOT NULL AND narrative_summary IS NULL",
+    )
+    .await;
+
+    match remaining {
+        Ok(remaining) => Json(json!({ "remaining": remaining })).into_response(),
+        Err(e) => {
+            error!("error checking narrative summary backfill status: {e:?}");
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
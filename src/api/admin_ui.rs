@@ -0,0 +1,113 @@
+//! Embedded admin panel: a single unauthenticated-by-default HTML page plus a
+//! handful of JSON endpoints it calls, for pausing/resuming the route fetcher
+//! and triggering an out-of-cycle refetch without shelling into the box.
+use axum::{
+    extract::{Request, State},
+    http::{HeaderMap, StatusCode},
+    middleware::Next,
+    response::{Html, IntoResponse, Response},
+    Json,
+};
+use serde_json::json;
+use tracing::error;
+
+use super::AppState;
+
+/// Gates every `/admin/*` route behind the `X-Admin-Token` header. The panel
+/// is disabled (404, not 401, so its existence isn't advertised) unless
+/// `admin_token` is configured.
+pub async fn require_admin_token(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(admin_token) = &state.admin_token else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+
+    let provided = headers
+        .get("X-Admin-Token")
+        .and_then(|v| v.to_str().ok());
+
+    if provided != Some(admin_token.as_str()) {
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+
+    next.run(request).await
+}
+
+pub async fn dashboard() -> Html<&'static str> {
+    Html(
+        r#"<!DOCTYPE html>
+<html>
+<head><title>hzpp-delay-stats admin</title></head>
+<body>
+<h1>Route fetcher</h1>
+<p>Status: <span id="status">loading...</span></p>
+<button onclick="post('/admin/pause')">Pause</button>
+<button onclick="post('/admin/resume')">Resume</button>
+<button onclick="post('/admin/refetch')">Refetch now</button>
+<h2>Recent failures</h2>
+<ul id="failures"></ul>
+<h2>Endpoint usage</h2>
+<table id="usage"><thead><tr><th>Method</th><th>Route</th><th>Count</th><th>Avg ms</th><th>Max ms</th></tr></thead><tbody></tbody></table>
+<script>
+const token = prompt("Admin token");
+const headers = { "X-Admin-Token": token };
+
+async function post(path) {
+    await fetch(path, { method: "POST", headers });
+    refresh();
+}
+
+async function refresh() {
+    const res = await fetch("/admin/status", { headers });
+    const data = await res.json();
+    document.getElementById("status").textContent = data.paused ? "paused" : "running";
+    document.getElementById("failures").innerHTML = data.recent_failures
+        .map(f => `<li>${f.occurred_at}: ${f.message}</li>`)
+        .join("");
+
+    const usageRes = await fetch("/api/v1/admin/usage-metrics", { headers });
+    const usage = await usageRes.json();
+    document.querySelector('#usage tbody').innerHTML = usage
+        .map(u => `<tr><td>${u.method}</td><td>${u.path}</td><td>${u.count}</td><td>${u.avg_ms.toFixed(1)}</td><td>${u.max_ms.toFixed(1)}</td></tr>`)
+        .join("");
+}
+
+refresh();
+</script>
+</body>
+</html>"#,
+    )
+}
+
+pub async fn status(State(state): State<AppState>) -> Response {
+    let recent_failures = state.monitor_control.recent_failures().await;
+    Json(json!({
+        "paused": state.monitor_control.is_paused(),
+        "recent_failures": recent_failures,
+    }))
+    .into_response()
+}
+
+pub async fn pause(State(state): State<AppState>) -> Response {
+    state.monitor_control.set_paused(true);
+    StatusCode::NO_CONTENT.into_response()
+}
+
+pub async fn resume(State(state): State<AppState>) -> Response {
+    state.monitor_control.set_paused(false);
+    StatusCode::NO_CONTENT.into_response()
+}
+
+pub async fn refetch(State(state): State<AppState>) -> Response {
+    match state.monitor_control.trigger_refetch().await {
+        Ok(()) => StatusCode::ACCEPTED.into_response(),
+        Err(e) => {
+            error!("error triggering manual refetch: {e:?}");
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
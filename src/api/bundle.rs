@@ -0,0 +1,205 @@
+//! Everything on record for one run, bundled into a single ZIP so a user
+//! reporting "my train's data is wrong" can attach one file to an issue
+//! instead of screenshotting several endpoints.
+use axum::{
+    extract::{Path, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+};
+use chrono::{DateTime, Utc};
+use serde_json::json;
+use sqlx::{prelude::FromRow, query_as};
+use tracing::error;
+
+use crate::{query_stats, zip_writer};
+
+use super::AppState;
+
+#[derive(FromRow)]
+struct RouteHeader {
+    id: String,
+    numeric_id: i64,
+    slug: String,
+    route_number: i32,
+    source: String,
+    destination: String,
+    expected_start_time: DateTime<Utc>,
+    expected_end_time: DateTime<Utc>,
+    real_start_time: Option<DateTime<Utc>>,
+    real_end_time: Option<DateTime<Utc>>,
+    narrative_summary: Option<String>,
+}
+
+#[derive(FromRow, serde::Serialize)]
+struct RouteStop {
+    station_id: String,
+    sequence: i16,
+    expected_arrival: DateTime<Utc>,
+    real_arrival: Option<DateTime<Utc>>,
+    expected_departure: DateTime<Utc>,
+    real_departure: Option<DateTime<Utc>>,
+}
+
+#[derive(FromRow)]
+struct CorrectionEvent {
+    /// `None` for a run-wide correction ([`crate::admin::correct_route_real_time`])
+    /// rather than one against a specific stop.
+    sequence: Option<i16>,
+    action: String,
+    reason: String,
+    corrected_at: DateTime<Utc>,
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn csv_opt<T: ToString>(value: Option<T>) -> String {
+    value.map(|v| v.to_string()).unwrap_or_default()
+}
+
+/// Bundles the run identified by `numeric_id` into a ZIP: `run.json` (route +
+/// stop detail), `observations.csv` (per-stop scheduled vs real times) and
+/// `corrections.csv` (every manual edit recorded against it in
+/// `stop_corrections`, our closest equivalent of an event log for a run).
+/// Raw upstream HTML isn't retained anywhere in this deployment, so there's
+/// no archived page to include — `README.txt` says so rather than bundling
+/// nothing silently.
+pub async fn bundle_zip(State(state): State<AppState>, Path(numeric_id): Path<i64>) -> Response {
+    let route: Result<Option<RouteHeader>, _> = query_stats::timed(
+        "bundle_route_header",
+        query_as(
+            "SELECT id, numeric_id, slug, route_number, source, destination,
+                    expected_start_time, expected_end_time, real_start_time, real_end_time,
+                    narrative_summary
+             FROM routes
+             WHERE numeric_id = $1",
+        )
+        .bind(numeric_id)
+        .fetch_optional(&state.pool),
+    )
+    .await;
+
+    let route = match route {
+        Ok(Some(route)) => route,
+        Ok(None) => return StatusCode::NOT_FOUND.into_response(),
+        Err(e) => {
+            error!("error looking up run {numeric_id} for bundle: {e:?}");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+
+    let stops: Result<Vec<RouteStop>, _> = query_stats::timed(
+        "bundle_stops",
+        query_as(
+            "SELECT station_id, sequence, expected_arrival, real_arrival,
+                    expected_departure, real_departure
+             FROM stops
+             WHERE route_id = $1 AND route_expected_start_time = $2
+             ORDER BY sequence",
+        )
+        .bind(&route.id)
+        .bind(route.expected_start_time)
+        .fetch_all(&state.pool),
+    )
+    .await;
+
+    let stops = match stops {
+        Ok(stops) => stops,
+        Err(e) => {
+            error!("error loading stops for run {numeric_id} bundle: {e:?}");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+
+    let corrections: Result<Vec<CorrectionEvent>, _> = query_stats::timed(
+        "bundle_corrections",
+        query_as(
+            "SELECT sequence, action, reason, corrected_at
+             FROM stop_corrections
+             WHERE route_id = $1 AND route_expected_start_time = $2
+             ORDER BY corrected_at",
+        )
+        .bind(&route.id)
+        .bind(route.expected_start_time)
+        .fetch_all(&state.pool),
+    )
+    .await;
+
+    let corrections = match corrections {
+        Ok(corrections) => corrections,
+        Err(e) => {
+            error!("error loading corrections for run {numeric_id} bundle: {e:?}");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+
+    let run_json = json!({
+        "id": route.id,
+        "numeric_id": route.numeric_id,
+        "slug": route.slug,
+        "route_number": route.route_number,
+        "source": route.source,
+        "destination": route.destination,
+        "expected_start_time": route.expected_start_time,
+        "expected_end_time": route.expected_end_time,
+        "real_start_time": route.real_start_time,
+        "real_end_time": route.real_end_time,
+        "narrative_summary": route.narrative_summary,
+        "stops": stops,
+    })
+    .to_string();
+
+    let mut observations_csv = String::from(
+        "station_id,sequence,expected_arrival,real_arrival,expected_departure,real_departure\n",
+    );
+    for stop in &stops {
+        observations_csv.push_str(&format!(
+            "{},{},{},{},{},{}\n",
+            csv_field(&stop.station_id),
+            stop.sequence,
+            stop.expected_arrival.to_rfc3339(),
+            csv_opt(stop.real_arrival.map(|t| t.to_rfc3339())),
+            stop.expected_departure.to_rfc3339(),
+            csv_opt(stop.real_departure.map(|t| t.to_rfc3339())),
+        ));
+    }
+
+    let mut corrections_csv = String::from("sequence,action,reason,corrected_at\n");
+    for correction in &corrections {
+        corrections_csv.push_str(&format!(
+            "{},{},{},{}\n",
+            csv_opt(correction.sequence),
+            csv_field(&correction.action),
+            csv_field(&correction.reason),
+            correction.corrected_at.to_rfc3339(),
+        ));
+    }
+
+    let readme = "This bundle contains the run record, stop-level observations and any \
+manual corrections on file for this run.\n\nRaw upstream HTML pages aren't archived by this \
+deployment, so none is included here.\n";
+
+    let zip = zip_writer::build(&[
+        ("run.json", run_json.into_bytes()),
+        ("observations.csv", observations_csv.into_bytes()),
+        ("corrections.csv", corrections_csv.into_bytes()),
+        ("README.txt", readme.as_bytes().to_vec()),
+    ]);
+
+    (
+        [
+            (header::CONTENT_TYPE, "application/zip".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"run-{numeric_id}.zip\""),
+            ),
+        ],
+        zip,
+    )
+        .into_response()
+}
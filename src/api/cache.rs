@@ -0,0 +1,21 @@
+//! Tiny in-memory cache of the last successfully computed response body per route.
+use std::{collections::HashMap, sync::Arc};
+
+use tokio::sync::Mutex;
+
+/// Caches the last known-good JSON payload for a given request path so it can be
+/// served (marked `stale`) if the database becomes unreachable.
+#[derive(Clone, Default)]
+pub struct ResponseCache {
+    entries: Arc<Mutex<HashMap<String, serde_json::Value>>>,
+}
+
+impl ResponseCache {
+    pub async fn get(&self, path: &str) -> Option<serde_json::Value> {
+        self.entries.lock().await.get(path).cloned()
+    }
+
+    pub async fn put(&self, path: &str, value: serde_json::Value) {
+        self.entries.lock().await.insert(path.to_string(), value);
+    }
+}
@@ -0,0 +1,68 @@
+//! Which route numbers run directly between two stations, derived from stop
+//! sequences — the shared building block behind the client's station search
+//! and (eventually) a journey-delay endpoint, so neither has to re-derive it
+//! from raw stops. See [`super::travel_time::travel_time`] for the travel
+//! time distribution between two stations across all routes combined.
+use axum::{
+    extract::{Query, State},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Deserialize;
+use sqlx::{prelude::FromRow, query_as};
+
+use crate::query_stats;
+
+use super::{problem::ApiError, AppState};
+
+#[derive(Deserialize)]
+pub struct ConnectionsQuery {
+    from: String,
+    to: String,
+}
+
+#[derive(FromRow, serde::Serialize)]
+struct Connection {
+    route_number: i32,
+    sample_count: i64,
+    avg_real_minutes: Option<f64>,
+    on_time_percentage: Option<f64>,
+}
+
+/// Every route number observed running directly from `from` to `to` (in that
+/// stop-sequence order) over the last 30 days, with its typical realized
+/// travel time and on-time percentage at the `to` stop.
+pub async fn connections(
+    State(state): State<AppState>,
+    Query(params): Query<ConnectionsQuery>,
+) -> Result<Response, ApiError> {
+    let rows: Vec<Connection> = query_stats::timed(
+        "connections",
+        query_as(
+            "SELECT
+                r.route_number,
+                count(*) as sample_count,
+                avg(extract(epoch from (t.real_arrival - f.real_departure)) / 60)
+                    FILTER (WHERE t.real_arrival IS NOT NULL AND f.real_departure IS NOT NULL)::float8
+                    as avg_real_minutes,
+                (count(*) FILTER (WHERE t.real_arrival - t.expected_arrival <= interval '5 minutes')::float8
+                    / nullif(count(*) FILTER (WHERE t.real_arrival IS NOT NULL), 0)) as on_time_percentage
+             FROM stops f
+             JOIN stops t
+                ON t.route_id = f.route_id
+                AND t.route_expected_start_time = f.route_expected_start_time
+                AND t.sequence > f.sequence
+             JOIN routes r ON r.id = f.route_id AND r.expected_start_time = f.route_expected_start_time
+             WHERE f.station_id = $1 AND t.station_id = $2
+                   AND f.route_expected_start_time >= now() - interval '30 days'
+             GROUP BY r.route_number
+             ORDER BY sample_count DESC",
+        )
+        .bind(&params.from)
+        .bind(&params.to)
+        .fetch_all(&state.pool),
+    )
+    .await?;
+
+    Ok(Json(rows).into_response())
+}
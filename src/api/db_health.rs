@@ -0,0 +1,229 @@
+//! Handlers and middleware that keep the API answering with the last known-good
+//! data instead of a 500 during short database outages.
+use axum::{
+    extract::{OriginalUri, Query, Request, State},
+    http::{header, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sqlx::prelude::FromRow;
+use tracing::error;
+
+use super::AppState;
+
+/// Serves the last cached payload for this path (marked `stale: true`) whenever
+/// the wrapped handler fails, since a failure here is almost always the DB being
+/// unreachable rather than a client error.
+pub async fn serve_stale_on_db_outage(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let path = request.uri().path().to_string();
+    let response = next.run(request).await;
+
+    if response.status().is_success() {
+        return response;
+    }
+
+    match state.cache.get(&path).await {
+        Some(mut cached) => {
+            error!("serving stale cache for {path} due to upstream error");
+            if let Some(obj) = cached.as_object_mut() {
+                obj.insert("stale".to_string(), json!(true));
+            }
+            (
+                StatusCode::OK,
+                [(header::CACHE_CONTROL, "no-store")],
+                Json(cached),
+            )
+                .into_response()
+        }
+        None => response,
+    }
+}
+
+pub async fn summary(State(state): State<AppState>) -> Response {
+    let route_count: Result<i64, _> = sqlx::query_scalar("SELECT count(*) FROM routes")
+        .fetch_one(&state.pool)
+        .await;
+
+    match route_count {
+        Ok(route_count) => {
+            let payload = json!({ "route_count": route_count, "stale": false });
+            state.cache.put("/api/summary", payload.clone()).await;
+            Json(payload).into_response()
+        }
+        Err(e) => {
+            error!("error computing summary: {e:?}");
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+/// Serves a payload maintained entirely by the background cache refresher
+/// (e.g. the heatmap), keyed by the request path.
+pub async fn cached_payload(State(state): State<AppState>, OriginalUri(uri): OriginalUri) -> Response {
+    match state.cache.get(uri.path()).await {
+        Some(payload) => Json(payload).into_response(),
+        None => StatusCode::SERVICE_UNAVAILABLE.into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct LeaderboardFilters {
+    county: Option<String>,
+    /// Narrows to routes carrying this operator-assigned tag (see
+    /// [`crate::route_tags`]), e.g. `?tag=coastal-seasonal`.
+    tag: Option<String>,
+}
+
+#[derive(FromRow)]
+struct RouteDelayRow {
+    route_number: i32,
+    avg_minutes_late: f64,
+}
+
+/// Serves the network-wide leaderboard maintained by the cache refresher, or,
+/// when `?county=` and/or `?tag=` are given, computes it on demand scoped to
+/// that filter (too situational to keep pre-computed for).
+pub async fn leaderboard(
+    State(state): State<AppState>,
+    Query(filters): Query<LeaderboardFilters>,
+    OriginalUri(uri): OriginalUri,
+) -> Response {
+    if filters.county.is_none() && filters.tag.is_none() {
+        return cached_payload(State(state), OriginalUri(uri)).await;
+    }
+
+    let rows: Result<Vec<RouteDelayRow>, _> = sqlx::query_as(
+        "SELECT r.route_number,
+                avg(extract(epoch from (r.real_end_time - r.expected_end_time)) / 60)::float8 as avg_minutes_late
+         FROM routes r
+         WHERE r.real_end_time IS NOT NULL
+           AND ($1::text IS NULL OR EXISTS (
+               SELECT 1 FROM stops s
+               JOIN stations st ON st.id = s.station_id
+               WHERE s.route_id = r.id
+                 AND s.route_expected_start_time = r.expected_start_time
+                 AND st.county = $1
+           ))
+           AND ($2::text IS NULL OR EXISTS (
+               SELECT 1 FROM route_tags rt WHERE rt.route_number = r.route_number AND rt.tag = $2
+           ))
+         GROUP BY r.route_number
+         ORDER BY avg_minutes_late DESC
+         LIMIT 10",
+    )
+    .bind(&filters.county)
+    .bind(&filters.tag)
+    .fetch_all(&state.pool)
+    .await;
+
+    match rows {
+        Ok(rows) => {
+            let leaderboard = rows
+                .into_iter()
+                .map(|r| json!({ "route_number": r.route_number, "avg_minutes_late": r.avg_minutes_late }))
+                .collect::<Vec<_>>();
+
+            Json(json!({
+                "leaderboard": leaderboard,
+                "county": filters.county,
+                "tag": filters.tag,
+                "stale": false
+            }))
+            .into_response()
+        }
+        Err(e) => {
+            error!("error computing leaderboard for county={:?} tag={:?}: {e:?}", filters.county, filters.tag);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+#[derive(FromRow, Serialize)]
+struct ObservationLagBucket {
+    bucket: String,
+    run_count: i64,
+}
+
+/// Histogram of how many minutes after `expected_start_time` the delay
+/// checker first observed each run — the earliest `real_arrival`/`real_departure`
+/// recorded across its stops, which also covers a run picked up mid-route
+/// rather than only ones caught departing their origin. A proxy for upstream
+/// and polling lag, to help tune how eagerly newly-started routes get polled.
+pub async fn observation_lag_histogram(State(state): State<AppState>) -> Response {
+    let buckets: Result<Vec<ObservationLagBucket>, _> = sqlx::query_as(
+        "SELECT
+            CASE
+                WHEN lag_minutes < 5 THEN '<5m'
+                WHEN lag_minutes < 15 THEN '5-15m'
+                WHEN lag_minutes < 30 THEN '15-30m'
+                WHEN lag_minutes < 60 THEN '30-60m'
+                WHEN lag_minutes < 120 THEN '60-120m'
+                ELSE '>=120m'
+            END AS bucket,
+            count(*) AS run_count
+         FROM (
+             SELECT
+                 extract(epoch FROM (
+                     min(coalesce(s.real_arrival, s.real_departure)) - r.expected_start_time
+                 )) / 60 AS lag_minutes
+             FROM routes r
+             JOIN stops s
+                 ON s.route_id = r.id AND s.route_expected_start_time = r.expected_start_time
+             WHERE r.real_start_time IS NOT NULL
+             GROUP BY r.id, r.expected_start_time
+         ) first_observations
+         WHERE lag_minutes IS NOT NULL
+         GROUP BY bucket
+         ORDER BY min(lag_minutes)",
+    )
+    .fetch_all(&state.pool)
+    .await;
+
+    match buckets {
+        Ok(buckets) => Json(json!({ "buckets": buckets, "stale": false })).into_response(),
+        Err(e) => {
+            error!("error computing observation lag histogram: {e:?}");
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+/// Reports whether the startup warm-up (today's routes/stations preload) has
+/// finished, so an orchestrator can hold off routing traffic here until the
+/// first cold queries are already paid for instead of serving them to the
+/// first real requests.
+pub async fn readyz(State(state): State<AppState>) -> Response {
+    if state.readiness.is_ready().await {
+        Json(json!({ "ready": true })).into_response()
+    } else {
+        (StatusCode::SERVICE_UNAVAILABLE, Json(json!({ "ready": false }))).into_response()
+    }
+}
+
+pub async fn live(State(state): State<AppState>) -> Response {
+    let live_count: Result<i64, _> = sqlx::query_scalar(
+        "SELECT count(*) FROM routes WHERE real_start_time IS NOT NULL AND real_end_time IS NULL",
+    )
+    .fetch_one(&state.pool)
+    .await;
+
+    match live_count {
+        Ok(live_count) => {
+            let comparisons = state.live_comparisons.snapshot().await;
+            let payload = json!({ "live_count": live_count, "comparisons": comparisons, "stale": false });
+            state.cache.put("/api/live", payload.clone()).await;
+            Json(payload).into_response()
+        }
+        Err(e) => {
+            error!("error computing live count: {e:?}");
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
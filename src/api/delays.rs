@@ -0,0 +1,359 @@
+//! Live delay status for currently-running routes, backed by the in-memory
+//! snapshot the delay checker keeps up to date as it observes each stop.
+use std::collections::HashSet;
+use std::convert::Infallible;
+
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::{
+        sse::{Event, KeepAlive},
+        IntoResponse, Response, Sse,
+    },
+    Json,
+};
+use chrono::{DateTime, Utc};
+use futures::Stream;
+use serde::{Deserialize, Serialize};
+use sqlx::{prelude::FromRow, query_scalar};
+use tokio_stream::{wrappers::BroadcastStream, StreamExt};
+use tracing::error;
+
+use crate::query_stats;
+
+use super::{pagination::Page, AppState};
+
+#[derive(Deserialize)]
+pub struct LiveFilters {
+    /// Narrows to routes marked wheelchair-accessible (or, with `false`, to
+    /// ones explicitly marked not accessible).
+    accessible: Option<bool>,
+    /// Narrows to routes that allow bikes (or, with `false`, to ones that don't).
+    bikes: Option<bool>,
+}
+
+#[derive(FromRow)]
+struct RunningRoute {
+    id: String,
+    route_number: i32,
+    source: String,
+    destination: String,
+    expected_start_time: DateTime<Utc>,
+    wheelchair_accessible: bool,
+    bikes_allowed: bool,
+}
+
+#[derive(Serialize)]
+struct LiveDelay {
+    id: String,
+    route_number: i32,
+    source: String,
+    destination: String,
+    expected_start_time: DateTime<Utc>,
+    wheelchair_accessible: bool,
+    bikes_allowed: bool,
+    minutes_late: Option<i32>,
+    usual_minutes_late: Option<f64>,
+    updated_at: Option<DateTime<Utc>>,
+    /// When HŽ's own page last said it refreshed this train's status, as
+    /// opposed to `updated_at` (when we recorded it) — lets a client tell a
+    /// stale upstream position apart from a genuinely live one.
+    upstream_updated_at: Option<DateTime<Utc>>,
+}
+
+/// All routes that should have already started but haven't finished,
+/// together with the latest delay observed for each (if any). `accessible`/
+/// `bikes` match the upstream GTFS-style `wheelchair_accessible`/
+/// `bikes_allowed` codes, where `1` means allowed and anything else doesn't.
+pub async fn live(State(state): State<AppState>, Query(filters): Query<LiveFilters>) -> Response {
+    let routes: Result<Vec<RunningRoute>, _> = query_stats::timed(
+        "delays_live",
+        sqlx::query_as(
+            "SELECT id, route_number, source, destination, expected_start_time,
+                    wheelchair_accessible = 1 as wheelchair_accessible,
+                    bikes_allowed = 1 as bikes_allowed
+             FROM routes
+             WHERE expected_start_time <= now() AND real_end_time IS NULL
+               AND ($1::bool IS NULL OR (wheelchair_accessible = 1) = $1)
+               AND ($2::bool IS NULL OR (bikes_allowed = 1) = $2)",
+        )
+        .bind(filters.accessible)
+        .bind(filters.bikes)
+        .fetch_all(&state.pool),
+    )
+    .await;
+
+    let routes = match routes {
+        Ok(routes) => routes,
+        Err(e) => {
+            error!("error listing running routes: {e:?}");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+
+    let comparisons = state.live_comparisons.snapshot().await;
+
+    let delays = routes
+        .into_iter()
+        .map(|r| {
+            let comparison = comparisons.iter().find(|c| c.route_id == r.id);
+
+            LiveDelay {
+                id: r.id,
+                route_number: r.route_number,
+                source: r.source,
+                destination: r.destination,
+                expected_start_time: r.expected_start_time,
+                wheelchair_accessible: r.wheelchair_accessible,
+                bikes_allowed: r.bikes_allowed,
+                minutes_late: comparison.map(|c| c.minutes_late),
+                usual_minutes_late: comparison.and_then(|c| c.usual_minutes_late),
+                updated_at: comparison.map(|c| c.updated_at),
+                upstream_updated_at: comparison.map(|c| c.upstream_updated_at),
+            }
+        })
+        .collect::<Vec<_>>();
+
+    Json(delays).into_response()
+}
+
+const MAX_BULK_STATUS_ROUTES: usize = 200;
+
+#[derive(Deserialize)]
+pub struct BulkStatusRequest {
+    route_numbers: Vec<i32>,
+}
+
+#[derive(Serialize)]
+struct BulkStatusEntry {
+    route_number: i32,
+    running: bool,
+    id: Option<String>,
+    source: Option<String>,
+    destination: Option<String>,
+    expected_start_time: Option<DateTime<Utc>>,
+    minutes_late: Option<i32>,
+    usual_minutes_late: Option<f64>,
+    updated_at: Option<DateTime<Utc>>,
+    upstream_updated_at: Option<DateTime<Utc>>,
+}
+
+/// Latest known delay for each of `route_numbers` in one round trip, so a
+/// widget or dashboard showing many routes doesn't have to fan out a GET
+/// per route. Only currently-running routes carry delay data; anything not
+/// yet started, already finished, or simply unrecognised comes back with
+/// `running: false` and no delay fields rather than erroring.
+pub async fn bulk_status(State(state): State<AppState>, Json(body): Json<BulkStatusRequest>) -> Response {
+    if body.route_numbers.len() > MAX_BULK_STATUS_ROUTES {
+        return (
+            StatusCode::BAD_REQUEST,
+            format!("at most {MAX_BULK_STATUS_ROUTES} route_numbers per request"),
+        )
+            .into_response();
+    }
+
+    let routes: Result<Vec<RunningRoute>, _> = query_stats::timed(
+        "delays_bulk_status",
+        sqlx::query_as(
+            "SELECT id, route_number, source, destination, expected_start_time
+             FROM routes
+             WHERE route_number = ANY($1) AND expected_start_time <= now() AND real_end_time IS NULL",
+        )
+        .bind(&body.route_numbers)
+        .fetch_all(&state.pool),
+    )
+    .await;
+
+    let routes = match routes {
+        Ok(routes) => routes,
+        Err(e) => {
+            error!("error listing running routes for bulk status: {e:?}");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+
+    let comparisons = state.live_comparisons.snapshot().await;
+
+    let entries = body
+        .route_numbers
+        .iter()
+        .map(|&route_number| match routes.iter().find(|r| r.route_number == route_number) {
+            Some(r) => {
+                let comparison = comparisons.iter().find(|c| c.route_id == r.id);
+
+                BulkStatusEntry {
+                    route_number,
+                    running: true,
+                    id: Some(r.id.clone()),
+                    source: Some(r.source.clone()),
+                    destination: Some(r.destination.clone()),
+                    expected_start_time: Some(r.expected_start_time),
+                    minutes_late: comparison.map(|c| c.minutes_late),
+                    usual_minutes_late: comparison.and_then(|c| c.usual_minutes_late),
+                    updated_at: comparison.map(|c| c.updated_at),
+                    upstream_updated_at: comparison.map(|c| c.upstream_updated_at),
+                }
+            }
+            None => BulkStatusEntry {
+                route_number,
+                running: false,
+                id: None,
+                source: None,
+                destination: None,
+                expected_start_time: None,
+                minutes_late: None,
+                usual_minutes_late: None,
+                updated_at: None,
+                upstream_updated_at: None,
+            },
+        })
+        .collect::<Vec<_>>();
+
+    Json(entries).into_response()
+}
+
+const DEFAULT_HISTORY_LIMIT: u32 = 100;
+const MAX_HISTORY_LIMIT: u32 = 500;
+
+#[derive(Deserialize)]
+pub struct HistoryFilters {
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+    route_number: Option<i32>,
+    station_id: Option<String>,
+    /// Keyset cursor: the `expected_arrival`/`route_id`/`sequence` of the last
+    /// row from the previous page. Omit for the first page.
+    after: Option<DateTime<Utc>>,
+    after_route_id: Option<String>,
+    after_sequence: Option<i16>,
+    limit: Option<u32>,
+}
+
+#[derive(FromRow, Serialize)]
+struct HistoricalStop {
+    route_id: String,
+    route_expected_start_time: DateTime<Utc>,
+    route_number: i32,
+    station_id: String,
+    sequence: i16,
+    expected_arrival: DateTime<Utc>,
+    real_arrival: Option<DateTime<Utc>>,
+    expected_departure: DateTime<Utc>,
+    real_departure: Option<DateTime<Utc>>,
+}
+
+/// Historical stops across months of data without dumping the DB: keyset
+/// (not offset) pagination ordered by `(expected_arrival, route_id, sequence)`,
+/// since an `OFFSET` deep into a large range gets slower the further in you go.
+pub async fn history(
+    State(state): State<AppState>,
+    Query(filters): Query<HistoryFilters>,
+) -> Response {
+    let limit = filters.limit.unwrap_or(DEFAULT_HISTORY_LIMIT).clamp(1, MAX_HISTORY_LIMIT);
+
+    let stops: Result<Vec<HistoricalStop>, _> = query_stats::timed(
+        "delays_history",
+        sqlx::query_as(
+            "SELECT s.route_id, s.route_expected_start_time, r.route_number, s.station_id,
+                    s.sequence, s.expected_arrival, s.real_arrival, s.expected_departure, s.real_departure
+             FROM stops s
+             JOIN routes r ON r.id = s.route_id AND r.expected_start_time = s.route_expected_start_time
+             WHERE ($1::timestamptz IS NULL OR s.expected_arrival >= $1)
+               AND ($2::timestamptz IS NULL OR s.expected_arrival <= $2)
+               AND ($3::int IS NULL OR r.route_number = $3)
+               AND ($4::text IS NULL OR s.station_id = $4)
+               AND ($5::timestamptz IS NULL OR $6::text IS NULL OR $7::smallint IS NULL
+                    OR (s.expected_arrival, s.route_id, s.sequence) > ($5, $6, $7))
+             ORDER BY s.expected_arrival, s.route_id, s.sequence
+             LIMIT $8",
+        )
+        .bind(filters.from)
+        .bind(filters.to)
+        .bind(filters.route_number)
+        .bind(&filters.station_id)
+        .bind(filters.after)
+        .bind(&filters.after_route_id)
+        .bind(filters.after_sequence)
+        .bind(limit as i64)
+        .fetch_all(&state.pool),
+    )
+    .await;
+
+    match stops {
+        Ok(stops) => {
+            let next_cursor = stops.last().map(|s| {
+                serde_json::json!({
+                    "after": s.expected_arrival,
+                    "after_route_id": s.route_id,
+                    "after_sequence": s.sequence,
+                })
+            });
+
+            Json(Page::new(stops, next_cursor)).into_response()
+        }
+        Err(e) => {
+            error!("error querying historical delays: {e:?}");
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct StreamFilters {
+    /// Comma-separated route numbers to narrow the feed to.
+    route_numbers: Option<String>,
+    /// Comma-separated station ids; resolved to the route numbers currently
+    /// serving them and unioned with `route_numbers`.
+    stations: Option<String>,
+}
+
+/// SSE alternative to `/ws/delays` for clients that can't use WebSockets,
+/// backed by the same [`DelayUpdates`](crate::background_services::delay_broadcast::DelayUpdates)
+/// broadcast channel. With neither filter set, every update is forwarded.
+pub async fn stream(
+    State(state): State<AppState>,
+    Query(filters): Query<StreamFilters>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let mut wanted_route_numbers: Option<HashSet<i32>> = None;
+
+    if let Some(route_numbers) = &filters.route_numbers {
+        wanted_route_numbers
+            .get_or_insert_with(HashSet::new)
+            .extend(route_numbers.split(',').filter_map(|n| n.trim().parse::<i32>().ok()));
+    }
+
+    if let Some(stations) = &filters.stations {
+        let station_ids: Vec<&str> = stations.split(',').map(str::trim).collect();
+        let route_numbers: Result<Vec<i32>, _> = query_stats::timed(
+            "delays_stream_station_routes",
+            query_scalar(
+                "SELECT DISTINCT r.route_number
+                 FROM stops s
+                 JOIN routes r ON r.id = s.route_id AND r.expected_start_time = s.route_expected_start_time
+                 WHERE s.station_id = ANY($1) AND r.real_end_time IS NULL",
+            )
+            .bind(&station_ids)
+            .fetch_all(&state.pool),
+        )
+        .await;
+
+        match route_numbers {
+            Ok(route_numbers) => wanted_route_numbers
+                .get_or_insert_with(HashSet::new)
+                .extend(route_numbers),
+            Err(e) => error!("error resolving stations for delay stream filter: {e:?}"),
+        }
+    }
+
+    let updates = BroadcastStream::new(state.delay_updates.subscribe()).filter_map(move |result| {
+        let update = result.ok()?;
+        if let Some(wanted) = &wanted_route_numbers {
+            if !wanted.contains(&update.route_number) {
+                return None;
+            }
+        }
+        Some(Ok(Event::default().json_data(&update).unwrap_or_default()))
+    });
+
+    Sse::new(updates).keep_alive(KeepAlive::default())
+}
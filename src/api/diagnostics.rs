@@ -0,0 +1,153 @@
+//! A single composite report for triaging a sick instance, pulling together
+//! config, migration state, in-process queue depths and upstream health that
+//! would otherwise mean checking half a dozen places by hand.
+use std::path::Path;
+
+use axum::{
+    extract::State,
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::prelude::FromRow;
+use tracing::error;
+
+use crate::{query_stats, snapshot::SNAPSHOTS_DIR, LOGS_DIR};
+
+use super::AppState;
+
+#[derive(Serialize)]
+struct ConfigSummary {
+    admin_token_set: bool,
+    admin_mtls_enabled: bool,
+    cors_allowed_origins: usize,
+    rate_limit_per_minute: u64,
+    rate_limit_api_key_per_minute: u64,
+}
+
+#[derive(FromRow, Serialize)]
+struct LatestMigration {
+    version: i64,
+    description: String,
+    success: bool,
+    installed_on: DateTime<Utc>,
+}
+
+#[derive(Serialize)]
+struct MigrationStatus {
+    applied_count: i64,
+    latest: Option<LatestMigration>,
+}
+
+#[derive(Serialize)]
+struct QueueDepths {
+    watched_routes: usize,
+    ws_delay_subscribers: usize,
+    tracked_live_comparisons: usize,
+}
+
+#[derive(Serialize)]
+struct UpstreamHealth {
+    monitor_paused: bool,
+    recent_parse_failures: usize,
+}
+
+#[derive(Serialize)]
+struct DiagnosticsReport {
+    config: ConfigSummary,
+    migrations: MigrationStatus,
+    queue_depths: QueueDepths,
+    upstream: UpstreamHealth,
+    slow_queries: Vec<query_stats::NamedQueryStats>,
+    logs_dir_bytes: Option<u64>,
+    snapshots_dir_bytes: Option<u64>,
+}
+
+/// Walks `path` recursively, summing up regular file sizes. Returns `None`
+/// if the directory doesn't exist (e.g. nothing's been logged/snapshotted
+/// yet on a fresh instance) rather than treating that as an error.
+async fn dir_size_bytes(path: impl AsRef<Path>) -> Option<u64> {
+    let path = path.as_ref();
+
+    if !tokio::fs::try_exists(path).await.unwrap_or(false) {
+        return None;
+    }
+
+    Some(dir_size_bytes_inner(path).await.unwrap_or(0))
+}
+
+fn dir_size_bytes_inner(
+    path: &Path,
+) -> std::pin::Pin<Box<dyn std::future::Future<Output = std::io::Result<u64>> + Send + '_>> {
+    Box::pin(async move {
+        let mut total = 0;
+        let mut entries = tokio::fs::read_dir(path).await?;
+
+        while let Some(entry) = entries.next_entry().await? {
+            let file_type = entry.file_type().await?;
+
+            if file_type.is_dir() {
+                total += dir_size_bytes_inner(&entry.path()).await?;
+            } else if file_type.is_file() {
+                total += entry.metadata().await?.len();
+            }
+        }
+
+        Ok(total)
+    })
+}
+
+/// Everything needed to triage a sick instance in one call: a redacted
+/// config summary, migration status, in-process queue depths, upstream
+/// parser health, slow-query stats and on-disk log/snapshot usage.
+pub async fn report(State(state): State<AppState>) -> Response {
+    let migration_row: Result<Option<LatestMigration>, _> = query_stats::timed(
+        "diagnostics_latest_migration",
+        sqlx::query_as(
+            "SELECT version, description, success, installed_on FROM _sqlx_migrations ORDER BY version DESC LIMIT 1",
+        )
+        .fetch_optional(&state.pool),
+    )
+    .await;
+
+    let latest = match migration_row {
+        Ok(latest) => latest,
+        Err(e) => {
+            error!("error reading migration status: {e:?}");
+            None
+        }
+    };
+
+    let applied_count: i64 = query_stats::timed(
+        "diagnostics_migration_count",
+        sqlx::query_scalar("SELECT count(*) FROM _sqlx_migrations").fetch_one(&state.pool),
+    )
+    .await
+    .unwrap_or(0);
+
+    let report = DiagnosticsReport {
+        config: ConfigSummary {
+            admin_token_set: state.admin_token.is_some(),
+            admin_mtls_enabled: state.admin_mtls_enabled,
+            cors_allowed_origins: state.cors_allowed_origins.len(),
+            rate_limit_per_minute: state.rate_limit_config.per_ip_per_minute,
+            rate_limit_api_key_per_minute: state.rate_limit_config.per_api_key_per_minute,
+        },
+        migrations: MigrationStatus { applied_count, latest },
+        queue_depths: QueueDepths {
+            watched_routes: state.watch_list.count().await,
+            ws_delay_subscribers: state.delay_updates.subscriber_count(),
+            tracked_live_comparisons: state.live_comparisons.snapshot().await.len(),
+        },
+        upstream: UpstreamHealth {
+            monitor_paused: state.monitor_control.is_paused(),
+            recent_parse_failures: state.monitor_control.recent_failures().await.len(),
+        },
+        slow_queries: query_stats::snapshot(),
+        logs_dir_bytes: dir_size_bytes(LOGS_DIR).await,
+        snapshots_dir_bytes: dir_size_bytes(SNAPSHOTS_DIR).await,
+    };
+
+    Json(report).into_response()
+}
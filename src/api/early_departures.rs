@@ -0,0 +1,136 @@
+//! Early departures (`real_departure` earlier than scheduled) are a
+//! service-quality violation distinct from delays, so they get their own
+//! listing and per-route/station counts rather than folding into `stats`.
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::prelude::FromRow;
+use tracing::error;
+
+use crate::query_stats;
+
+use super::AppState;
+
+const DEFAULT_LIMIT: u32 = 100;
+const MAX_LIMIT: u32 = 500;
+
+#[derive(Deserialize)]
+pub struct EarlyDepartureFilters {
+    route_number: Option<i32>,
+    station_id: Option<String>,
+    limit: Option<u32>,
+}
+
+#[derive(FromRow, Serialize)]
+struct EarlyDeparture {
+    route_id: String,
+    route_number: i32,
+    station_id: String,
+    sequence: i16,
+    expected_departure: DateTime<Utc>,
+    real_departure: DateTime<Utc>,
+    minutes_early: f64,
+}
+
+/// Most recent early departures, optionally narrowed to one route number
+/// and/or station, newest first.
+pub async fn recent(
+    State(state): State<AppState>,
+    Query(filters): Query<EarlyDepartureFilters>,
+) -> Response {
+    let limit = filters.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+
+    let departures: Result<Vec<EarlyDeparture>, _> = query_stats::timed(
+        "early_departures_recent",
+        sqlx::query_as(
+            "SELECT s.route_id, r.route_number, s.station_id, s.sequence,
+                    s.expected_departure, s.real_departure,
+                    (extract(epoch from (s.expected_departure - s.real_departure)) / 60)::float8 as minutes_early
+             FROM stops s
+             JOIN routes r ON r.id = s.route_id AND r.expected_start_time = s.route_expected_start_time
+             WHERE s.real_departure IS NOT NULL AND s.real_departure < s.expected_departure
+               AND ($1::int IS NULL OR r.route_number = $1)
+               AND ($2::text IS NULL OR s.station_id = $2)
+             ORDER BY s.expected_departure DESC
+             LIMIT $3",
+        )
+        .bind(filters.route_number)
+        .bind(&filters.station_id)
+        .bind(limit as i64)
+        .fetch_all(&state.pool),
+    )
+    .await;
+
+    match departures {
+        Ok(departures) => Json(departures).into_response(),
+        Err(e) => {
+            error!("error listing early departures: {e:?}");
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+#[derive(FromRow, Serialize)]
+struct RouteEarlyDepartureCount {
+    route_number: i32,
+    early_departures: i64,
+}
+
+#[derive(FromRow, Serialize)]
+struct StationEarlyDepartureCount {
+    station_id: String,
+    early_departures: i64,
+}
+
+/// Per-route and per-station counts of early departures on record.
+pub async fn counts(State(state): State<AppState>) -> Response {
+    let by_route: Result<Vec<RouteEarlyDepartureCount>, _> = query_stats::timed(
+        "early_departures_by_route",
+        sqlx::query_as(
+            "SELECT r.route_number, count(*) as early_departures
+             FROM stops s
+             JOIN routes r ON r.id = s.route_id AND r.expected_start_time = s.route_expected_start_time
+             WHERE s.real_departure IS NOT NULL AND s.real_departure < s.expected_departure
+             GROUP BY r.route_number
+             ORDER BY early_departures DESC",
+        )
+        .fetch_all(&state.pool),
+    )
+    .await;
+
+    let by_route = match by_route {
+        Ok(by_route) => by_route,
+        Err(e) => {
+            error!("error counting early departures by route: {e:?}");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+
+    let by_station: Result<Vec<StationEarlyDepartureCount>, _> = query_stats::timed(
+        "early_departures_by_station",
+        sqlx::query_as(
+            "SELECT station_id, count(*) as early_departures
+             FROM stops
+             WHERE real_departure IS NOT NULL AND real_departure < expected_departure
+             GROUP BY station_id
+             ORDER BY early_departures DESC",
+        )
+        .fetch_all(&state.pool),
+    )
+    .await;
+
+    match by_station {
+        Ok(by_station) => {
+            Json(serde_json::json!({ "by_route": by_route, "by_station": by_station })).into_response()
+        }
+        Err(e) => {
+            error!("error counting early departures by station: {e:?}");
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
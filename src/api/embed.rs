@@ -0,0 +1,227 @@
+//! A tiny self-contained HTML widget for embedding one route's live status
+//! and punctuality on a third-party site — no SPA, no JS framework, so it
+//! works the same whether the embedding page runs one or none. URLs are
+//! signed per origin so only sites we've actually handed a link to can embed
+//! it, rather than leaving it open to anyone who finds the route number.
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+};
+use hmac::{Hmac, Mac};
+use serde::Deserialize;
+use sha2::Sha256;
+use sqlx::prelude::FromRow;
+use tracing::error;
+
+use crate::query_stats;
+
+use super::AppState;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Checks `sig` (hex-encoded HMAC-SHA256 of `route_number` and `origin`,
+/// keyed by `secret`) against the signature we'd have handed out for them,
+/// in constant time so a timing attack can't be used to guess a valid
+/// signature one byte at a time. Stops a URL signed for one origin being
+/// reused for another.
+fn signature_matches(secret: &str, route_number: i32, origin: &str, sig: &str) -> bool {
+    let Ok(sig) = hex::decode(sig) else {
+        return false;
+    };
+
+    let mut mac = HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(format!("{route_number}:{origin}").as_bytes());
+    mac.verify_slice(&sig).is_ok()
+}
+
+#[derive(Deserialize)]
+pub struct EmbedParams {
+    /// The embedding site's origin, e.g. `https://example.com`, as handed out
+    /// alongside `sig` when the embed snippet was generated.
+    origin: String,
+    sig: String,
+}
+
+#[derive(FromRow)]
+struct RunningRoute {
+    id: String,
+    source: String,
+    destination: String,
+}
+
+#[derive(FromRow)]
+struct LatestRoute {
+    source: String,
+    destination: String,
+}
+
+#[derive(FromRow)]
+struct Punctuality {
+    sample_count: i64,
+    on_time_percentage: Option<f64>,
+}
+
+/// Serves the widget for `route_number` if `sig` matches a signature we
+/// would have handed out for `origin`, and the `Referer` header (when
+/// present) agrees with `origin`. 404s entirely while no
+/// `EMBED_SIGNING_SECRET` is configured.
+pub async fn widget(
+    State(state): State<AppState>,
+    Path(route_number): Path<i32>,
+    Query(params): Query<EmbedParams>,
+    headers: HeaderMap,
+) -> Response {
+    let Some(secret) = &state.embed_signing_secret else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+
+    if !signature_matches(secret, route_number, &params.origin, &params.sig) {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+
+    if let Some(referer) = headers.get(header::REFERER).and_then(|v| v.to_str().ok()) {
+        if !referer.starts_with(&params.origin) {
+            return StatusCode::FORBIDDEN.into_response();
+        }
+    }
+
+    let running: Result<Option<RunningRoute>, _> = query_stats::timed(
+        "embed_running_route",
+        sqlx::query_as(
+            "SELECT id, source, destination FROM routes
+             WHERE route_number = $1 AND expected_start_time <= now() AND real_end_time IS NULL
+             ORDER BY expected_start_time DESC LIMIT 1",
+        )
+        .bind(route_number)
+        .fetch_optional(&state.pool),
+    )
+    .await;
+
+    let running = match running {
+        Ok(running) => running,
+        Err(e) => {
+            error!("error loading running route for embed widget {route_number}: {e:?}");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+
+    let minutes_late = if let Some(running) = &running {
+        state
+            .live_comparisons
+            .snapshot()
+            .await
+            .iter()
+            .find(|c| c.route_id == running.id)
+            .map(|c| c.minutes_late)
+    } else {
+        None
+    };
+
+    let (source, destination) = if let Some(running) = running {
+        (running.source, running.destination)
+    } else {
+        let latest: Result<Option<LatestRoute>, _> = query_stats::timed(
+            "embed_latest_route",
+            sqlx::query_as(
+                "SELECT source, destination FROM routes WHERE route_number = $1 ORDER BY expected_start_time DESC LIMIT 1",
+            )
+            .bind(route_number)
+            .fetch_optional(&state.pool),
+        )
+        .await;
+
+        match latest {
+            Ok(Some(latest)) => (latest.source, latest.destination),
+            Ok(None) => return StatusCode::NOT_FOUND.into_response(),
+            Err(e) => {
+                error!("error loading latest route for embed widget {route_number}: {e:?}");
+                return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+            }
+        }
+    };
+
+    let punctuality: Result<Punctuality, _> = query_stats::timed(
+        "embed_punctuality_30d",
+        sqlx::query_as(
+            "SELECT
+                count(*) as sample_count,
+                (count(*) FILTER (WHERE real_end_time - expected_end_time <= interval '5 minutes')::float8
+                    / nullif(count(*), 0)) as on_time_percentage
+             FROM routes
+             WHERE route_number = $1 AND real_end_time IS NOT NULL
+                   AND expected_start_time >= now() - interval '30 days'",
+        )
+        .bind(route_number)
+        .fetch_one(&state.pool),
+    )
+    .await;
+
+    let punctuality = match punctuality {
+        Ok(punctuality) => punctuality,
+        Err(e) => {
+            error!("error loading 30-day punctuality for embed widget {route_number}: {e:?}");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+
+    let html = render_widget(route_number, &source, &destination, minutes_late, &punctuality);
+
+    (
+        [(header::CONTENT_TYPE, "text/html; charset=utf-8")],
+        html,
+    )
+        .into_response()
+}
+
+fn render_widget(
+    route_number: i32,
+    source: &str,
+    destination: &str,
+    minutes_late: Option<i32>,
+    punctuality: &Punctuality,
+) -> String {
+    let live_line = match minutes_late {
+        Some(0) => "Running on time".to_string(),
+        Some(minutes) if minutes > 0 => format!("Running {minutes} min late"),
+        Some(_) => "Running ahead of schedule".to_string(),
+        None => "Not currently running".to_string(),
+    };
+
+    let punctuality_line = match punctuality.on_time_percentage {
+        Some(pct) => format!(
+            "{:.0}% on time over the last 30 days ({} runs)",
+            pct * 100.0,
+            punctuality.sample_count
+        ),
+        None => "No punctuality data for the last 30 days yet".to_string(),
+    };
+
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<title>Route {route_number} status</title>
+<style>
+  body {{ margin: 0; font-family: sans-serif; font-size: 14px; padding: 0.75rem; color: #1a1a1a; }}
+  .route {{ font-weight: bold; margin-bottom: 0.25rem; }}
+  .live {{ margin-bottom: 0.25rem; }}
+  .punctuality {{ color: #555; }}
+</style>
+</head>
+<body>
+  <div class="route">Route {route_number}: {source} &rarr; {destination}</div>
+  <div class="live">{live_line}</div>
+  <div class="punctuality">{punctuality_line}</div>
+</body>
+</html>
+"#,
+        source = html_escape(source),
+        destination = html_escape(destination),
+    )
+}
+
+fn html_escape(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
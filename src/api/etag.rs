@@ -0,0 +1,63 @@
+//! Conditional-request support: hashes successful `GET` response bodies into
+//! an `ETag` and answers a matching `If-None-Match` with `304 Not Modified`,
+//! so large payloads (the routes/stations listings, CSV exports) don't need
+//! to be re-sent when the caller already has the current copy.
+//!
+//! This intentionally doesn't also gzip/brotli responses — that would go
+//! through `tower_http`'s `compression-*` features, which pull in
+//! `async-compression`, a crate this deployment's vendored registry doesn't
+//! carry. Conditional requests at least avoid re-sending unchanged bodies in
+//! the meantime.
+use axum::{
+    body::{to_bytes, Body},
+    extract::Request,
+    http::{header, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use sha2::{Digest, Sha256};
+use tracing::error;
+
+/// Responses larger than this are passed through untagged rather than
+/// buffered into memory to hash — comfortably above anything this API
+/// serves today.
+const MAX_BUFFERED_BODY_BYTES: usize = 16 * 1024 * 1024;
+
+pub async fn add_etag(request: Request, next: Next) -> Response {
+    if request.method() != Method::GET {
+        return next.run(request).await;
+    }
+
+    let if_none_match = request
+        .headers()
+        .get(header::IF_NONE_MATCH)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string);
+
+    let response = next.run(request).await;
+
+    if response.status() != StatusCode::OK {
+        return response;
+    }
+
+    let (parts, body) = response.into_parts();
+    let bytes = match to_bytes(body, MAX_BUFFERED_BODY_BYTES).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            error!("error buffering response body to compute ETag: {e}");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+
+    let etag = format!("\"{}\"", hex::encode(Sha256::digest(&bytes)));
+
+    if if_none_match.as_deref() == Some(etag.as_str()) {
+        return (StatusCode::NOT_MODIFIED, [(header::ETAG, etag)]).into_response();
+    }
+
+    let mut response = Response::from_parts(parts, Body::from(bytes));
+    if let Ok(value) = header::HeaderValue::from_str(&etag) {
+        response.headers_mut().insert(header::ETAG, value);
+    }
+    response
+}
@@ -0,0 +1,172 @@
+//! CSV exports for ad-hoc analysis in Excel/pandas, so pulling a date range
+//! doesn't require a manual `psql \copy`.
+use axum::{
+    extract::{Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use sqlx::prelude::FromRow;
+use tracing::error;
+
+use crate::query_stats;
+
+use super::AppState;
+
+#[derive(Deserialize)]
+pub struct ExportFilters {
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+}
+
+/// Wraps a field in double quotes (doubling any quotes it contains) when it
+/// holds a comma, quote or newline, per the CSV quoting rules Excel expects.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn csv_opt<T: ToString>(value: Option<T>) -> String {
+    value.map(|v| v.to_string()).unwrap_or_default()
+}
+
+fn csv_response(filename: &str, body: String) -> Response {
+    (
+        [
+            (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+            (header::CONTENT_DISPOSITION, format!("attachment; filename=\"{filename}\"")),
+        ],
+        body,
+    )
+        .into_response()
+}
+
+#[derive(FromRow)]
+struct RouteRow {
+    id: String,
+    route_number: i32,
+    source: String,
+    destination: String,
+    expected_start_time: DateTime<Utc>,
+    expected_end_time: DateTime<Utc>,
+    real_start_time: Option<DateTime<Utc>>,
+    real_end_time: Option<DateTime<Utc>>,
+    max_delay_minutes: Option<i32>,
+    final_delay_minutes: Option<i32>,
+}
+
+/// Every route whose `expected_start_time` falls in `[from, to]` (both optional).
+pub async fn routes_csv(State(state): State<AppState>, Query(filters): Query<ExportFilters>) -> Response {
+    let rows: Result<Vec<RouteRow>, _> = query_stats::timed(
+        "export_routes_csv",
+        sqlx::query_as(
+            "SELECT id, route_number, source, destination, expected_start_time, expected_end_time,
+                    real_start_time, real_end_time, max_delay_minutes, final_delay_minutes
+             FROM routes
+             WHERE ($1::timestamptz IS NULL OR expected_start_time >= $1)
+               AND ($2::timestamptz IS NULL OR expected_start_time <= $2)
+             ORDER BY expected_start_time",
+        )
+        .bind(filters.from)
+        .bind(filters.to)
+        .fetch_all(&state.pool),
+    )
+    .await;
+
+    let rows = match rows {
+        Ok(rows) => rows,
+        Err(e) => {
+            error!("error exporting routes csv: {e:?}");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+
+    let mut csv = String::from(
+        "id,route_number,source,destination,expected_start_time,expected_end_time,\
+         real_start_time,real_end_time,max_delay_minutes,final_delay_minutes\n",
+    );
+
+    for r in rows {
+        csv.push_str(&format!(
+            "{},{},{},{},{},{},{},{},{},{}\n",
+            csv_field(&r.id),
+            r.route_number,
+            csv_field(&r.source),
+            csv_field(&r.destination),
+            r.expected_start_time.to_rfc3339(),
+            r.expected_end_time.to_rfc3339(),
+            csv_opt(r.real_start_time.map(|t| t.to_rfc3339())),
+            csv_opt(r.real_end_time.map(|t| t.to_rfc3339())),
+            csv_opt(r.max_delay_minutes),
+            csv_opt(r.final_delay_minutes),
+        ));
+    }
+
+    csv_response("routes.csv", csv)
+}
+
+#[derive(FromRow)]
+struct StopRow {
+    route_id: String,
+    route_expected_start_time: DateTime<Utc>,
+    sequence: i16,
+    station_id: String,
+    expected_arrival: DateTime<Utc>,
+    real_arrival: Option<DateTime<Utc>>,
+    expected_departure: DateTime<Utc>,
+    real_departure: Option<DateTime<Utc>>,
+}
+
+/// Every stop belonging to a route whose `expected_start_time` falls in
+/// `[from, to]` (both optional).
+pub async fn stops_csv(State(state): State<AppState>, Query(filters): Query<ExportFilters>) -> Response {
+    let rows: Result<Vec<StopRow>, _> = query_stats::timed(
+        "export_stops_csv",
+        sqlx::query_as(
+            "SELECT s.route_id, s.route_expected_start_time, s.sequence, s.station_id,
+                    s.expected_arrival, s.real_arrival, s.expected_departure, s.real_departure
+             FROM stops s
+             JOIN routes r ON r.id = s.route_id AND r.expected_start_time = s.route_expected_start_time
+             WHERE ($1::timestamptz IS NULL OR r.expected_start_time >= $1)
+               AND ($2::timestamptz IS NULL OR r.expected_start_time <= $2)
+             ORDER BY s.route_expected_start_time, s.route_id, s.sequence",
+        )
+        .bind(filters.from)
+        .bind(filters.to)
+        .fetch_all(&state.pool),
+    )
+    .await;
+
+    let rows = match rows {
+        Ok(rows) => rows,
+        Err(e) => {
+            error!("error exporting stops csv: {e:?}");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+
+    let mut csv = String::from(
+        "route_id,route_expected_start_time,sequence,station_id,\
+         expected_arrival,real_arrival,expected_departure,real_departure\n",
+    );
+
+    for s in rows {
+        csv.push_str(&format!(
+            "{},{},{},{},{},{},{},{}\n",
+            csv_field(&s.route_id),
+            s.route_expected_start_time.to_rfc3339(),
+            s.sequence,
+            csv_field(&s.station_id),
+            s.expected_arrival.to_rfc3339(),
+            csv_opt(s.real_arrival.map(|t| t.to_rfc3339())),
+            s.expected_departure.to_rfc3339(),
+            csv_opt(s.real_departure.map(|t| t.to_rfc3339())),
+        ));
+    }
+
+    csv_response("stops.csv", csv)
+}
@@ -0,0 +1,98 @@
+//! JSON Feed (https://jsonfeed.org/version/1.1) views over monitored routes,
+//! for automation tools and bots that already consume the format.
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::prelude::FromRow;
+use tracing::error;
+
+use crate::query_stats;
+
+use super::AppState;
+
+#[derive(Serialize)]
+struct JsonFeed {
+    version: &'static str,
+    title: &'static str,
+    home_page_url: &'static str,
+    feed_url: &'static str,
+    items: Vec<JsonFeedItem>,
+}
+
+#[derive(Serialize)]
+struct JsonFeedItem {
+    id: String,
+    url: String,
+    title: String,
+    content_text: String,
+    date_published: DateTime<Utc>,
+}
+
+#[derive(FromRow)]
+struct TodayRoute {
+    id: String,
+    route_number: i32,
+    source: String,
+    destination: String,
+    expected_start_time: DateTime<Utc>,
+}
+
+/// Today's monitored departures, most recent first, each linking to its
+/// `/api/routes/{id}/{expected_start_time}` detail page.
+pub async fn today(State(state): State<AppState>) -> Response {
+    let today = Utc::now().date_naive();
+
+    let routes: Result<Vec<TodayRoute>, _> = query_stats::timed(
+        "feed_today",
+        sqlx::query_as(
+            "SELECT id, route_number, source, destination, expected_start_time
+             FROM routes
+             WHERE expected_start_time::date = $1
+             ORDER BY expected_start_time",
+        )
+        .bind(today)
+        .fetch_all(&state.pool),
+    )
+    .await;
+
+    match routes {
+        Ok(routes) => {
+            let items = routes
+                .into_iter()
+                .map(|r| {
+                    let detail_url =
+                        format!("/api/routes/{}/{}", r.id, r.expected_start_time.to_rfc3339());
+
+                    JsonFeedItem {
+                        id: detail_url.clone(),
+                        url: detail_url,
+                        title: format!("Route {}: {} \u{2192} {}", r.route_number, r.source, r.destination),
+                        content_text: format!(
+                            "Route {} departs {} at {}",
+                            r.route_number, r.source, r.expected_start_time
+                        ),
+                        date_published: r.expected_start_time,
+                    }
+                })
+                .collect();
+
+            Json(JsonFeed {
+                version: "https://jsonfeed.org/version/1.1",
+                title: "HZPP monitored departures — today",
+                home_page_url: "/",
+                feed_url: "/feeds/today.json",
+                items,
+            })
+            .into_response()
+        }
+        Err(e) => {
+            error!("error building today's feed: {e:?}");
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
@@ -0,0 +1,147 @@
+//! GeoJSON views over stations and route shapes, so the frontend can draw a
+//! Leaflet/MapLibre map straight from these responses instead of duplicating
+//! the station/stop data model in JS.
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde_json::json;
+use sqlx::{prelude::FromRow, query_as};
+use tracing::error;
+
+use crate::query_stats;
+
+use super::AppState;
+
+#[derive(FromRow)]
+struct StationPoint {
+    id: String,
+    name: String,
+    latitude: f64,
+    longitude: f64,
+}
+
+/// Every station as a GeoJSON `FeatureCollection` of `Point`s.
+pub async fn stations_geojson(State(state): State<AppState>) -> Response {
+    let stations: Result<Vec<StationPoint>, _> = query_stats::timed(
+        "geo_stations",
+        query_as("SELECT id, name, latitude, longitude FROM stations").fetch_all(&state.pool),
+    )
+    .await;
+
+    let stations = match stations {
+        Ok(stations) => stations,
+        Err(e) => {
+            error!("error fetching stations for geojson: {e:?}");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+
+    let features = stations
+        .into_iter()
+        .map(|s| {
+            json!({
+                "type": "Feature",
+                "geometry": { "type": "Point", "coordinates": [s.longitude, s.latitude] },
+                "properties": { "id": s.id, "name": s.name },
+            })
+        })
+        .collect::<Vec<_>>();
+
+    Json(json!({ "type": "FeatureCollection", "features": features })).into_response()
+}
+
+#[derive(FromRow)]
+struct RouteHeader {
+    id: String,
+    route_number: i32,
+    expected_start_time: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(FromRow)]
+struct StopPoint {
+    sequence: i16,
+    station_id: String,
+    latitude: f64,
+    longitude: f64,
+}
+
+/// The path of one run's scheduled stops as a GeoJSON `FeatureCollection`: a
+/// `LineString` following the stop order, plus one `Point` per stop. Unlike
+/// [`super::traces::trace_geojson`], this only carries the scheduled shape,
+/// not observed timings — for drawing the route itself on a map.
+pub async fn route_geojson(State(state): State<AppState>, Path(path_segment): Path<String>) -> Response {
+    let Some(numeric_id) = path_segment
+        .strip_suffix(".geojson")
+        .and_then(|id| id.parse::<i64>().ok())
+    else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+
+    let route: Result<Option<RouteHeader>, _> = query_stats::timed(
+        "geo_route_header",
+        query_as("SELECT id, route_number, expected_start_time FROM routes WHERE numeric_id = $1")
+            .bind(numeric_id)
+            .fetch_optional(&state.pool),
+    )
+    .await;
+
+    let route = match route {
+        Ok(Some(route)) => route,
+        Ok(None) => return StatusCode::NOT_FOUND.into_response(),
+        Err(e) => {
+            error!("error looking up run {numeric_id}: {e:?}");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+
+    let stops: Result<Vec<StopPoint>, _> = query_stats::timed(
+        "geo_route_stops",
+        query_as(
+            "SELECT s.sequence, s.station_id, st.latitude, st.longitude
+             FROM stops s
+             JOIN stations st ON st.id = s.station_id
+             WHERE s.route_id = $1 AND s.route_expected_start_time = $2
+             ORDER BY s.sequence",
+        )
+        .bind(&route.id)
+        .bind(route.expected_start_time)
+        .fetch_all(&state.pool),
+    )
+    .await;
+
+    let stops = match stops {
+        Ok(stops) => stops,
+        Err(e) => {
+            error!("error fetching stops for run {numeric_id}: {e:?}");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+
+    let line = stops
+        .iter()
+        .map(|s| json!([s.longitude, s.latitude]))
+        .collect::<Vec<_>>();
+
+    let points = stops
+        .iter()
+        .map(|s| {
+            json!({
+                "type": "Feature",
+                "geometry": { "type": "Point", "coordinates": [s.longitude, s.latitude] },
+                "properties": { "sequence": s.sequence, "station_id": s.station_id },
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let mut features = vec![json!({
+        "type": "Feature",
+        "geometry": { "type": "LineString", "coordinates": line },
+        "properties": { "route_id": route.id, "route_number": route.route_number },
+    })];
+    features.extend(points);
+
+    Json(json!({ "type": "FeatureCollection", "features": features })).into_response()
+}
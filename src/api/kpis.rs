@@ -0,0 +1,103 @@
+//! Serves the rolling-window KPIs maintained by the cache refresher, so the
+//! homepage and status endpoint never need to scan `routes` directly.
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::Utc;
+use serde::Deserialize;
+use sqlx::{prelude::FromRow, query_as};
+use tracing::error;
+
+use crate::query_stats;
+
+use super::AppState;
+
+#[derive(FromRow, serde::Serialize)]
+struct Kpi {
+    key: String,
+    value: f64,
+}
+
+#[derive(Deserialize)]
+pub struct KpiFilters {
+    county: Option<String>,
+}
+
+/// Rolling windows (in days) a county-scoped query recomputes on demand.
+/// Mirrors the cache refresher's own windows, since the precomputed `kpis`
+/// table only ever holds the network-wide figures.
+const KPI_WINDOWS_DAYS: [i64; 3] = [7, 30, 365];
+
+pub async fn kpis(State(state): State<AppState>, Query(filters): Query<KpiFilters>) -> Response {
+    let Some(county) = filters.county else {
+        let kpis: Result<Vec<Kpi>, _> = query_stats::timed(
+            "kpis",
+            query_as("SELECT key, value FROM kpis").fetch_all(&state.pool),
+        )
+        .await;
+
+        return match kpis {
+            Ok(kpis) => Json(kpis).into_response(),
+            Err(e) => {
+                error!("error reading kpis: {e:?}");
+                StatusCode::INTERNAL_SERVER_ERROR.into_response()
+            }
+        };
+    };
+
+    match county_kpis(&state, &county).await {
+        Ok(kpis) => Json(kpis).into_response(),
+        Err(e) => {
+            error!("error computing kpis for county {county}: {e:?}");
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+async fn county_kpis(state: &AppState, county: &str) -> Result<Vec<Kpi>, sqlx::Error> {
+    let mut kpis = vec![];
+
+    for window_days in KPI_WINDOWS_DAYS {
+        let since = Utc::now() - chrono::Duration::days(window_days);
+
+        let (monitored_runs, punctual_runs): (i64, i64) = query_as(
+            "SELECT
+                count(*) FILTER (WHERE real_end_time IS NOT NULL) as monitored_runs,
+                count(*) FILTER (WHERE real_end_time IS NOT NULL
+                    AND real_end_time - expected_end_time <= interval '5 minutes') as punctual_runs
+            FROM routes r
+            WHERE expected_start_time >= $1
+              AND EXISTS (
+                  SELECT 1 FROM stops s
+                  JOIN stations st ON st.id = s.station_id
+                  WHERE s.route_id = r.id
+                    AND s.route_expected_start_time = r.expected_start_time
+                    AND st.county = $2
+              )",
+        )
+        .bind(since)
+        .bind(county)
+        .fetch_one(&state.pool)
+        .await?;
+
+        let punctuality = if monitored_runs == 0 {
+            0.0
+        } else {
+            punctual_runs as f64 / monitored_runs as f64
+        };
+
+        kpis.push(Kpi {
+            key: format!("punctuality_{window_days}d"),
+            value: punctuality,
+        });
+        kpis.push(Kpi {
+            key: format!("monitored_runs_{window_days}d"),
+            value: monitored_runs as f64,
+        });
+    }
+
+    Ok(kpis)
+}
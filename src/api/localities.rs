@@ -0,0 +1,82 @@
+//! City-level punctuality stats, aggregated over the stations a locality's
+//! (operator-maintained) mapping table assigns to it — the granularity
+//! journalists usually ask for instead of a single station or route.
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde_json::json;
+use sqlx::{prelude::FromRow, query_as, query_scalar};
+use tracing::error;
+
+use crate::query_stats;
+
+use super::AppState;
+
+#[derive(FromRow)]
+struct LocalityStats {
+    observed_stops: i64,
+    punctual_stops: i64,
+    avg_minutes_late: Option<f64>,
+}
+
+pub async fn stats(State(state): State<AppState>, Path(id): Path<i32>) -> Response {
+    let name: Result<Option<String>, _> = query_stats::timed(
+        "locality_name",
+        query_scalar("SELECT name FROM localities WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&state.pool),
+    )
+    .await;
+
+    let name = match name {
+        Ok(Some(name)) => name,
+        Ok(None) => return StatusCode::NOT_FOUND.into_response(),
+        Err(e) => {
+            error!("error looking up locality {id}: {e:?}");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+
+    let stats: Result<LocalityStats, _> = query_stats::timed(
+        "locality_stats",
+        query_as(
+            "SELECT
+                count(*) FILTER (WHERE s.real_arrival IS NOT NULL) as observed_stops,
+                count(*) FILTER (WHERE s.real_arrival IS NOT NULL
+                    AND s.real_arrival - s.expected_arrival <= interval '5 minutes') as punctual_stops,
+                avg(extract(epoch from (s.real_arrival - s.expected_arrival)) / 60)::float8 as avg_minutes_late
+            FROM stops s
+            JOIN stations st ON st.id = s.station_id
+            WHERE st.locality_id = $1",
+        )
+        .bind(id)
+        .fetch_one(&state.pool),
+    )
+    .await;
+
+    match stats {
+        Ok(stats) => {
+            let punctuality = if stats.observed_stops == 0 {
+                0.0
+            } else {
+                stats.punctual_stops as f64 / stats.observed_stops as f64
+            };
+
+            Json(json!({
+                "locality_id": id,
+                "name": name,
+                "observed_stops": stats.observed_stops,
+                "punctuality": punctuality,
+                "avg_minutes_late": stats.avg_minutes_late,
+            }))
+            .into_response()
+        }
+        Err(e) => {
+            error!("error computing stats for locality {id}: {e:?}");
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
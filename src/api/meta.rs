@@ -0,0 +1,311 @@
+//! A hand-maintained data dictionary for the entities this API exposes (via
+//! JSON responses and the CSV `export` endpoints), so researchers don't have
+//! to reverse-engineer column meaning from a sample export. Kept alongside
+//! the DTOs rather than generated from them, since provenance and caveats
+//! aren't something the type system can express.
+use axum::{
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Serialize;
+
+#[derive(Serialize)]
+pub struct FieldDoc {
+    name: &'static str,
+    data_type: &'static str,
+    unit: Option<&'static str>,
+    provenance: &'static str,
+    caveats: Option<&'static str>,
+}
+
+#[derive(Serialize)]
+pub struct EntityDoc {
+    name: &'static str,
+    description: &'static str,
+    fields: &'static [FieldDoc],
+}
+
+const ROUTE_FIELDS: &[FieldDoc] = &[
+    FieldDoc {
+        name: "id",
+        data_type: "string",
+        unit: None,
+        provenance: "derived from the HZPP planner's train identifier at ingest time",
+        caveats: None,
+    },
+    FieldDoc {
+        name: "route_number",
+        data_type: "integer",
+        unit: None,
+        provenance: "HZPP's published train number",
+        caveats: Some("not unique on its own — the same number runs on many days"),
+    },
+    FieldDoc {
+        name: "source",
+        data_type: "string",
+        unit: None,
+        provenance: "HZPP planner",
+        caveats: None,
+    },
+    FieldDoc {
+        name: "destination",
+        data_type: "string",
+        unit: None,
+        provenance: "HZPP planner",
+        caveats: None,
+    },
+    FieldDoc {
+        name: "expected_start_time",
+        data_type: "timestamp (UTC)",
+        unit: None,
+        provenance: "HZPP planner's published timetable",
+        caveats: None,
+    },
+    FieldDoc {
+        name: "expected_end_time",
+        data_type: "timestamp (UTC)",
+        unit: None,
+        provenance: "HZPP planner's published timetable",
+        caveats: None,
+    },
+    FieldDoc {
+        name: "real_start_time",
+        data_type: "timestamp (UTC), nullable",
+        unit: None,
+        provenance: "observed from the live delay checker",
+        caveats: Some("null until departure is observed; may be backed out from a later stop's delay if the origin departure itself wasn't caught"),
+    },
+    FieldDoc {
+        name: "real_end_time",
+        data_type: "timestamp (UTC), nullable",
+        unit: None,
+        provenance: "observed from the live delay checker",
+        caveats: Some("null until the run is observed to finish"),
+    },
+    FieldDoc {
+        name: "max_delay_minutes",
+        data_type: "integer, nullable",
+        unit: Some("minutes"),
+        provenance: "largest delay observed at any point during the run",
+        caveats: None,
+    },
+    FieldDoc {
+        name: "final_delay_minutes",
+        data_type: "integer, nullable",
+        unit: Some("minutes"),
+        provenance: "delay the run finished with, set once real_end_time is known",
+        caveats: None,
+    },
+];
+
+const STOP_FIELDS: &[FieldDoc] = &[
+    FieldDoc {
+        name: "route_id",
+        data_type: "string",
+        unit: None,
+        provenance: "foreign key to routes.id",
+        caveats: None,
+    },
+    FieldDoc {
+        name: "route_expected_start_time",
+        data_type: "timestamp (UTC)",
+        unit: None,
+        provenance: "part of the composite foreign key to routes, since route ids repeat across days",
+        caveats: None,
+    },
+    FieldDoc {
+        name: "sequence",
+        data_type: "integer",
+        unit: None,
+        provenance: "position of this stop along the route, starting at 0",
+        caveats: None,
+    },
+    FieldDoc {
+        name: "station_id",
+        data_type: "string",
+        unit: None,
+        provenance: "foreign key to stations.id",
+        caveats: None,
+    },
+    FieldDoc {
+        name: "expected_arrival",
+        data_type: "timestamp (UTC)",
+        unit: None,
+        provenance: "HZPP planner's published timetable",
+        caveats: None,
+    },
+    FieldDoc {
+        name: "real_arrival",
+        data_type: "timestamp (UTC), nullable",
+        unit: None,
+        provenance: "observed from the live delay checker",
+        caveats: Some("null until this stop's arrival is observed"),
+    },
+    FieldDoc {
+        name: "expected_departure",
+        data_type: "timestamp (UTC)",
+        unit: None,
+        provenance: "HZPP planner's published timetable",
+        caveats: None,
+    },
+    FieldDoc {
+        name: "real_departure",
+        data_type: "timestamp (UTC), nullable",
+        unit: None,
+        provenance: "observed from the live delay checker",
+        caveats: Some("null until this stop's departure is observed; earlier than expected_departure marks an early departure"),
+    },
+];
+
+const STATION_FIELDS: &[FieldDoc] = &[
+    FieldDoc {
+        name: "id",
+        data_type: "string",
+        unit: None,
+        provenance: "HZPP planner's station identifier",
+        caveats: None,
+    },
+    FieldDoc {
+        name: "code",
+        data_type: "integer",
+        unit: None,
+        provenance: "HZPP's numeric station code",
+        caveats: None,
+    },
+    FieldDoc {
+        name: "name",
+        data_type: "string",
+        unit: None,
+        provenance: "HZPP planner",
+        caveats: None,
+    },
+    FieldDoc {
+        name: "latitude",
+        data_type: "float",
+        unit: Some("degrees"),
+        provenance: "HZPP planner",
+        caveats: None,
+    },
+    FieldDoc {
+        name: "longitude",
+        data_type: "float",
+        unit: Some("degrees"),
+        provenance: "HZPP planner",
+        caveats: None,
+    },
+    FieldDoc {
+        name: "county",
+        data_type: "string, nullable",
+        unit: None,
+        provenance: "operator-maintained station-to-county mapping",
+        caveats: Some("null until an operator assigns this station a county"),
+    },
+];
+
+const WEATHER_EVENT_FIELDS: &[FieldDoc] = &[
+    FieldDoc {
+        name: "date",
+        data_type: "date",
+        unit: None,
+        provenance: "operator-recorded via POST /admin/weather-events",
+        caveats: None,
+    },
+    FieldDoc {
+        name: "bad_weather",
+        data_type: "boolean",
+        unit: None,
+        provenance: "operator-recorded via POST /admin/weather-events",
+        caveats: Some("manually curated, not sourced from a live weather feed"),
+    },
+];
+
+const DELAY_UPDATE_FIELDS: &[FieldDoc] = &[
+    FieldDoc {
+        name: "route_id",
+        data_type: "string",
+        unit: None,
+        provenance: "foreign key to routes.id",
+        caveats: None,
+    },
+    FieldDoc {
+        name: "route_number",
+        data_type: "integer",
+        unit: None,
+        provenance: "HZPP's published train number",
+        caveats: None,
+    },
+    FieldDoc {
+        name: "sequence",
+        data_type: "integer, nullable",
+        unit: None,
+        provenance: "the stop this update is about",
+        caveats: Some("null for a delay-only update (e.g. a new worst-delay-so-far) not tied to a particular stop"),
+    },
+    FieldDoc {
+        name: "station_id",
+        data_type: "string, nullable",
+        unit: None,
+        provenance: "foreign key to stations.id, mirroring sequence",
+        caveats: Some("null under the same circumstances as sequence"),
+    },
+    FieldDoc {
+        name: "event",
+        data_type: "string",
+        unit: None,
+        provenance: "\"arrival\", \"departure\", or \"delay\" for the sequence-less worst-delay-so-far case",
+        caveats: None,
+    },
+    FieldDoc {
+        name: "minutes_late",
+        data_type: "integer",
+        unit: Some("minutes"),
+        provenance: "observed from the live delay checker",
+        caveats: None,
+    },
+    FieldDoc {
+        name: "updated_at",
+        data_type: "timestamp (UTC)",
+        unit: None,
+        provenance: "server clock at the moment the update was recorded",
+        caveats: None,
+    },
+    FieldDoc {
+        name: "upstream_updated_at",
+        data_type: "timestamp (UTC)",
+        unit: None,
+        provenance: "the \"Stanje vlaka od ...\" line on HZPP's own delay page, parsed at fetch time",
+        caveats: Some("can trail updated_at noticeably when HZPP's page itself hasn't refreshed in a while"),
+    },
+];
+
+const DICTIONARY: &[EntityDoc] = &[
+    EntityDoc {
+        name: "route",
+        description: "One scheduled train run, from its origin departure to its destination arrival.",
+        fields: ROUTE_FIELDS,
+    },
+    EntityDoc {
+        name: "stop",
+        description: "One scheduled stop along a route's path.",
+        fields: STOP_FIELDS,
+    },
+    EntityDoc {
+        name: "station",
+        description: "A physical station a route can stop at.",
+        fields: STATION_FIELDS,
+    },
+    EntityDoc {
+        name: "weather_event",
+        description: "Operator-curated flag for whether a given day had bad weather, used to split punctuality stats.",
+        fields: WEATHER_EVENT_FIELDS,
+    },
+    EntityDoc {
+        name: "delay_update",
+        description: "One message pushed over /ws/delays or /delays/stream when the delay checker records a new observation.",
+        fields: DELAY_UPDATE_FIELDS,
+    },
+];
+
+pub async fn dictionary() -> Response {
+    Json(DICTIONARY).into_response()
+}
@@ -0,0 +1,94 @@
+//! Shared RFC 5988 pagination for list endpoints: slices an in-memory
+//! collection to the requested page and attaches `Link` (first/prev/next/last)
+//! and `X-Total-Count` headers, so callers don't have to build pagers by hand.
+use axum::{
+    http::{header, HeaderValue, Uri},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+
+const DEFAULT_PER_PAGE: u32 = 50;
+const MAX_PER_PAGE: u32 = 200;
+
+/// Shared envelope for keyset-paginated list endpoints, the variant for
+/// tables too large to page by offset (see [`paginate`] for that one):
+/// a page of items plus an opaque-to-the-client cursor for the next page,
+/// `None` once there's nothing more to fetch. `total` is only set when the
+/// query already produces an exact count cheaply alongside the page — most
+/// keyset queries don't, since counting a large filtered range defeats the
+/// point of avoiding `OFFSET`.
+#[derive(Serialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub next_cursor: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total: Option<i64>,
+}
+
+impl<T> Page<T> {
+    pub fn new(items: Vec<T>, next_cursor: Option<serde_json::Value>) -> Self {
+        Page { items, next_cursor, total: None }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct PageParams {
+    page: Option<u32>,
+    per_page: Option<u32>,
+}
+
+impl PageParams {
+    fn page(&self) -> u32 {
+        self.page.unwrap_or(1).max(1)
+    }
+
+    fn per_page(&self) -> u32 {
+        self.per_page.unwrap_or(DEFAULT_PER_PAGE).clamp(1, MAX_PER_PAGE)
+    }
+}
+
+/// Slices `items` to the page requested by `params` and returns a JSON
+/// response carrying `Link` and `X-Total-Count` headers describing the whole collection.
+pub fn paginate<T: Serialize>(items: Vec<T>, params: &PageParams, uri: &Uri) -> Response {
+    let total = items.len() as u32;
+    let page = params.page();
+    let per_page = params.per_page();
+    let last_page = total.div_ceil(per_page).max(1);
+
+    let start = ((page - 1) * per_page) as usize;
+    let page_items: Vec<T> = items.into_iter().skip(start).take(per_page as usize).collect();
+
+    let mut response = Json(page_items).into_response();
+    let headers = response.headers_mut();
+    headers.insert(
+        header::HeaderName::from_static("x-total-count"),
+        HeaderValue::from_str(&total.to_string()).unwrap(),
+    );
+    headers.insert(
+        header::LINK,
+        HeaderValue::from_str(&build_link_header(uri, page, per_page, last_page)).unwrap(),
+    );
+    response
+}
+
+fn build_link_header(uri: &Uri, page: u32, per_page: u32, last_page: u32) -> String {
+    let path = uri.path();
+    let mut links = vec![format!("<{path}?page=1&per_page={per_page}>; rel=\"first\"")];
+    if page > 1 {
+        links.push(format!(
+            "<{path}?page={}&per_page={per_page}>; rel=\"prev\"",
+            page - 1
+        ));
+    }
+    if page < last_page {
+        links.push(format!(
+            "<{path}?page={}&per_page={per_page}>; rel=\"next\"",
+            page + 1
+        ));
+    }
+    links.push(format!(
+        "<{path}?page={last_page}&per_page={per_page}>; rel=\"last\""
+    ));
+    links.join(", ")
+}
@@ -0,0 +1,148 @@
+//! Estimated live train position, dead-reckoned between the last confirmed
+//! stop and the next expected one using the train's current delay — there's
+//! no live GPS feed, so this is a schedule-based estimate rather than a
+//! real-time position.
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::{prelude::FromRow, query_as};
+use tracing::error;
+
+use crate::query_stats;
+
+use super::AppState;
+
+#[derive(FromRow)]
+struct RunningRoute {
+    id: String,
+    expected_start_time: DateTime<Utc>,
+}
+
+#[derive(FromRow)]
+struct StopPoint {
+    station_id: String,
+    latitude: f64,
+    longitude: f64,
+    expected_arrival: DateTime<Utc>,
+    real_arrival: Option<DateTime<Utc>>,
+    expected_departure: DateTime<Utc>,
+    real_departure: Option<DateTime<Utc>>,
+}
+
+#[derive(Serialize)]
+struct EstimatedPosition {
+    route_id: String,
+    route_number: i32,
+    latitude: f64,
+    longitude: f64,
+    /// 0.0 at `from_station_id`, 1.0 at `to_station_id`.
+    progress: f64,
+    minutes_late: i32,
+    from_station_id: String,
+    to_station_id: String,
+}
+
+/// Interpolates the currently-running route numbered `route_number` between
+/// the last stop it's been observed at and the next one, assuming its
+/// current delay holds steady across the segment.
+pub async fn estimated_position(State(state): State<AppState>, Path(route_number): Path<i32>) -> Response {
+    let route: Result<Option<RunningRoute>, _> = query_stats::timed(
+        "position_running_route",
+        query_as(
+            "SELECT id, expected_start_time FROM routes
+             WHERE route_number = $1 AND real_start_time IS NOT NULL AND real_end_time IS NULL
+             ORDER BY expected_start_time DESC
+             LIMIT 1",
+        )
+        .bind(route_number)
+        .fetch_optional(&state.pool),
+    )
+    .await;
+
+    let route = match route {
+        Ok(Some(route)) => route,
+        Ok(None) => return StatusCode::NOT_FOUND.into_response(),
+        Err(e) => {
+            error!("error looking up running route {route_number}: {e:?}");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+
+    let stops: Result<Vec<StopPoint>, _> = query_stats::timed(
+        "position_stops",
+        query_as(
+            "SELECT s.station_id, st.latitude, st.longitude,
+                    s.expected_arrival, s.real_arrival, s.expected_departure, s.real_departure
+             FROM stops s
+             JOIN stations st ON st.id = s.station_id
+             WHERE s.route_id = $1 AND s.route_expected_start_time = $2
+             ORDER BY s.sequence",
+        )
+        .bind(&route.id)
+        .bind(route.expected_start_time)
+        .fetch_all(&state.pool),
+    )
+    .await;
+
+    let stops = match stops {
+        Ok(stops) => stops,
+        Err(e) => {
+            error!("error fetching stops for route {route_number}: {e:?}");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+
+    let Some(current_index) =
+        stops.iter().rposition(|s| s.real_arrival.is_some() || s.real_departure.is_some())
+    else {
+        // Started, but no stop observed yet (e.g. still sitting at its origin).
+        return StatusCode::CONFLICT.into_response();
+    };
+
+    let minutes_late = state
+        .live_comparisons
+        .snapshot()
+        .await
+        .iter()
+        .find(|c| c.route_id == route.id)
+        .map(|c| c.minutes_late)
+        .unwrap_or(0);
+
+    let current = &stops[current_index];
+    let delay = chrono::Duration::try_minutes(minutes_late.into()).unwrap();
+
+    let (latitude, longitude, progress, to_station_id) = match stops.get(current_index + 1) {
+        Some(next) => {
+            let segment_start = current.expected_departure + delay;
+            let segment_end = next.expected_arrival + delay;
+            let total_secs = (segment_end - segment_start).num_seconds().max(1) as f64;
+            let elapsed_secs = (Utc::now() - segment_start).num_seconds() as f64;
+            let progress = (elapsed_secs / total_secs).clamp(0.0, 1.0);
+
+            (
+                current.latitude + (next.latitude - current.latitude) * progress,
+                current.longitude + (next.longitude - current.longitude) * progress,
+                progress,
+                next.station_id.clone(),
+            )
+        }
+        None => (current.latitude, current.longitude, 1.0, current.station_id.clone()),
+    };
+
+    Json(EstimatedPosition {
+        route_id: route.id,
+        route_number,
+        latitude,
+        longitude,
+        progress,
+        minutes_late,
+        from_station_id: current.station_id.clone(),
+        to_station_id,
+    })
+    .into_response()
+}
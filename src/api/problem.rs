@@ -0,0 +1,82 @@
+//! RFC 7807 ("Problem Details for HTTP APIs") error responses: a stable
+//! `code` plus a human-readable `detail`, instead of a bare 500 with an
+//! empty body. New endpoints should prefer returning `Result<T, ApiError>`
+//! and propagating `sqlx::Error`/`anyhow::Error` with `?` over hand-matching
+//! every query's result, the way [`super::stats::StatsError`] already does
+//! for its own, more specific, error codes.
+use axum::{
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde_json::json;
+use tracing::error;
+
+use crate::query_stats;
+
+/// General-purpose API error for endpoints that don't need their own
+/// domain-specific error enum (see [`super::stats::StatsError`] for one that
+/// does). Grows new variants as handlers need them.
+#[derive(thiserror::Error, Debug)]
+pub enum ApiError {
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+    #[error(transparent)]
+    Internal(#[from] anyhow::Error),
+}
+
+impl ApiError {
+    fn status(&self) -> StatusCode {
+        match self {
+            ApiError::Database(e) if query_stats::is_statement_timeout(e) => StatusCode::REQUEST_TIMEOUT,
+            ApiError::Database(_) | ApiError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn code(&self) -> &'static str {
+        match self {
+            ApiError::Database(e) if query_stats::is_statement_timeout(e) => "query_too_broad",
+            ApiError::Database(_) => "database_error",
+            ApiError::Internal(_) => "internal_error",
+        }
+    }
+}
+
+/// Renders `status`/`code`/`detail` as an `application/problem+json` body.
+/// Shared by [`ApiError`] and [`super::stats::StatsError`] so every error
+/// shape in the API looks the same on the wire.
+pub fn problem_response(status: StatusCode, code: &str, detail: &str) -> Response {
+    let body = json!({
+        "type": "about:blank",
+        "title": status.canonical_reason().unwrap_or("Error"),
+        "status": status.as_u16(),
+        "detail": detail,
+        "code": code,
+    });
+
+    (status, [(header::CONTENT_TYPE, "application/problem+json")], Json(body)).into_response()
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let status = self.status();
+        let code = self.code();
+
+        // Database/internal failures are logged in full but never echoed
+        // back verbatim — same as every other 500 in this API, just with a
+        // stable code and shape now instead of an empty body.
+        let detail = match &self {
+            ApiError::Database(e) if !query_stats::is_statement_timeout(e) => {
+                error!("database error: {e:?}");
+                "an internal error occurred".to_string()
+            }
+            ApiError::Internal(e) => {
+                error!("internal error: {e:?}");
+                "an internal error occurred".to_string()
+            }
+            other => other.to_string(),
+        };
+
+        problem_response(status, code, &detail)
+    }
+}
@@ -0,0 +1,120 @@
+//! Per-IP / per-API-key request rate limiting, applied ahead of every `/api`
+//! route so a single misbehaving client can't hammer the stats queries and
+//! starve the delay checker's DB pool. There's no `tower-governor`/`governor`
+//! crate available in this build, so this follows the same
+//! sliding-window-of-recent-timestamps approach already used for the
+//! anonymous "watch tonight" rate limit, generalized to any caller and keyed
+//! by IP or by an `X-Api-Key` header recognized in [`crate::config::Config::api_keys`].
+use std::collections::{HashMap, VecDeque};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    http::{HeaderMap, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use tokio::{sync::Mutex, time::sleep};
+
+const SWEEP_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+#[derive(Clone, Debug)]
+pub struct RateLimitConfig {
+    pub per_ip_per_minute: u64,
+    pub per_api_key_per_minute: u64,
+    pub api_keys: Vec<String>,
+}
+
+#[derive(Clone, Default)]
+pub struct RateLimiter {
+    buckets: Arc<Mutex<HashMap<String, VecDeque<Instant>>>>,
+}
+
+impl RateLimiter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records a request against `key`'s bucket and reports whether it was
+    /// still under `limit` requests in the past minute.
+    async fn check(&self, key: String, limit: u64) -> bool {
+        let mut buckets = self.buckets.lock().await;
+        let now = Instant::now();
+        let bucket = buckets.entry(key).or_default();
+
+        while bucket
+            .front()
+            .is_some_and(|t| now.duration_since(*t) > Duration::from_secs(60))
+        {
+            bucket.pop_front();
+        }
+
+        if bucket.len() as u64 >= limit {
+            return false;
+        }
+
+        bucket.push_back(now);
+        true
+    }
+
+    /// Drops buckets that have fully aged out, so a flood of distinct
+    /// one-off keys (spoofed IPs, throwaway `X-Api-Key` values) that never
+    /// come back can't grow `buckets` forever — `check` only trims a key's
+    /// own deque when that same key shows up again.
+    async fn sweep_stale_buckets(&self) {
+        let mut buckets = self.buckets.lock().await;
+        let now = Instant::now();
+
+        buckets.retain(|_, bucket| {
+            while bucket
+                .front()
+                .is_some_and(|t| now.duration_since(*t) > Duration::from_secs(60))
+            {
+                bucket.pop_front();
+            }
+
+            !bucket.is_empty()
+        });
+    }
+}
+
+/// Runs forever, sweeping stale rate-limit buckets every [`SWEEP_INTERVAL`].
+pub async fn sweep_stale_buckets_periodically(rate_limiter: RateLimiter) {
+    loop {
+        sleep(SWEEP_INTERVAL).await;
+        rate_limiter.sweep_stale_buckets().await;
+    }
+}
+
+pub async fn enforce(
+    State(state): State<super::AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    request: Request,
+    next: Next,
+) -> Response {
+    let api_key = headers.get("X-Api-Key").and_then(|v| v.to_str().ok());
+
+    let allowed = match api_key {
+        Some(key) if state.rate_limit_config.api_keys.iter().any(|k| k == key) => {
+            state
+                .rate_limiter
+                .check(format!("key:{key}"), state.rate_limit_config.per_api_key_per_minute)
+                .await
+        }
+        _ => {
+            state
+                .rate_limiter
+                .check(format!("ip:{}", addr.ip()), state.rate_limit_config.per_ip_per_minute)
+                .await
+        }
+    };
+
+    if !allowed {
+        return StatusCode::TOO_MANY_REQUESTS.into_response();
+    }
+
+    next.run(request).await
+}
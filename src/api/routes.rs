@@ -0,0 +1,404 @@
+//! Route lookup by the stable surrogate id/slug, for links that must keep
+//! resolving even if the upstream's own route id format changes.
+use axum::{
+    extract::{OriginalUri, Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use serde::Deserialize;
+use serde_json::json;
+use sqlx::{prelude::FromRow, Row};
+use tracing::error;
+
+use crate::{background_services::watchlist::WatchError, query_stats};
+
+use super::{
+    pagination::{paginate, PageParams},
+    AppState,
+};
+
+#[derive(Deserialize)]
+pub struct RouteFilters {
+    date: Option<NaiveDate>,
+    route_number: Option<i32>,
+    source: Option<String>,
+    destination: Option<String>,
+    route_type: Option<i16>,
+    /// Narrows to routes marked wheelchair-accessible (or, with `false`, to
+    /// ones explicitly marked not accessible).
+    accessible: Option<bool>,
+    /// Narrows to routes that allow bikes (or, with `false`, to ones that don't).
+    bikes: Option<bool>,
+}
+
+#[derive(FromRow, serde::Serialize)]
+struct ListedRoute {
+    id: String,
+    numeric_id: Option<i64>,
+    slug: String,
+    route_number: i32,
+    source: String,
+    destination: String,
+    expected_start_time: DateTime<Utc>,
+    expected_end_time: DateTime<Utc>,
+    real_start_time: Option<DateTime<Utc>>,
+    real_end_time: Option<DateTime<Utc>>,
+    wheelchair_accessible: bool,
+    bikes_allowed: bool,
+}
+
+/// Lists routes, most recent first, with optional filters. `date` matches
+/// against `expected_start_time`'s calendar day; `source`/`destination` are
+/// substring matches so `?source=zagreb` finds every Zagreb station variant;
+/// `accessible`/`bikes` match the upstream GTFS-style `wheelchair_accessible`/
+/// `bikes_allowed` codes, where `1` means allowed and anything else doesn't.
+pub async fn list(
+    State(state): State<AppState>,
+    Query(filters): Query<RouteFilters>,
+    Query(page_params): Query<PageParams>,
+    OriginalUri(uri): OriginalUri,
+) -> Response {
+    let routes: Result<Vec<ListedRoute>, _> = query_stats::timed(
+        "routes_list",
+        sqlx::query_as(
+            "SELECT id, numeric_id, slug, route_number, source, destination,
+                    expected_start_time, expected_end_time, real_start_time, real_end_time,
+                    wheelchair_accessible = 1 as wheelchair_accessible,
+                    bikes_allowed = 1 as bikes_allowed
+             FROM routes
+             WHERE ($1::date IS NULL OR expected_start_time::date = $1)
+               AND ($2::int IS NULL OR route_number = $2)
+               AND ($3::text IS NULL OR source ILIKE '%' || $3 || '%')
+               AND ($4::text IS NULL OR destination ILIKE '%' || $4 || '%')
+               AND ($5::smallint IS NULL OR route_type = $5)
+               AND ($6::bool IS NULL OR (wheelchair_accessible = 1) = $6)
+               AND ($7::bool IS NULL OR (bikes_allowed = 1) = $7)
+             ORDER BY expected_start_time DESC",
+        )
+        .bind(filters.date)
+        .bind(filters.route_number)
+        .bind(&filters.source)
+        .bind(&filters.destination)
+        .bind(filters.route_type)
+        .bind(filters.accessible)
+        .bind(filters.bikes)
+        .fetch_all(&state.pool),
+    )
+    .await;
+
+    match routes {
+        Ok(routes) => paginate(routes, &page_params, &uri),
+        Err(e) => {
+            error!("error listing routes: {e:?}");
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+/// Accepts either the numeric surrogate id or the slug in `:id`.
+pub async fn get_route(State(state): State<AppState>, Path(id): Path<String>) -> Response {
+    let numeric_id: Option<i64> = id.parse().ok();
+
+    let row = query_stats::timed(
+        "route_by_id_or_slug",
+        sqlx::query(
+            "SELECT id, numeric_id, slug, route_number, source, destination,
+                    expected_start_time, expected_end_time, real_start_time, real_end_time,
+                    wheelchair_accessible = 1 as wheelchair_accessible,
+                    bikes_allowed = 1 as bikes_allowed
+             FROM routes
+             WHERE numeric_id = $1 OR slug = $2
+             ORDER BY expected_start_time DESC
+             LIMIT 1",
+        )
+        .bind(numeric_id)
+        .bind(&id)
+        .fetch_optional(&state.pool),
+    )
+    .await;
+
+    match row {
+        Ok(Some(row)) => Json(json!({
+            "id": row.get::<String, _>("id"),
+            "numeric_id": row.get::<i64, _>("numeric_id"),
+            "slug": row.get::<String, _>("slug"),
+            "route_number": row.get::<i32, _>("route_number"),
+            "source": row.get::<String, _>("source"),
+            "destination": row.get::<String, _>("destination"),
+            "expected_start_time": row.get::<chrono::DateTime<chrono::Utc>, _>("expected_start_time"),
+            "expected_end_time": row.get::<chrono::DateTime<chrono::Utc>, _>("expected_end_time"),
+            "real_start_time": row.get::<Option<chrono::DateTime<chrono::Utc>>, _>("real_start_time"),
+            "real_end_time": row.get::<Option<chrono::DateTime<chrono::Utc>>, _>("real_end_time"),
+            "wheelchair_accessible": row.get::<bool, _>("wheelchair_accessible"),
+            "bikes_allowed": row.get::<bool, _>("bikes_allowed"),
+        }))
+        .into_response(),
+        Ok(None) => StatusCode::NOT_FOUND.into_response(),
+        Err(e) => {
+            error!("error looking up route {id}: {e:?}");
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+#[derive(FromRow, serde::Serialize)]
+struct RouteStop {
+    station_id: String,
+    sequence: i16,
+    expected_arrival: DateTime<Utc>,
+    real_arrival: Option<DateTime<Utc>>,
+    expected_departure: DateTime<Utc>,
+    real_departure: Option<DateTime<Utc>>,
+}
+
+/// Full detail for one run, identified by its upstream `id` and the
+/// `expected_start_time` that disambiguates repeated runs of the same route.
+pub async fn get_route_detail(
+    State(state): State<AppState>,
+    Path((id, expected_start_time)): Path<(String, DateTime<Utc>)>,
+) -> Response {
+    let route = query_stats::timed(
+        "route_detail",
+        sqlx::query(
+            "SELECT id, numeric_id, slug, route_number, source, destination,
+                    expected_start_time, expected_end_time, real_start_time, real_end_time,
+                    narrative_summary,
+                    wheelchair_accessible = 1 as wheelchair_accessible,
+                    bikes_allowed = 1 as bikes_allowed
+             FROM routes
+             WHERE id = $1 AND expected_start_time = $2",
+        )
+        .bind(&id)
+        .bind(expected_start_time)
+        .fetch_optional(&state.pool),
+    )
+    .await;
+
+    let route = match route {
+        Ok(Some(route)) => route,
+        Ok(None) => return StatusCode::NOT_FOUND.into_response(),
+        Err(e) => {
+            error!("error looking up route {id} at {expected_start_time}: {e:?}");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+
+    let stops: Result<Vec<RouteStop>, _> = query_stats::timed(
+        "route_detail_stops",
+        sqlx::query_as(
+            "SELECT station_id, sequence, expected_arrival, real_arrival,
+                    expected_departure, real_departure
+             FROM stops
+             WHERE route_id = $1 AND route_expected_start_time = $2
+             ORDER BY sequence",
+        )
+        .bind(&id)
+        .bind(expected_start_time)
+        .fetch_all(&state.pool),
+    )
+    .await;
+
+    let stops = match stops {
+        Ok(stops) => stops,
+        Err(e) => {
+            error!("error loading stops for route {id} at {expected_start_time}: {e:?}");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+
+    Json(json!({
+        "id": route.get::<String, _>("id"),
+        "numeric_id": route.get::<i64, _>("numeric_id"),
+        "slug": route.get::<String, _>("slug"),
+        "route_number": route.get::<i32, _>("route_number"),
+        "source": route.get::<String, _>("source"),
+        "destination": route.get::<String, _>("destination"),
+        "expected_start_time": route.get::<chrono::DateTime<chrono::Utc>, _>("expected_start_time"),
+        "expected_end_time": route.get::<chrono::DateTime<chrono::Utc>, _>("expected_end_time"),
+        "real_start_time": route.get::<Option<chrono::DateTime<chrono::Utc>>, _>("real_start_time"),
+        "real_end_time": route.get::<Option<chrono::DateTime<chrono::Utc>>, _>("real_end_time"),
+        "narrative_summary": route.get::<Option<String>, _>("narrative_summary"),
+        "wheelchair_accessible": route.get::<bool, _>("wheelchair_accessible"),
+        "bikes_allowed": route.get::<bool, _>("bikes_allowed"),
+        "stops": stops,
+    }))
+    .into_response()
+}
+
+#[derive(FromRow)]
+struct ScheduledDeparture {
+    id: String,
+    source: String,
+    destination: String,
+    expected_start_time: DateTime<Utc>,
+    expected_end_time: DateTime<Utc>,
+    real_start_time: Option<DateTime<Utc>>,
+}
+
+/// Escapes the characters RFC 5545 requires escaped in `TEXT` property
+/// values: backslash, comma, semicolon and newline.
+fn ics_escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace(',', "\\,").replace(';', "\\;").replace('\n', "\\n")
+}
+
+/// An ICS feed of `route_number`'s scheduled departures over the next two
+/// weeks, so a commuter can subscribe once in their calendar app rather than
+/// checking the site every morning. There's no `icalendar` crate in this
+/// build, so the feed is assembled by hand — RFC 5545 is simple enough for a
+/// handful of flat `TEXT` properties.
+pub async fn calendar_ics(State(state): State<AppState>, Path(route_number): Path<i32>) -> Response {
+    let now = Utc::now();
+
+    let departures: Result<Vec<ScheduledDeparture>, _> = query_stats::timed(
+        "route_calendar_ics",
+        sqlx::query_as(
+            "SELECT id, source, destination, expected_start_time, expected_end_time, real_start_time
+             FROM routes
+             WHERE route_number = $1 AND expected_start_time BETWEEN $2 AND $3
+             ORDER BY expected_start_time",
+        )
+        .bind(route_number)
+        .bind(now)
+        .bind(now + Duration::days(14))
+        .fetch_all(&state.pool),
+    )
+    .await;
+
+    let departures = match departures {
+        Ok(departures) => departures,
+        Err(e) => {
+            error!("error building calendar feed for route {route_number}: {e:?}");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+
+    let mut ics = String::from("BEGIN:VCALENDAR\r\nVERSION:2.0\r\nPRODID:-//hzpp-delay-stats//route calendar//EN\r\n");
+
+    for d in departures {
+        let summary = format!("Route {route_number}: {} \u{2192} {}", d.source, d.destination);
+        let description = match d.real_start_time {
+            Some(real) if real > d.expected_start_time => {
+                format!("Departed {} late on its last run.", (real - d.expected_start_time).num_minutes())
+            }
+            Some(_) => "Departed on time on its last run.".to_string(),
+            None => "No delay data yet for this run.".to_string(),
+        };
+
+        ics.push_str("BEGIN:VEVENT\r\n");
+        ics.push_str(&format!("UID:{}@hzpp-delay-stats\r\n", d.id));
+        ics.push_str(&format!("DTSTAMP:{}\r\n", now.format("%Y%m%dT%H%M%SZ")));
+        ics.push_str(&format!("DTSTART:{}\r\n", d.expected_start_time.format("%Y%m%dT%H%M%SZ")));
+        ics.push_str(&format!("DTEND:{}\r\n", d.expected_end_time.format("%Y%m%dT%H%M%SZ")));
+        ics.push_str(&format!("SUMMARY:{}\r\n", ics_escape(&summary)));
+        ics.push_str(&format!("DESCRIPTION:{}\r\n", ics_escape(&description)));
+        ics.push_str("END:VEVENT\r\n");
+    }
+
+    ics.push_str("END:VCALENDAR\r\n");
+
+    (
+        [
+            (header::CONTENT_TYPE, "text/calendar; charset=utf-8".to_string()),
+            (header::CONTENT_DISPOSITION, format!("attachment; filename=\"route-{route_number}.ics\"")),
+        ],
+        ics,
+    )
+        .into_response()
+}
+
+#[derive(Deserialize)]
+pub struct YoyFilters {
+    date: NaiveDate,
+}
+
+#[derive(FromRow, Clone, serde::Serialize)]
+struct YoyRun {
+    id: String,
+    source: String,
+    destination: String,
+    expected_start_time: DateTime<Utc>,
+    real_start_time: Option<DateTime<Utc>>,
+    final_delay_minutes: Option<i32>,
+}
+
+#[derive(serde::Serialize)]
+struct YoyComparison {
+    route_number: i32,
+    current: Option<YoyRun>,
+    year_ago: Option<YoyRun>,
+    /// `None` when either side is missing; otherwise set when the two runs'
+    /// source/destination differ, which usually means the timetable changed
+    /// between the two dates and the comparison should be read with that in
+    /// mind rather than as a clean apples-to-apples punctuality delta.
+    timetable_changed: Option<bool>,
+}
+
+async fn fetch_run_on_date(
+    state: &AppState,
+    route_number: i32,
+    date: NaiveDate,
+) -> Result<Option<YoyRun>, sqlx::Error> {
+    query_stats::timed(
+        "route_yoy_run",
+        sqlx::query_as(
+            "SELECT id, source, destination, expected_start_time, real_start_time, final_delay_minutes
+             FROM routes
+             WHERE route_number = $1 AND expected_start_time::date = $2
+             ORDER BY expected_start_time
+             LIMIT 1",
+        )
+        .bind(route_number)
+        .bind(date)
+        .fetch_optional(&state.pool),
+    )
+    .await
+}
+
+/// Compares `route_number`'s run on `date` against the run on the same
+/// weekday a year earlier (364 days back, so the day-of-week lines up),
+/// when one was scheduled. `timetable_changed` flags a changed
+/// source/destination as a caveat on the comparison, since a renumbered or
+/// rerouted line makes the punctuality delta less meaningful.
+pub async fn yoy(State(state): State<AppState>, Path(route_number): Path<i32>, Query(filters): Query<YoyFilters>) -> Response {
+    let year_ago_date = filters.date - Duration::days(364);
+
+    let current = fetch_run_on_date(&state, route_number, filters.date).await;
+    let year_ago = fetch_run_on_date(&state, route_number, year_ago_date).await;
+
+    match (current, year_ago) {
+        (Ok(current), Ok(year_ago)) => {
+            let timetable_changed = match (&current, &year_ago) {
+                (Some(c), Some(y)) => Some(c.source != y.source || c.destination != y.destination),
+                _ => None,
+            };
+
+            Json(YoyComparison { route_number, current, year_ago, timetable_changed }).into_response()
+        }
+        (Err(e), _) | (_, Err(e)) => {
+            error!("error comparing route {route_number} year over year: {e:?}");
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+/// Flags `route_number` for closer monitoring the next time it runs: the
+/// delay checker polls it more often and logs every observed status in full,
+/// so a user-reported oddity has maximal data to look at if it recurs.
+/// Anonymous requests are accepted but rate-limited; a request carrying the
+/// admin token bypasses that limit.
+pub async fn watch(
+    State(state): State<AppState>,
+    Path(route_number): Path<i32>,
+    headers: HeaderMap,
+) -> Response {
+    let authenticated = state.admin_token.as_deref().is_some_and(|token| {
+        headers.get("X-Admin-Token").and_then(|v| v.to_str().ok()) == Some(token)
+    });
+
+    match state.watch_list.watch(route_number, authenticated).await {
+        Ok(()) => StatusCode::ACCEPTED.into_response(),
+        Err(WatchError::RateLimited) => StatusCode::TOO_MANY_REQUESTS.into_response(),
+    }
+}
@@ -0,0 +1,37 @@
+//! Read-only access to the dataset snapshots taken via `hzpp_delay_stats snapshot`.
+use axum::{
+    extract::{OriginalUri, Path, Query},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use tokio::fs;
+use tracing::error;
+
+use super::pagination::{paginate, PageParams};
+use crate::snapshot::{self, SNAPSHOTS_DIR};
+
+pub async fn list(Query(page_params): Query<PageParams>, OriginalUri(uri): OriginalUri) -> Response {
+    match snapshot::list_snapshots().await {
+        Ok(manifests) => paginate(manifests, &page_params, &uri),
+        Err(e) => {
+            error!("error listing snapshots: {e:?}");
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+/// Downloads one file (e.g. `manifest.json`, `routes.json`) from a given snapshot.
+pub async fn download(Path((tag, file)): Path<(String, String)>) -> Response {
+    if tag.contains('/') || tag.contains("..") || file.contains('/') || file.contains("..") {
+        return StatusCode::BAD_REQUEST.into_response();
+    }
+
+    match fs::read(format!("{SNAPSHOTS_DIR}/{tag}/{file}")).await {
+        Ok(contents) => contents.into_response(),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => StatusCode::NOT_FOUND.into_response(),
+        Err(e) => {
+            error!("error reading snapshot file: {e:?}");
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
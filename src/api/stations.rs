@@ -0,0 +1,403 @@
+//! Station lookups for pickers and maps — raw station rows, no aggregation.
+use axum::{
+    extract::{OriginalUri, Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::Deserialize;
+use sqlx::{prelude::FromRow, query_as, query_scalar};
+use tracing::error;
+
+use crate::query_stats;
+
+use super::{
+    pagination::{paginate, PageParams},
+    AppState,
+};
+
+#[derive(Deserialize)]
+pub struct StationFilters {
+    name: Option<String>,
+    county: Option<String>,
+}
+
+#[derive(FromRow, serde::Serialize)]
+struct Station {
+    id: String,
+    code: i32,
+    name: String,
+    latitude: f64,
+    longitude: f64,
+    county: Option<String>,
+}
+
+/// Lists stations, optionally narrowed by a `?name=` substring match and/or
+/// an exact `?county=` match.
+pub async fn list(
+    State(state): State<AppState>,
+    Query(filters): Query<StationFilters>,
+    Query(page_params): Query<PageParams>,
+    OriginalUri(uri): OriginalUri,
+) -> Response {
+    let stations: Result<Vec<Station>, _> = query_stats::timed(
+        "stations_list",
+        query_as(
+            "SELECT id, code, name, latitude, longitude, county
+             FROM stations
+             WHERE ($1::text IS NULL OR name ILIKE '%' || $1 || '%')
+               AND ($2::text IS NULL OR county = $2)
+             ORDER BY name",
+        )
+        .bind(&filters.name)
+        .bind(&filters.county)
+        .fetch_all(&state.pool),
+    )
+    .await;
+
+    match stations {
+        Ok(stations) => paginate(stations, &page_params, &uri),
+        Err(e) => {
+            error!("error listing stations: {e:?}");
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct StationHistoryFilters {
+    date: NaiveDate,
+}
+
+#[derive(FromRow, serde::Serialize)]
+struct StationArrival {
+    route_id: String,
+    route_number: i32,
+    sequence: i16,
+    expected_arrival: DateTime<Utc>,
+    real_arrival: Option<DateTime<Utc>>,
+    expected_departure: DateTime<Utc>,
+    real_departure: Option<DateTime<Utc>>,
+}
+
+/// Every scheduled and observed arrival/departure at a station on `date`, in
+/// order — the dataset local communities ask for most often when
+/// complaining to HZ about a particular stop.
+pub async fn history(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(filters): Query<StationHistoryFilters>,
+) -> Response {
+    let stops: Result<Vec<StationArrival>, _> = query_stats::timed(
+        "station_history",
+        query_as(
+            "SELECT s.route_id, r.route_number, s.sequence,
+                    s.expected_arrival, s.real_arrival, s.expected_departure, s.real_departure
+             FROM stops s
+             JOIN routes r ON r.id = s.route_id AND r.expected_start_time = s.route_expected_start_time
+             WHERE s.station_id = $1 AND s.expected_arrival::date = $2
+             ORDER BY s.expected_arrival",
+        )
+        .bind(&id)
+        .bind(filters.date)
+        .fetch_all(&state.pool),
+    )
+    .await;
+
+    match stops {
+        Ok(stops) => Json(stops).into_response(),
+        Err(e) => {
+            error!("error fetching history for station {id}: {e:?}");
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct NearbyFilters {
+    lat: f64,
+    lng: f64,
+    /// Defaults to 5km — wide enough for "stations I could walk to", narrow
+    /// enough not to scan the whole network on every request.
+    radius_km: Option<f64>,
+}
+
+#[derive(FromRow, serde::Serialize)]
+struct NearbyStation {
+    id: String,
+    code: i32,
+    name: String,
+    latitude: f64,
+    longitude: f64,
+    county: Option<String>,
+    distance_km: f64,
+}
+
+/// Stations within `radius_km` of `(lat, lng)`, nearest first. No PostGIS
+/// extension available in this dataset, so distance is the haversine
+/// formula computed directly in SQL rather than `ST_DWithin`/`ST_Distance`.
+pub async fn nearby(State(state): State<AppState>, Query(filters): Query<NearbyFilters>) -> Response {
+    let radius_km = filters.radius_km.unwrap_or(5.0);
+
+    let stations: Result<Vec<NearbyStation>, _> = query_stats::timed(
+        "stations_nearby",
+        query_as(
+            "SELECT id, code, name, latitude, longitude, county, distance_km
+             FROM (
+                 SELECT id, code, name, latitude, longitude, county,
+                        2 * 6371 * asin(sqrt(
+                            sin(radians(latitude - $1) / 2) ^ 2
+                            + cos(radians($1)) * cos(radians(latitude))
+                              * sin(radians(longitude - $2) / 2) ^ 2
+                        )) AS distance_km
+                 FROM stations
+             ) AS with_distance
+             WHERE distance_km <= $3
+             ORDER BY distance_km",
+        )
+        .bind(filters.lat)
+        .bind(filters.lng)
+        .bind(radius_km)
+        .fetch_all(&state.pool),
+    )
+    .await;
+
+    match stations {
+        Ok(stations) => Json(stations).into_response(),
+        Err(e) => {
+            error!("error finding stations near ({}, {}): {e:?}", filters.lat, filters.lng);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct AutocompleteFilters {
+    q: String,
+}
+
+#[derive(FromRow, serde::Serialize)]
+struct StationSuggestion {
+    id: String,
+    code: i32,
+    name: String,
+    similarity: f32,
+}
+
+/// Prefix/fuzzy station name search, case- and diacritic-insensitive
+/// (`zabno` finds `SV. IVAN ŽABNO`) using `pg_trgm` similarity over a
+/// `lower(unaccent(name))` generated column, since the raw names' mixed case
+/// and abbreviations like `GL. KOL.` make a plain `ILIKE` useless for typeahead.
+pub async fn autocomplete(State(state): State<AppState>, Query(filters): Query<AutocompleteFilters>) -> Response {
+    let stations: Result<Vec<StationSuggestion>, _> = query_stats::timed(
+        "stations_autocomplete",
+        query_as(
+            "SELECT id, code, name, similarity(name_normalized, lower(unaccent($1))) AS similarity
+             FROM stations
+             WHERE name_normalized % lower(unaccent($1))
+                OR name_normalized LIKE lower(unaccent($1)) || '%'
+             ORDER BY similarity DESC, name
+             LIMIT 20",
+        )
+        .bind(&filters.q)
+        .fetch_all(&state.pool),
+    )
+    .await;
+
+    match stations {
+        Ok(stations) => Json(stations).into_response(),
+        Err(e) => {
+            error!("error autocompleting stations for {:?}: {e:?}", filters.q);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct CalendarFilters {
+    year: i32,
+}
+
+#[derive(FromRow, serde::Serialize)]
+struct CalendarDay {
+    date: NaiveDate,
+    sample_count: i64,
+    avg_minutes_late: Option<f64>,
+    on_time_percentage: Option<f64>,
+}
+
+/// One row per day of `year` that had at least one scheduled arrival at the
+/// station, for the client to render as a GitHub-style contribution calendar
+/// instead of the plain per-station averages [`crate::api::stats::station_stats`]
+/// already exposes.
+pub async fn calendar(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(filters): Query<CalendarFilters>,
+) -> Response {
+    let days: Result<Vec<CalendarDay>, _> = query_stats::timed(
+        "station_calendar",
+        query_as(
+            "SELECT expected_arrival::date as date,
+                    count(*) FILTER (WHERE real_arrival IS NOT NULL) as sample_count,
+                    avg(extract(epoch from (real_arrival - expected_arrival)) / 60)
+                        FILTER (WHERE real_arrival IS NOT NULL)::float8 as avg_minutes_late,
+                    (count(*) FILTER (WHERE real_arrival IS NOT NULL
+                        AND real_arrival - expected_arrival <= interval '5 minutes')::float8
+                        / nullif(count(*) FILTER (WHERE real_arrival IS NOT NULL), 0)) as on_time_percentage
+             FROM stops
+             WHERE station_id = $1 AND extract(year from expected_arrival) = $2
+             GROUP BY date
+             ORDER BY date",
+        )
+        .bind(&id)
+        .bind(filters.year)
+        .fetch_all(&state.pool),
+    )
+    .await;
+
+    match days {
+        Ok(days) => Json(days).into_response(),
+        Err(e) if query_stats::is_statement_timeout(&e) => StatusCode::REQUEST_TIMEOUT.into_response(),
+        Err(e) => {
+            error!("error computing station calendar for {id} in {}: {e:?}", filters.year);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct TimetableFilters {
+    date: NaiveDate,
+}
+
+#[derive(FromRow, serde::Serialize)]
+struct TimetableStop {
+    route_number: i32,
+    destination: String,
+    expected_arrival: DateTime<Utc>,
+    real_arrival: Option<DateTime<Utc>>,
+    expected_departure: DateTime<Utc>,
+    real_departure: Option<DateTime<Utc>>,
+}
+
+/// Every stop scheduled at `id` on `date`, with real times filled in where
+/// already observed. Unlike `/delays/live`'s live departure board this
+/// covers any date — past or future — not just what's running right now.
+pub async fn timetable(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(filters): Query<TimetableFilters>,
+) -> Response {
+    let station_exists: Result<Option<String>, _> = query_stats::timed(
+        "station_timetable_lookup",
+        query_scalar("SELECT id FROM stations WHERE id = $1").bind(&id).fetch_optional(&state.pool),
+    )
+    .await;
+
+    match station_exists {
+        Ok(Some(_)) => {}
+        Ok(None) => return StatusCode::NOT_FOUND.into_response(),
+        Err(e) => {
+            error!("error looking up station {id}: {e:?}");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    }
+
+    let stops: Result<Vec<TimetableStop>, _> = query_stats::timed(
+        "station_timetable",
+        query_as(
+            "SELECT r.route_number, r.destination,
+                    s.expected_arrival, s.real_arrival, s.expected_departure, s.real_departure
+             FROM stops s
+             JOIN routes r ON r.id = s.route_id AND r.expected_start_time = s.route_expected_start_time
+             WHERE s.station_id = $1 AND s.expected_arrival::date = $2
+             ORDER BY s.expected_arrival",
+        )
+        .bind(&id)
+        .bind(filters.date)
+        .fetch_all(&state.pool),
+    )
+    .await;
+
+    match stops {
+        Ok(stops) => Json(stops).into_response(),
+        Err(e) if query_stats::is_statement_timeout(&e) => StatusCode::REQUEST_TIMEOUT.into_response(),
+        Err(e) => {
+            error!("error computing timetable for station {id} on {}: {e:?}", filters.date);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+#[derive(FromRow, serde::Serialize)]
+struct PlatformUsage {
+    route_number: i32,
+    destination: String,
+    expected_departure: DateTime<Utc>,
+    platform: String,
+}
+
+/// The platforms/tracks most recently observed at `id`, newest first. Only
+/// covers stops the delay checker happened to see a platform for — HŽ's
+/// planner feed doesn't carry this field, so most historical stops have none.
+pub async fn recent_platforms(State(state): State<AppState>, Path(id): Path<String>) -> Response {
+    let station_exists: Result<Option<String>, _> = query_stats::timed(
+        "station_recent_platforms_lookup",
+        query_scalar("SELECT id FROM stations WHERE id = $1").bind(&id).fetch_optional(&state.pool),
+    )
+    .await;
+
+    match station_exists {
+        Ok(Some(_)) => {}
+        Ok(None) => return StatusCode::NOT_FOUND.into_response(),
+        Err(e) => {
+            error!("error looking up station {id}: {e:?}");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    }
+
+    let usages: Result<Vec<PlatformUsage>, _> = query_stats::timed(
+        "station_recent_platforms",
+        query_as(
+            "SELECT r.route_number, r.destination, s.expected_departure, s.platform
+             FROM stops s
+             JOIN routes r ON r.id = s.route_id AND r.expected_start_time = s.route_expected_start_time
+             WHERE s.station_id = $1 AND s.platform IS NOT NULL
+             ORDER BY s.expected_departure DESC
+             LIMIT 50",
+        )
+        .bind(&id)
+        .fetch_all(&state.pool),
+    )
+    .await;
+
+    match usages {
+        Ok(usages) => Json(usages).into_response(),
+        Err(e) if query_stats::is_statement_timeout(&e) => StatusCode::REQUEST_TIMEOUT.into_response(),
+        Err(e) => {
+            error!("error computing recent platforms for station {id}: {e:?}");
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+pub async fn get(State(state): State<AppState>, Path(id): Path<String>) -> Response {
+    let station: Result<Option<Station>, _> = query_stats::timed(
+        "station_get",
+        query_as("SELECT id, code, name, latitude, longitude, county FROM stations WHERE id = $1")
+            .bind(&id)
+            .fetch_optional(&state.pool),
+    )
+    .await;
+
+    match station {
+        Ok(Some(station)) => Json(station).into_response(),
+        Ok(None) => StatusCode::NOT_FOUND.into_response(),
+        Err(e) => {
+            error!("error looking up station {id}: {e:?}");
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
@@ -0,0 +1,725 @@
+//! Aggregate punctuality statistics — the headline numbers a delay-stats
+//! project exists to produce, computed on the fly from real vs expected
+//! times rather than maintained incrementally like the `kpis` cache.
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::NaiveDate;
+use serde::Deserialize;
+use serde_json::json;
+use sqlx::prelude::FromRow;
+use tracing::error;
+
+use crate::{model::stock_class::StockClass, query_stats, route_renumbering};
+
+use super::{problem::problem_response, AppState};
+
+/// Distinguishes "this route/station has no completed runs yet" from a
+/// malformed request, so clients can tell an empty chart apart from a typo'd
+/// route number instead of both rendering as the same bare 404.
+#[derive(thiserror::Error, Debug)]
+pub enum StatsError {
+    #[error("no completed runs on record for route {route_number}")]
+    NoRouteData { route_number: i32 },
+    #[error("station {station_id} does not exist")]
+    UnknownStation { station_id: String },
+    #[error("no completed stops on record for station {station_id}")]
+    NoStationData { station_id: String },
+    /// Raised when the DB cancels one of these aggregate queries for
+    /// exceeding `statement_timeout` (see [`crate::query_stats::is_statement_timeout`]),
+    /// rather than letting it run for minutes against a large table.
+    #[error("query took too long to run, narrow the request and try again")]
+    TooBroad,
+}
+
+impl IntoResponse for StatsError {
+    fn into_response(self) -> Response {
+        let status = match &self {
+            StatsError::NoRouteData { .. } | StatsError::UnknownStation { .. } | StatsError::NoStationData { .. } => {
+                StatusCode::NOT_FOUND
+            }
+            StatsError::TooBroad => StatusCode::REQUEST_TIMEOUT,
+        };
+        let code = match &self {
+            StatsError::NoRouteData { .. } => "no_route_data",
+            StatsError::UnknownStation { .. } => "unknown_station",
+            StatsError::NoStationData { .. } => "no_station_data",
+            StatsError::TooBroad => "query_too_broad",
+        };
+
+        problem_response(status, code, &self.to_string())
+    }
+}
+
+/// How many completed runs each figure in a stats response is computed over,
+/// so a client can tell a confident average from one resting on a handful of
+/// samples.
+#[derive(serde::Serialize)]
+struct Coverage {
+    runs: i64,
+}
+
+#[derive(Deserialize)]
+pub struct SlotFilters {
+    route_number: Option<i32>,
+    source: Option<String>,
+    /// Narrows to routes carrying this operator-assigned tag (see
+    /// [`crate::route_tags`]), e.g. `?tag=zagreb-commuter`.
+    tag: Option<String>,
+}
+
+#[derive(FromRow, serde::Serialize)]
+struct DepartureSlot {
+    route_number: i32,
+    source: String,
+    destination: String,
+    scheduled_departure: String,
+    sample_count: i64,
+    avg_minutes_late: Option<f64>,
+    on_time_percentage: Option<f64>,
+}
+
+/// One row per (route_number, source, destination, time-of-day) combination,
+/// aggregated across every day that slot has actually run.
+pub async fn slots(State(state): State<AppState>, Query(filters): Query<SlotFilters>) -> Response {
+    let slots: Result<Vec<DepartureSlot>, _> = query_stats::timed(
+        "stats_slots",
+        sqlx::query_as(
+            "SELECT route_number, source, destination,
+                    to_char(expected_start_time, 'HH24:MI') as scheduled_departure,
+                    count(*) FILTER (WHERE real_start_time IS NOT NULL) as sample_count,
+                    avg(extract(epoch from (real_start_time - expected_start_time)) / 60)
+                        FILTER (WHERE real_start_time IS NOT NULL)::float8 as avg_minutes_late,
+                    (count(*) FILTER (WHERE real_start_time IS NOT NULL
+                        AND real_start_time - expected_start_time <= interval '5 minutes')::float8
+                        / nullif(count(*) FILTER (WHERE real_start_time IS NOT NULL), 0)) as on_time_percentage
+             FROM routes
+             WHERE ($1::int IS NULL OR route_number = $1)
+               AND ($2::text IS NULL OR source ILIKE '%' || $2 || '%')
+               AND ($3::text IS NULL OR EXISTS (
+                   SELECT 1 FROM route_tags rt WHERE rt.route_number = routes.route_number AND rt.tag = $3))
+             GROUP BY route_number, source, destination, to_char(expected_start_time, 'HH24:MI')
+             ORDER BY route_number, scheduled_departure",
+        )
+        .bind(filters.route_number)
+        .bind(&filters.source)
+        .bind(&filters.tag)
+        .fetch_all(&state.pool),
+    )
+    .await;
+
+    match slots {
+        Ok(slots) => Json(slots).into_response(),
+        Err(e) if query_stats::is_statement_timeout(&e) => StatsError::TooBroad.into_response(),
+        Err(e) => {
+            error!("error computing departure slot stats: {e:?}");
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+#[derive(FromRow)]
+struct RouteAggregate {
+    sample_count: i64,
+    avg_minutes_late: Option<f64>,
+    median_minutes_late: Option<f64>,
+    on_time_percentage: Option<f64>,
+}
+
+#[derive(FromRow, serde::Serialize)]
+struct WorstDay {
+    date: NaiveDate,
+    avg_minutes_late: f64,
+}
+
+#[derive(FromRow)]
+struct WeatherBucket {
+    bad_weather: bool,
+    sample_count: i64,
+    avg_minutes_late: Option<f64>,
+    on_time_percentage: Option<f64>,
+}
+
+#[derive(serde::Serialize)]
+struct WeatherPunctuality {
+    sample_count: i64,
+    avg_minutes_late: Option<f64>,
+    on_time_percentage: Option<f64>,
+}
+
+impl From<WeatherBucket> for WeatherPunctuality {
+    fn from(bucket: WeatherBucket) -> Self {
+        WeatherPunctuality {
+            sample_count: bucket.sample_count,
+            avg_minutes_late: bucket.avg_minutes_late,
+            on_time_percentage: bucket.on_time_percentage,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct RouteStatsFilters {
+    /// When true, also pulls in every route number `route_number` was
+    /// renumbered to at a later timetable change (see
+    /// [`crate::route_renumbering`]), so history doesn't reset at the
+    /// renumbering.
+    follow_successors: Option<bool>,
+}
+
+#[derive(serde::Serialize)]
+struct RouteStats {
+    route_number: i32,
+    /// Every route number these stats were computed over — just
+    /// `[route_number]` unless `follow_successors=true` turned up a chain.
+    included_route_numbers: Vec<i32>,
+    /// Inferred from `route_number` — see [`StockClass`] — since composition
+    /// isn't tracked per run.
+    stock_class: StockClass,
+    coverage: Coverage,
+    avg_minutes_late: Option<f64>,
+    median_minutes_late: Option<f64>,
+    on_time_percentage: Option<f64>,
+    worst_day: Option<WorstDay>,
+    /// Only covers days an operator has recorded in `weather_events` — `None`
+    /// until at least one of this route's runs falls on a curated day.
+    fair_weather: Option<WeatherPunctuality>,
+    bad_weather: Option<WeatherPunctuality>,
+}
+
+/// Aggregate punctuality for one route number across every completed run on
+/// record — the headline numbers a delay-stats project exists to produce.
+pub async fn route_stats(
+    State(state): State<AppState>,
+    Path(route_number): Path<i32>,
+    Query(filters): Query<RouteStatsFilters>,
+) -> Response {
+    let included_route_numbers = if filters.follow_successors.unwrap_or(false) {
+        match route_renumbering::successor_chain(&state.pool, route_number).await {
+            Ok(chain) => chain,
+            Err(e) => {
+                error!("error resolving successor chain for route {route_number}: {e:?}");
+                return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+            }
+        }
+    } else {
+        vec![route_number]
+    };
+
+    let aggregate: Result<RouteAggregate, _> = query_stats::timed(
+        "stats_route_aggregate",
+        sqlx::query_as(
+            "SELECT
+                count(*) as sample_count,
+                avg(extract(epoch from (real_end_time - expected_end_time)) / 60)::float8 as avg_minutes_late,
+                percentile_cont(0.5) WITHIN GROUP (
+                    ORDER BY extract(epoch from (real_end_time - expected_end_time)) / 60
+                )::float8 as median_minutes_late,
+                (count(*) FILTER (WHERE real_end_time - expected_end_time <= interval '5 minutes')::float8
+                    / nullif(count(*), 0)) as on_time_percentage
+             FROM routes
+             WHERE route_number = ANY($1) AND real_end_time IS NOT NULL",
+        )
+        .bind(&included_route_numbers)
+        .fetch_one(&state.pool),
+    )
+    .await;
+
+    let aggregate = match aggregate {
+        Ok(aggregate) => aggregate,
+        Err(e) if query_stats::is_statement_timeout(&e) => return StatsError::TooBroad.into_response(),
+        Err(e) => {
+            error!("error computing aggregate stats for route {route_number}: {e:?}");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+
+    if aggregate.sample_count == 0 {
+        return StatsError::NoRouteData { route_number }.into_response();
+    }
+
+    let worst_day: Result<Option<WorstDay>, _> = query_stats::timed(
+        "stats_route_worst_day",
+        sqlx::query_as(
+            "SELECT expected_start_time::date as date,
+                    avg(extract(epoch from (real_end_time - expected_end_time)) / 60)::float8 as avg_minutes_late
+             FROM routes
+             WHERE route_number = ANY($1) AND real_end_time IS NOT NULL
+             GROUP BY expected_start_time::date
+             ORDER BY avg_minutes_late DESC
+             LIMIT 1",
+        )
+        .bind(&included_route_numbers)
+        .fetch_optional(&state.pool),
+    )
+    .await;
+
+    let worst_day = match worst_day {
+        Ok(worst_day) => worst_day,
+        Err(e) if query_stats::is_statement_timeout(&e) => return StatsError::TooBroad.into_response(),
+        Err(e) => {
+            error!("error computing worst day for route {route_number}: {e:?}");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+
+    let weather_buckets: Result<Vec<WeatherBucket>, _> = query_stats::timed(
+        "stats_route_weather_split",
+        sqlx::query_as(
+            "SELECT we.bad_weather as bad_weather,
+                    count(*) as sample_count,
+                    avg(extract(epoch from (real_end_time - expected_end_time)) / 60)::float8 as avg_minutes_late,
+                    (count(*) FILTER (WHERE real_end_time - expected_end_time <= interval '5 minutes')::float8
+                        / nullif(count(*), 0)) as on_time_percentage
+             FROM routes r
+             JOIN weather_events we ON we.date = r.expected_start_time::date
+             WHERE r.route_number = ANY($1) AND r.real_end_time IS NOT NULL
+             GROUP BY we.bad_weather",
+        )
+        .bind(&included_route_numbers)
+        .fetch_all(&state.pool),
+    )
+    .await;
+
+    let mut fair_weather = None;
+    let mut bad_weather = None;
+
+    match weather_buckets {
+        Ok(buckets) => {
+            for bucket in buckets {
+                if bucket.bad_weather {
+                    bad_weather = Some(bucket.into());
+                } else {
+                    fair_weather = Some(bucket.into());
+                }
+            }
+        }
+        Err(e) if query_stats::is_statement_timeout(&e) => return StatsError::TooBroad.into_response(),
+        Err(e) => {
+            error!("error computing weather-adjusted stats for route {route_number}: {e:?}");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    }
+
+    Json(RouteStats {
+        route_number,
+        included_route_numbers,
+        stock_class: StockClass::from_route_number(route_number),
+        coverage: Coverage { runs: aggregate.sample_count },
+        avg_minutes_late: aggregate.avg_minutes_late,
+        median_minutes_late: aggregate.median_minutes_late,
+        on_time_percentage: aggregate.on_time_percentage,
+        worst_day,
+        fair_weather,
+        bad_weather,
+    })
+    .into_response()
+}
+
+#[derive(FromRow, serde::Serialize)]
+struct HeatmapCell {
+    hour_of_day: i32,
+    /// Postgres' `extract(dow ...)` convention: 0 is Sunday, 6 is Saturday.
+    day_of_week: i32,
+    sample_count: i64,
+    avg_minutes_late: Option<f64>,
+}
+
+/// Average delay bucketed by scheduled hour-of-day and day-of-week, across
+/// every completed run on record — the coarse view of when the network
+/// tends to run worst, for the client to render as a heatmap.
+pub async fn heatmap(State(state): State<AppState>) -> Response {
+    let cells: Result<Vec<HeatmapCell>, _> = query_stats::timed(
+        "stats_heatmap",
+        sqlx::query_as(
+            "SELECT
+                extract(hour from expected_start_time)::int as hour_of_day,
+                extract(dow from expected_start_time)::int as day_of_week,
+                count(*) as sample_count,
+                avg(extract(epoch from (real_end_time - expected_end_time)) / 60)::float8 as avg_minutes_late
+             FROM routes
+             WHERE real_end_time IS NOT NULL
+             GROUP BY hour_of_day, day_of_week
+             ORDER BY day_of_week, hour_of_day",
+        )
+        .fetch_all(&state.pool),
+    )
+    .await;
+
+    match cells {
+        Ok(cells) => Json(cells).into_response(),
+        Err(e) if query_stats::is_statement_timeout(&e) => StatsError::TooBroad.into_response(),
+        Err(e) => {
+            error!("error computing delay heatmap: {e:?}");
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+#[derive(FromRow)]
+struct StationAggregate {
+    sample_count: i64,
+    avg_minutes_late: Option<f64>,
+    p90_minutes_late: Option<f64>,
+    distinct_days: i64,
+}
+
+#[derive(serde::Serialize)]
+struct StationStats {
+    station_id: String,
+    coverage: Coverage,
+    avg_minutes_late: Option<f64>,
+    p90_minutes_late: Option<f64>,
+    trains_per_day: Option<f64>,
+}
+
+/// Aggregate arrival-delay stats for one station, across every stop ever
+/// scheduled there.
+pub async fn station_stats(State(state): State<AppState>, Path(id): Path<String>) -> Response {
+    let station_exists: Result<Option<String>, _> = query_stats::timed(
+        "stats_station_lookup",
+        sqlx::query_scalar("SELECT id FROM stations WHERE id = $1").bind(&id).fetch_optional(&state.pool),
+    )
+    .await;
+
+    match station_exists {
+        Ok(Some(_)) => {}
+        Ok(None) => return StatsError::UnknownStation { station_id: id }.into_response(),
+        Err(e) => {
+            error!("error looking up station {id}: {e:?}");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    }
+
+    let aggregate: Result<StationAggregate, _> = query_stats::timed(
+        "stats_station_aggregate",
+        sqlx::query_as(
+            "SELECT
+                count(*) FILTER (WHERE real_arrival IS NOT NULL) as sample_count,
+                avg(extract(epoch from (real_arrival - expected_arrival)) / 60)
+                    FILTER (WHERE real_arrival IS NOT NULL)::float8 as avg_minutes_late,
+                percentile_cont(0.9) WITHIN GROUP (
+                    ORDER BY extract(epoch from (real_arrival - expected_arrival)) / 60
+                ) FILTER (WHERE real_arrival IS NOT NULL)::float8 as p90_minutes_late,
+                count(DISTINCT route_expected_start_time::date)
+                    FILTER (WHERE real_arrival IS NOT NULL) as distinct_days
+             FROM stops
+             WHERE station_id = $1",
+        )
+        .bind(&id)
+        .fetch_one(&state.pool),
+    )
+    .await;
+
+    let aggregate = match aggregate {
+        Ok(aggregate) => aggregate,
+        Err(e) if query_stats::is_statement_timeout(&e) => return StatsError::TooBroad.into_response(),
+        Err(e) => {
+            error!("error computing aggregate stats for station {id}: {e:?}");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+
+    if aggregate.sample_count == 0 {
+        return StatsError::NoStationData { station_id: id }.into_response();
+    }
+
+    Json(StationStats {
+        coverage: Coverage { runs: aggregate.sample_count },
+        avg_minutes_late: aggregate.avg_minutes_late,
+        p90_minutes_late: aggregate.p90_minutes_late,
+        trains_per_day: (aggregate.distinct_days > 0)
+            .then(|| aggregate.sample_count as f64 / aggregate.distinct_days as f64),
+        station_id: id,
+    })
+    .into_response()
+}
+
+#[derive(FromRow)]
+struct StockClassAggregate {
+    is_emu_6112: bool,
+    sample_count: i64,
+    avg_minutes_late: Option<f64>,
+    on_time_percentage: Option<f64>,
+}
+
+#[derive(serde::Serialize)]
+struct StockClassStats {
+    stock_class: StockClass,
+    coverage: Coverage,
+    avg_minutes_late: Option<f64>,
+    on_time_percentage: Option<f64>,
+}
+
+/// Punctuality segmented by [`StockClass`], to see whether the newer
+/// 6111/6112-series EMUs actually run more on time than the older loco-hauled
+/// stock they're gradually replacing.
+pub async fn stock_class(State(state): State<AppState>) -> Response {
+    let aggregates: Result<Vec<StockClassAggregate>, _> = query_stats::timed(
+        "stats_stock_class",
+        sqlx::query_as(
+            "SELECT
+                route_number BETWEEN 6100 AND 6199 as is_emu_6112,
+                count(*) as sample_count,
+                avg(extract(epoch from (real_end_time - expected_end_time)) / 60)::float8 as avg_minutes_late,
+                (count(*) FILTER (WHERE real_end_time - expected_end_time <= interval '5 minutes')::float8
+                    / nullif(count(*), 0)) as on_time_percentage
+             FROM routes
+             WHERE real_end_time IS NOT NULL
+             GROUP BY is_emu_6112",
+        )
+        .fetch_all(&state.pool),
+    )
+    .await;
+
+    match aggregates {
+        Ok(aggregates) => Json(
+            aggregates
+                .into_iter()
+                .map(|a| StockClassStats {
+                    stock_class: if a.is_emu_6112 { StockClass::Emu6112 } else { StockClass::OlderStock },
+                    coverage: Coverage { runs: a.sample_count },
+                    avg_minutes_late: a.avg_minutes_late,
+                    on_time_percentage: a.on_time_percentage,
+                })
+                .collect::<Vec<_>>(),
+        )
+        .into_response(),
+        Err(e) if query_stats::is_statement_timeout(&e) => StatsError::TooBroad.into_response(),
+        Err(e) => {
+            error!("error computing stock class stats: {e:?}");
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+#[derive(FromRow)]
+struct AccessibilityAggregate {
+    wheelchair_accessible: bool,
+    bikes_allowed: bool,
+    sample_count: i64,
+    avg_minutes_late: Option<f64>,
+    on_time_percentage: Option<f64>,
+}
+
+#[derive(serde::Serialize)]
+struct AccessibilityStats {
+    wheelchair_accessible: bool,
+    bikes_allowed: bool,
+    coverage: Coverage,
+    avg_minutes_late: Option<f64>,
+    on_time_percentage: Option<f64>,
+}
+
+/// Punctuality segmented by `wheelchair_accessible`/`bikes_allowed`, since a
+/// cancelled or late accessible service disproportionately matters to the
+/// riders who depend on it rather than the next one along.
+pub async fn accessibility(State(state): State<AppState>) -> Response {
+    let aggregates: Result<Vec<AccessibilityAggregate>, _> = query_stats::timed(
+        "stats_accessibility",
+        sqlx::query_as(
+            "SELECT
+                wheelchair_accessible = 1 as wheelchair_accessible,
+                bikes_allowed = 1 as bikes_allowed,
+                count(*) as sample_count,
+                avg(extract(epoch from (real_end_time - expected_end_time)) / 60)::float8 as avg_minutes_late,
+                (count(*) FILTER (WHERE real_end_time - expected_end_time <= interval '5 minutes')::float8
+                    / nullif(count(*), 0)) as on_time_percentage
+             FROM routes
+             WHERE real_end_time IS NOT NULL
+             GROUP BY wheelchair_accessible, bikes_allowed",
+        )
+        .fetch_all(&state.pool),
+    )
+    .await;
+
+    match aggregates {
+        Ok(aggregates) => Json(
+            aggregates
+                .into_iter()
+                .map(|a| AccessibilityStats {
+                    wheelchair_accessible: a.wheelchair_accessible,
+                    bikes_allowed: a.bikes_allowed,
+                    coverage: Coverage { runs: a.sample_count },
+                    avg_minutes_late: a.avg_minutes_late,
+                    on_time_percentage: a.on_time_percentage,
+                })
+                .collect::<Vec<_>>(),
+        )
+        .into_response(),
+        Err(e) if query_stats::is_statement_timeout(&e) => StatsError::TooBroad.into_response(),
+        Err(e) => {
+            error!("error computing accessibility stats: {e:?}");
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct OnTimeFilters {
+    /// Lateness, in minutes, still counted as "on time". Defaults to 5,
+    /// matching every other on-time figure in this file.
+    threshold_minutes: Option<i64>,
+    /// Rolling window to compute over, e.g. `90d`. Defaults to `90d`.
+    period: Option<String>,
+}
+
+#[derive(FromRow, serde::Serialize)]
+struct OnTimeRouteBucket {
+    route_number: i32,
+    sample_count: i64,
+    on_time_percentage: Option<f64>,
+}
+
+/// Parses a window like `90d` into a day count. Only the `d` suffix is
+/// supported for now — there's no call yet for weeks or months here.
+fn parse_period_days(period: &str) -> Result<i64, String> {
+    period
+        .strip_suffix('d')
+        .ok_or_else(|| format!("period must look like \"90d\", got {period:?}"))?
+        .parse()
+        .map_err(|_| format!("period must look like \"90d\", got {period:?}"))
+}
+
+/// Share of completed runs finishing within `threshold_minutes` of schedule,
+/// per route, over a rolling window — the one number journalists ask for.
+pub async fn on_time_percentage(State(state): State<AppState>, Query(filters): Query<OnTimeFilters>) -> Response {
+    let threshold_minutes = filters.threshold_minutes.unwrap_or(5);
+    let period_days = match parse_period_days(filters.period.as_deref().unwrap_or("90d")) {
+        Ok(days) => days,
+        Err(message) => {
+            return (StatusCode::BAD_REQUEST, Json(json!({ "error": "invalid_period", "message": message })))
+                .into_response()
+        }
+    };
+    let since = chrono::Utc::now() - chrono::Duration::days(period_days);
+
+    let buckets: Result<Vec<OnTimeRouteBucket>, _> = query_stats::timed(
+        "stats_on_time_percentage",
+        sqlx::query_as(
+            "SELECT
+                route_number,
+                count(*) as sample_count,
+                (count(*) FILTER (WHERE real_end_time - expected_end_time <= make_interval(mins => $1))::float8
+                    / nullif(count(*), 0)) as on_time_percentage
+             FROM routes
+             WHERE real_end_time IS NOT NULL AND expected_start_time >= $2
+             GROUP BY route_number
+             ORDER BY route_number",
+        )
+        .bind(threshold_minutes as i32)
+        .bind(since)
+        .fetch_all(&state.pool),
+    )
+    .await;
+
+    match buckets {
+        Ok(buckets) => Json(json!({
+            "threshold_minutes": threshold_minutes,
+            "period_days": period_days,
+            "routes": buckets,
+        }))
+        .into_response(),
+        Err(e) if query_stats::is_statement_timeout(&e) => StatsError::TooBroad.into_response(),
+        Err(e) => {
+            error!("error computing on-time percentage: {e:?}");
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct OdFilters {
+    from: String,
+    to: String,
+    /// Rolling window to compute over, e.g. `90d`. Defaults to `90d`.
+    period: Option<String>,
+}
+
+#[derive(FromRow, serde::Serialize)]
+struct OdAggregate {
+    sample_count: i64,
+    avg_scheduled_minutes: Option<f64>,
+    avg_actual_minutes: Option<f64>,
+    median_actual_minutes: Option<f64>,
+    p90_actual_minutes: Option<f64>,
+    avg_minutes_late_at_destination: Option<f64>,
+    on_time_percentage: Option<f64>,
+}
+
+/// Scheduled vs actual travel time distribution, and destination-arrival
+/// delay stats, for every run of any route that calls at both `from` and
+/// `to` (in that order) within the window — the question a commuter
+/// comparing two routes for the same trip actually wants answered.
+pub async fn od(State(state): State<AppState>, Query(filters): Query<OdFilters>) -> Response {
+    let period_days = match parse_period_days(filters.period.as_deref().unwrap_or("90d")) {
+        Ok(days) => days,
+        Err(message) => {
+            return (StatusCode::BAD_REQUEST, Json(json!({ "error": "invalid_period", "message": message })))
+                .into_response()
+        }
+    };
+    let since = chrono::Utc::now() - chrono::Duration::days(period_days);
+
+    let aggregate: Result<OdAggregate, _> = query_stats::timed(
+        "stats_od",
+        sqlx::query_as(
+            "SELECT
+                count(*) as sample_count,
+                avg(extract(epoch from (t.expected_arrival - f.expected_departure)) / 60)::float8
+                    as avg_scheduled_minutes,
+                avg(extract(epoch from (t.real_arrival - f.real_departure)) / 60)
+                    FILTER (WHERE t.real_arrival IS NOT NULL AND f.real_departure IS NOT NULL)::float8
+                    as avg_actual_minutes,
+                percentile_cont(0.5) WITHIN GROUP (
+                    ORDER BY extract(epoch from (t.real_arrival - f.real_departure)) / 60
+                ) FILTER (WHERE t.real_arrival IS NOT NULL AND f.real_departure IS NOT NULL)::float8
+                    as median_actual_minutes,
+                percentile_cont(0.9) WITHIN GROUP (
+                    ORDER BY extract(epoch from (t.real_arrival - f.real_departure)) / 60
+                ) FILTER (WHERE t.real_arrival IS NOT NULL AND f.real_departure IS NOT NULL)::float8
+                    as p90_actual_minutes,
+                avg(extract(epoch from (t.real_arrival - t.expected_arrival)) / 60)
+                    FILTER (WHERE t.real_arrival IS NOT NULL)::float8
+                    as avg_minutes_late_at_destination,
+                (count(*) FILTER (WHERE t.real_arrival IS NOT NULL
+                        AND t.real_arrival - t.expected_arrival <= interval '5 minutes')::float8
+                    / nullif(count(*) FILTER (WHERE t.real_arrival IS NOT NULL), 0))
+                    as on_time_percentage
+             FROM stops f
+             JOIN stops t
+                ON t.route_id = f.route_id
+                AND t.route_expected_start_time = f.route_expected_start_time
+                AND t.sequence > f.sequence
+             WHERE f.station_id = $1 AND t.station_id = $2 AND f.route_expected_start_time >= $3",
+        )
+        .bind(&filters.from)
+        .bind(&filters.to)
+        .bind(since)
+        .fetch_one(&state.pool),
+    )
+    .await;
+
+    match aggregate {
+        Ok(aggregate) => Json(json!({
+            "from": filters.from,
+            "to": filters.to,
+            "period_days": period_days,
+            "sample_count": aggregate.sample_count,
+            "avg_scheduled_minutes": aggregate.avg_scheduled_minutes,
+            "avg_actual_minutes": aggregate.avg_actual_minutes,
+            "median_actual_minutes": aggregate.median_actual_minutes,
+            "p90_actual_minutes": aggregate.p90_actual_minutes,
+            "avg_minutes_late_at_destination": aggregate.avg_minutes_late_at_destination,
+            "on_time_percentage": aggregate.on_time_percentage,
+        }))
+        .into_response(),
+        Err(e) if query_stats::is_statement_timeout(&e) => StatsError::TooBroad.into_response(),
+        Err(e) => {
+            error!("error computing origin-destination stats from {} to {}: {e:?}", filters.from, filters.to);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
@@ -0,0 +1,53 @@
+//! Public, unauthenticated view of the route fetcher and delay checker's
+//! health, so "is the fetcher silently failing?" doesn't require grepping
+//! logs or reaching for the admin-token-gated [`super::diagnostics::report`].
+use axum::{
+    extract::State,
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use super::AppState;
+
+#[derive(Serialize)]
+struct FetcherStatus {
+    paused: bool,
+    last_successful_fetch_at: Option<DateTime<Utc>>,
+    recent_parse_failures: usize,
+}
+
+#[derive(Serialize)]
+struct DelayCheckerStatus {
+    active_monitor_tasks: usize,
+    watched_routes: usize,
+    ws_delay_subscribers: usize,
+}
+
+#[derive(Serialize)]
+struct StatusReport {
+    fetcher: FetcherStatus,
+    delay_checker: DelayCheckerStatus,
+}
+
+/// Reports whether the fetcher is paused, when it last succeeded, and how
+/// many routes are currently being polled for delays — deliberately only
+/// counts and timestamps, not the failure messages themselves, since unlike
+/// `/admin/diagnostics` this endpoint has no auth gate.
+pub async fn report(State(state): State<AppState>) -> Response {
+    let report = StatusReport {
+        fetcher: FetcherStatus {
+            paused: state.monitor_control.is_paused(),
+            last_successful_fetch_at: state.monitor_control.last_successful_fetch().await,
+            recent_parse_failures: state.monitor_control.recent_failures().await.len(),
+        },
+        delay_checker: DelayCheckerStatus {
+            active_monitor_tasks: state.active_monitors.count(),
+            watched_routes: state.watch_list.count().await,
+            ws_delay_subscribers: state.delay_updates.subscriber_count(),
+        },
+    };
+
+    Json(report).into_response()
+}
@@ -0,0 +1,88 @@
+//! Skipped stops — a scheduled stop with no recorded arrival or departure on
+//! an otherwise-completed run — are a service-quality issue distinct from
+//! delays, so they get their own per-station/per-route aggregate here
+//! instead of folding into `stats`. A stop only counts once its run has
+//! actually finished (`real_end_time` set); an in-progress run's
+//! not-yet-reached stops would otherwise look identical to skipped ones.
+use axum::{extract::State, http::StatusCode, response::IntoResponse, response::Response, Json};
+use serde::Serialize;
+use sqlx::prelude::FromRow;
+use tracing::error;
+
+use crate::query_stats;
+
+use super::AppState;
+
+#[derive(FromRow, Serialize)]
+struct RouteSkipCount {
+    route_number: i32,
+    scheduled_stops: i64,
+    skipped_stops: i64,
+    skip_percentage: Option<f64>,
+}
+
+#[derive(FromRow, Serialize)]
+struct StationSkipCount {
+    station_id: String,
+    scheduled_stops: i64,
+    skipped_stops: i64,
+    skip_percentage: Option<f64>,
+}
+
+/// Per-route and per-station counts of skipped stops across every completed
+/// run on record.
+pub async fn counts(State(state): State<AppState>) -> Response {
+    let by_route: Result<Vec<RouteSkipCount>, _> = query_stats::timed(
+        "stop_skips_by_route",
+        sqlx::query_as(
+            "SELECT
+                r.route_number,
+                count(*) as scheduled_stops,
+                count(*) FILTER (WHERE s.real_arrival IS NULL AND s.real_departure IS NULL) as skipped_stops,
+                (count(*) FILTER (WHERE s.real_arrival IS NULL AND s.real_departure IS NULL)::float8
+                    / nullif(count(*), 0)) as skip_percentage
+             FROM stops s
+             JOIN routes r ON r.id = s.route_id AND r.expected_start_time = s.route_expected_start_time
+             WHERE r.real_end_time IS NOT NULL
+             GROUP BY r.route_number
+             ORDER BY skip_percentage DESC NULLS LAST",
+        )
+        .fetch_all(&state.pool),
+    )
+    .await;
+
+    let by_route = match by_route {
+        Ok(by_route) => by_route,
+        Err(e) => {
+            error!("error counting skipped stops by route: {e:?}");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+
+    let by_station: Result<Vec<StationSkipCount>, _> = query_stats::timed(
+        "stop_skips_by_station",
+        sqlx::query_as(
+            "SELECT
+                s.station_id,
+                count(*) as scheduled_stops,
+                count(*) FILTER (WHERE s.real_arrival IS NULL AND s.real_departure IS NULL) as skipped_stops,
+                (count(*) FILTER (WHERE s.real_arrival IS NULL AND s.real_departure IS NULL)::float8
+                    / nullif(count(*), 0)) as skip_percentage
+             FROM stops s
+             JOIN routes r ON r.id = s.route_id AND r.expected_start_time = s.route_expected_start_time
+             WHERE r.real_end_time IS NOT NULL
+             GROUP BY s.station_id
+             ORDER BY skip_percentage DESC NULLS LAST",
+        )
+        .fetch_all(&state.pool),
+    )
+    .await;
+
+    match by_station {
+        Ok(by_station) => Json(serde_json::json!({ "by_route": by_route, "by_station": by_station })).into_response(),
+        Err(e) => {
+            error!("error counting skipped stops by station: {e:?}");
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
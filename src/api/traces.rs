@@ -0,0 +1,113 @@
+//! Per-run GeoJSON traces, so a specific delayed journey can be plotted on a map.
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde_json::json;
+use sqlx::{prelude::FromRow, query_as};
+use tracing::error;
+
+use crate::query_stats;
+
+use super::AppState;
+
+#[derive(FromRow)]
+struct RouteHeader {
+    id: String,
+    expected_start_time: DateTime<Utc>,
+}
+
+#[derive(FromRow)]
+struct StopPoint {
+    sequence: i16,
+    latitude: f64,
+    longitude: f64,
+    expected_arrival: DateTime<Utc>,
+    real_arrival: Option<DateTime<Utc>>,
+    expected_departure: DateTime<Utc>,
+    real_departure: Option<DateTime<Utc>>,
+}
+
+/// Renders the run identified by `numeric_id` as a GeoJSON `FeatureCollection`: a
+/// `LineString` following the stop order, plus one `Point` per stop timestamped
+/// with the observed arrival/departure time, or the scheduled time (marked
+/// `"interpolated": true`) for stops the delay checker never observed.
+pub async fn trace_geojson(State(state): State<AppState>, Path(numeric_id): Path<i64>) -> Response {
+    let route: Result<Option<RouteHeader>, _> = query_stats::timed(
+        "trace_route_header",
+        query_as("SELECT id, expected_start_time FROM routes WHERE numeric_id = $1")
+            .bind(numeric_id)
+            .fetch_optional(&state.pool),
+    )
+    .await;
+
+    let route = match route {
+        Ok(Some(route)) => route,
+        Ok(None) => return StatusCode::NOT_FOUND.into_response(),
+        Err(e) => {
+            error!("error looking up run {numeric_id}: {e:?}");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+
+    let stops: Result<Vec<StopPoint>, _> = query_stats::timed(
+        "trace_stops",
+        query_as(
+            "SELECT s.sequence, st.latitude, st.longitude,
+                    s.expected_arrival, s.real_arrival, s.expected_departure, s.real_departure
+             FROM stops s
+             JOIN stations st ON st.id = s.station_id
+             WHERE s.route_id = $1 AND s.route_expected_start_time = $2
+             ORDER BY s.sequence",
+        )
+        .bind(&route.id)
+        .bind(route.expected_start_time)
+        .fetch_all(&state.pool),
+    )
+    .await;
+
+    let stops = match stops {
+        Ok(stops) => stops,
+        Err(e) => {
+            error!("error fetching stops for run {numeric_id}: {e:?}");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+
+    let line = stops
+        .iter()
+        .map(|s| json!([s.longitude, s.latitude]))
+        .collect::<Vec<_>>();
+
+    let points = stops
+        .iter()
+        .map(|s| {
+            let (time, interpolated) = match s.real_arrival.or(s.real_departure) {
+                Some(time) => (time, false),
+                None => (s.expected_arrival.min(s.expected_departure), true),
+            };
+
+            json!({
+                "type": "Feature",
+                "geometry": { "type": "Point", "coordinates": [s.longitude, s.latitude] },
+                "properties": {
+                    "sequence": s.sequence,
+                    "time": time,
+                    "interpolated": interpolated,
+                },
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let mut features = vec![json!({
+        "type": "Feature",
+        "geometry": { "type": "LineString", "coordinates": line },
+        "properties": { "route_id": route.id },
+    })];
+    features.extend(points);
+
+    Json(json!({ "type": "FeatureCollection", "features": features })).into_response()
+}
@@ -0,0 +1,88 @@
+//! Scheduled vs realized travel time between two stations, answering "how much
+//! buffer should I plan for this trip?".
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::Utc;
+use serde::Deserialize;
+use serde_json::json;
+use sqlx::{prelude::FromRow, query_as};
+use tracing::error;
+
+use crate::query_stats;
+
+use super::AppState;
+
+#[derive(Deserialize)]
+pub struct TravelTimeQuery {
+    from: String,
+    to: String,
+    /// Rolling window, in days, to compute the distribution over. Defaults to 30.
+    period: Option<i64>,
+}
+
+#[derive(FromRow)]
+struct TravelTimeRow {
+    sample_count: i64,
+    avg_expected_minutes: Option<f64>,
+    avg_real_minutes: Option<f64>,
+    max_real_minutes: Option<f64>,
+}
+
+pub async fn travel_time(
+    State(state): State<AppState>,
+    Query(params): Query<TravelTimeQuery>,
+) -> Response {
+    let period_days = params.period.unwrap_or(30);
+    let since = Utc::now() - chrono::Duration::days(period_days);
+
+    let row: Result<TravelTimeRow, _> = query_stats::timed(
+        "travel_time",
+        query_as(
+            "SELECT
+                count(*) as sample_count,
+                avg(extract(epoch from (t.expected_arrival - f.expected_departure)) / 60)::float8
+                    as avg_expected_minutes,
+                avg(extract(epoch from (t.real_arrival - f.real_departure)) / 60)
+                    FILTER (WHERE t.real_arrival IS NOT NULL AND f.real_departure IS NOT NULL)::float8
+                    as avg_real_minutes,
+                max(extract(epoch from (t.real_arrival - f.real_departure)) / 60)
+                    FILTER (WHERE t.real_arrival IS NOT NULL AND f.real_departure IS NOT NULL)::float8
+                    as max_real_minutes
+            FROM stops f
+            JOIN stops t
+                ON t.route_id = f.route_id
+                AND t.route_expected_start_time = f.route_expected_start_time
+                AND t.sequence > f.sequence
+            WHERE f.station_id = $1 AND t.station_id = $2 AND f.route_expected_start_time >= $3",
+        )
+        .bind(&params.from)
+        .bind(&params.to)
+        .bind(since)
+        .fetch_one(&state.pool),
+    )
+    .await;
+
+    match row {
+        Ok(row) => Json(json!({
+            "from": params.from,
+            "to": params.to,
+            "period_days": period_days,
+            "sample_count": row.sample_count,
+            "avg_expected_minutes": row.avg_expected_minutes,
+            "avg_real_minutes": row.avg_real_minutes,
+            "max_real_minutes": row.max_real_minutes,
+        }))
+        .into_response(),
+        Err(e) => {
+            error!(
+                "error computing travel time from {} to {}: {e:?}",
+                params.from, params.to
+            );
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
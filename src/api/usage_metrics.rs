@@ -0,0 +1,86 @@
+//! Opt-in per-endpoint request counts and latencies, gated by
+//! `USAGE_METRICS_ENABLED` since tracking costs a lock on every request even
+//! when nobody's looking at the numbers. Keyed by (method, route template)
+//! only — no IPs, no payloads — and surfaced via [`crate::api::admin::usage_metrics`].
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use axum::{
+    extract::{MatchedPath, Request, State},
+    middleware::Next,
+    response::Response,
+};
+use serde::Serialize;
+
+use super::AppState;
+
+static STATS: OnceLock<Mutex<HashMap<(String, String), Stats>>> = OnceLock::new();
+
+#[derive(Default, Clone, Copy)]
+struct Stats {
+    count: u64,
+    total: Duration,
+    max: Duration,
+}
+
+fn stats() -> &'static Mutex<HashMap<(String, String), Stats>> {
+    STATS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Records this request's method/route/latency when `usage_metrics_enabled`
+/// is set; a no-op layer otherwise.
+pub async fn track(
+    State(state): State<AppState>,
+    matched_path: Option<MatchedPath>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if !state.usage_metrics_enabled {
+        return next.run(request).await;
+    }
+
+    let method = request.method().to_string();
+    let path = matched_path.map(|p| p.as_str().to_string()).unwrap_or_else(|| "unmatched".to_string());
+
+    let start = Instant::now();
+    let response = next.run(request).await;
+    let elapsed = start.elapsed();
+
+    let mut stats = stats().lock().unwrap();
+    let entry = stats.entry((method, path)).or_default();
+    entry.count += 1;
+    entry.total += elapsed;
+    entry.max = entry.max.max(elapsed);
+    drop(stats);
+
+    response
+}
+
+#[derive(Serialize)]
+pub struct EndpointUsage {
+    pub method: String,
+    pub path: String,
+    pub count: u64,
+    pub avg_ms: f64,
+    pub max_ms: f64,
+}
+
+/// Every (method, route) seen so far, busiest first.
+pub fn snapshot() -> Vec<EndpointUsage> {
+    let mut snapshot: Vec<_> = stats()
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|((method, path), stats)| EndpointUsage {
+            method: method.clone(),
+            path: path.clone(),
+            count: stats.count,
+            avg_ms: stats.total.as_secs_f64() * 1000.0 / stats.count as f64,
+            max_ms: stats.max.as_secs_f64() * 1000.0,
+        })
+        .collect();
+
+    snapshot.sort_by_key(|u| std::cmp::Reverse(u.count));
+    snapshot
+}
@@ -0,0 +1,46 @@
+//! Versioning for the JSON API: `/api/v1/...` is canonical, and the same
+//! routes stay mounted unprefixed at `/api/...` for compatibility. Keeping the
+//! route table in [`super::api_v1_router`] prefix-agnostic means a future
+//! `/api/v2` can nest a differently-behaving router alongside this one
+//! without disturbing it.
+use axum::{
+    extract::Request,
+    http::{HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+const CURRENT_VERSION: &str = "v1";
+
+/// Rejects a request that names a version this server doesn't serve via the
+/// `Accept-Version` header. A request with no such header gets whatever its
+/// path prefix implies.
+pub async fn negotiate_version(request: Request, next: Next) -> Response {
+    if let Some(requested) = request.headers().get("Accept-Version") {
+        if requested.as_bytes() != CURRENT_VERSION.as_bytes() {
+            return (
+                StatusCode::BAD_REQUEST,
+                format!("unsupported API version requested, this server only serves {CURRENT_VERSION}"),
+            )
+                .into_response();
+        }
+    }
+
+    next.run(request).await
+}
+
+/// Marks a response as having come from the deprecated unprefixed `/api/...`
+/// mount rather than its `/api/v1/...` equivalent.
+pub async fn mark_deprecated(request: Request, next: Next) -> Response {
+    let mut response = next.run(request).await;
+
+    response
+        .headers_mut()
+        .insert("Deprecation", HeaderValue::from_static("true"));
+    response.headers_mut().insert(
+        "Link",
+        HeaderValue::from_static("</api/v1>; rel=\"successor-version\""),
+    );
+
+    response
+}
@@ -0,0 +1,32 @@
+//! Pushes [`DelayUpdate`]s to connected clients as the delay checker
+//! observes them, so the frontend doesn't need to poll `/delays/live`.
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::State;
+use axum::response::Response;
+use tokio::sync::broadcast;
+
+use crate::background_services::delay_broadcast::DelayUpdate;
+
+use super::AppState;
+
+pub async fn delays(ws: WebSocketUpgrade, State(state): State<AppState>) -> Response {
+    ws.on_upgrade(move |socket| handle_socket(socket, state.delay_updates.subscribe()))
+}
+
+async fn handle_socket(mut socket: WebSocket, mut updates: broadcast::Receiver<DelayUpdate>) {
+    loop {
+        let update = match updates.recv().await {
+            Ok(update) => update,
+            Err(broadcast::error::RecvError::Lagged(_)) => continue,
+            Err(broadcast::error::RecvError::Closed) => break,
+        };
+
+        let Ok(text) = serde_json::to_string(&update) else {
+            continue;
+        };
+
+        if socket.send(Message::Text(text)).await.is_err() {
+            break;
+        }
+    }
+}
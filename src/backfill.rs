@@ -0,0 +1,82 @@
+//! Reusable pieces for rolling out a schema change without stopping the
+//! monitor: a dual-write toggle read from the environment so a migration can
+//! be flipped on/off without a deploy, a batched backfill loop so catching up
+//! old rows doesn't compete with the delay checker for connections, and a
+//! verification helper to confirm the backfill actually finished before the
+//! old column/table is dropped.
+use sqlx::{Pool, Postgres};
+use tracing::info;
+
+/// Whether an in-flight migration step is switched on, e.g. whether to
+/// still dual-write to an old representation, or whether an admin backfill
+/// endpoint is allowed to run at all. Each migration picks its own env var
+/// name (e.g. `"DUAL_WRITE_ROUTE_NARRATIVE"`) so several can be in flight at
+/// once without stepping on each other.
+pub fn migration_flag_enabled(flag_env_var: &str) -> bool {
+    std::env::var(flag_env_var)
+        .map(|v| v == "true" || v == "1")
+        .unwrap_or(false)
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct BackfillProgress {
+    pub batches_run: u32,
+    pub rows_updated: u64,
+    /// `false` means `run_backfill` stopped because it hit `max_batches`,
+    /// not because the backfill is done — call it again to keep going.
+    pub complete: bool,
+}
+
+/// Runs `update_sql` repeatedly until it affects zero rows or `max_batches`
+/// batches have run, whichever comes first, so one call (and the HTTP
+/// request behind it, for [`crate::api::admin::backfill_narrative_summaries`])
+/// can't block for an unbounded duration on a large backlog.
+///
+/// `update_sql` must itself be batch-limited, e.g.
+/// ```sql
+/// UPDATE routes SET new_col = old_col
+/// WHERE id IN (SELECT id FROM routes WHERE new_col IS NULL LIMIT 500)
+/// ```
+/// since Postgres `UPDATE` has no native `LIMIT`. Progress is logged after
+/// every batch so a long backfill shows up in the logs as it runs rather
+/// than only at the end.
+pub async fn run_backfill(
+    pool: &Pool<Postgres>,
+    update_sql: &str,
+    max_batches: u32,
+) -> Result<BackfillProgress, anyhow::Error> {
+    let mut progress = BackfillProgress::default();
+
+    while progress.batches_run < max_batches {
+        let result = sqlx::query(update_sql).execute(pool).await?;
+        let rows_affected = result.rows_affected();
+
+        if rows_affected == 0 {
+            progress.complete = true;
+            break;
+        }
+
+        progress.batches_run += 1;
+        progress.rows_updated += rows_affected;
+
+        info!(
+            batches_run = progress.batches_run,
+            rows_updated = progress.rows_updated,
+            "backfill batch complete"
+        );
+    }
+
+    Ok(progress)
+}
+
+/// Runs a caller-supplied query expected to return a single count of rows
+/// where the old and new representations still disagree. `0` means the
+/// backfill is verified and the old representation can be retired.
+pub async fn count_mismatches(
+    pool: &Pool<Postgres>,
+    mismatch_count_sql: &str,
+) -> Result<i64, anyhow::Error> {
+    let count: i64 = sqlx::query_scalar(mismatch_count_sql).fetch_one(pool).await?;
+
+    Ok(count)
+}
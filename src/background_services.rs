@@ -1,2 +1,16 @@
+pub mod active_monitors;
+pub mod cache_refresher;
+pub mod chaos;
 pub mod data_fetcher;
+pub mod data_integrity;
+pub mod delay_broadcast;
 pub mod delay_checker;
+pub mod digest;
+pub mod finalization;
+pub mod influx_exporter;
+pub mod live_comparison;
+pub mod log_retention;
+pub mod monitor_control;
+pub mod readiness;
+pub mod wake_schedule_stats;
+pub mod watchlist;
@@ -0,0 +1,35 @@
+//! How many routes [`super::delay_checker`] is actively polling right now,
+//! for [`crate::api::status`] to report without reaching into the delay
+//! checker's own task-spawning internals.
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+#[derive(Clone, Default, Debug)]
+pub struct ActiveMonitors(Arc<AtomicUsize>);
+
+impl ActiveMonitors {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Marks one monitor task as started; the count is decremented again
+    /// when the returned guard is dropped.
+    pub fn track(&self) -> ActiveMonitorGuard {
+        self.0.fetch_add(1, Ordering::Relaxed);
+        ActiveMonitorGuard(self.0.clone())
+    }
+
+    pub fn count(&self) -> usize {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+pub struct ActiveMonitorGuard(Arc<AtomicUsize>);
+
+impl Drop for ActiveMonitorGuard {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
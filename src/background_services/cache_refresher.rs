@@ -0,0 +1,178 @@
+//! Periodically recomputes the payloads served by the read-heavy summary
+//! endpoints so they can be served in O(1) instead of on every request.
+use std::time::Duration;
+
+use chrono::Utc;
+use serde_json::json;
+use sqlx::{prelude::FromRow, query, query_as, Pool, Postgres};
+use tokio::time::sleep;
+use tracing::{error, info};
+
+use crate::api::cache::ResponseCache;
+
+const REFRESH_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// Runs forever, recomputing the leaderboard and heatmap payloads every
+/// [`REFRESH_INTERVAL`] and writing them both to the in-memory [`ResponseCache`]
+/// and to the `cached_payloads` table, so a fresh instance can warm up from the
+/// last computed value instead of starting cold.
+pub async fn refresh_cached_payloads(pool: Pool<Postgres>, cache: ResponseCache) {
+    loop {
+        if let Err(e) = refresh_once(&pool, &cache).await {
+            error!("error refreshing cached payloads: {e:?}");
+        }
+
+        sleep(REFRESH_INTERVAL).await;
+    }
+}
+
+async fn refresh_once(pool: &Pool<Postgres>, cache: &ResponseCache) -> Result<(), anyhow::Error> {
+    let leaderboard = compute_leaderboard(pool).await?;
+    let heatmap = compute_heatmap(pool).await?;
+
+    store_payload(pool, cache, "/api/leaderboard", leaderboard).await?;
+    store_payload(pool, cache, "/api/heatmap", heatmap).await?;
+
+    update_kpis(pool).await?;
+
+    info!("refreshed cached payloads");
+
+    Ok(())
+}
+
+/// Rolling windows (in days) the `kpis` table tracks punctuality, monitored run
+/// count and data completeness for.
+const KPI_WINDOWS_DAYS: [i64; 3] = [7, 30, 365];
+
+/// Recomputes the rolling-window KPIs and upserts them into the `kpis` table, so
+/// the homepage and status endpoint can read them with a single indexed lookup
+/// instead of scanning `routes` on every request.
+async fn update_kpis(pool: &Pool<Postgres>) -> Result<(), anyhow::Error> {
+    for window_days in KPI_WINDOWS_DAYS {
+        let since = Utc::now() - chrono::Duration::days(window_days);
+
+        let (monitored_runs, punctual_runs, complete_runs): (i64, i64, i64) = query_as(
+            "SELECT
+                count(*) FILTER (WHERE real_end_time IS NOT NULL) as monitored_runs,
+                count(*) FILTER (WHERE real_end_time IS NOT NULL
+                    AND real_end_time - expected_end_time <= interval '5 minutes') as punctual_runs,
+                count(*) FILTER (WHERE real_start_time IS NOT NULL AND real_end_time IS NOT NULL) as complete_runs
+            FROM routes
+            WHERE expected_start_time >= $1",
+        )
+        .bind(since)
+        .fetch_one(pool)
+        .await?;
+
+        let punctuality = ratio(punctual_runs, monitored_runs);
+        let completeness = ratio(complete_runs, monitored_runs);
+
+        put_kpi(pool, &format!("punctuality_{window_days}d"), punctuality).await?;
+        put_kpi(
+            pool,
+            &format!("monitored_runs_{window_days}d"),
+            monitored_runs as f64,
+        )
+        .await?;
+        put_kpi(pool, &format!("data_completeness_{window_days}d"), completeness).await?;
+    }
+
+    Ok(())
+}
+
+fn ratio(numerator: i64, denominator: i64) -> f64 {
+    if denominator == 0 {
+        0.0
+    } else {
+        numerator as f64 / denominator as f64
+    }
+}
+
+async fn put_kpi(pool: &Pool<Postgres>, key: &str, value: f64) -> Result<(), anyhow::Error> {
+    query(
+        "INSERT INTO kpis (key, value, updated_at)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (key) DO UPDATE SET value = $2, updated_at = $3",
+    )
+    .bind(key)
+    .bind(value)
+    .bind(Utc::now())
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+async fn store_payload(
+    pool: &Pool<Postgres>,
+    cache: &ResponseCache,
+    key: &str,
+    payload: serde_json::Value,
+) -> Result<(), anyhow::Error> {
+    query(
+        "INSERT INTO cached_payloads (key, payload, updated_at)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (key) DO UPDATE SET payload = $2, updated_at = $3",
+    )
+    .bind(key)
+    .bind(&payload)
+    .bind(Utc::now())
+    .execute(pool)
+    .await?;
+
+    cache.put(key, payload).await;
+
+    Ok(())
+}
+
+#[derive(FromRow)]
+struct RouteDelayRow {
+    route_number: i32,
+    avg_minutes_late: f64,
+}
+
+async fn compute_leaderboard(pool: &Pool<Postgres>) -> Result<serde_json::Value, anyhow::Error> {
+    let rows: Vec<RouteDelayRow> = query_as(
+        "SELECT route_number, avg(extract(epoch from (real_end_time - expected_end_time)) / 60)::float8 as avg_minutes_late
+        FROM routes
+        WHERE real_end_time IS NOT NULL
+        GROUP BY route_number
+        ORDER BY avg_minutes_late DESC
+        LIMIT 10",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let leaderboard = rows
+        .into_iter()
+        .map(|r| json!({ "route_number": r.route_number, "avg_minutes_late": r.avg_minutes_late }))
+        .collect::<Vec<_>>();
+
+    Ok(json!({ "leaderboard": leaderboard, "stale": false }))
+}
+
+#[derive(FromRow)]
+struct HourlyDelayRow {
+    hour: f64,
+    avg_minutes_late: f64,
+}
+
+async fn compute_heatmap(pool: &Pool<Postgres>) -> Result<serde_json::Value, anyhow::Error> {
+    let rows: Vec<HourlyDelayRow> = query_as(
+        "SELECT extract(hour from expected_start_time)::float8 as hour,
+            avg(extract(epoch from (real_start_time - expected_start_time)) / 60)::float8 as avg_minutes_late
+        FROM routes
+        WHERE real_start_time IS NOT NULL
+        GROUP BY hour
+        ORDER BY hour",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let heatmap = rows
+        .into_iter()
+        .map(|r| json!({ "hour": r.hour as i32, "avg_minutes_late": r.avg_minutes_late }))
+        .collect::<Vec<_>>();
+
+    Ok(json!({ "heatmap": heatmap, "stale": false }))
+}
@@ -0,0 +1,57 @@
+//! Deterministic fault injection for the upstream HZPP client wrappers, used to
+//! exercise retry, circuit-breaker and scheduler behavior in staging without
+//! waiting for a real upstream outage. Disabled unless configured on via
+//! [`configure`]; when enabled, the seed passed there controls the RNG so a
+//! run can be reproduced.
+use rand::{rngs::StdRng, RngExt, SeedableRng};
+use std::sync::{Mutex, OnceLock};
+use tracing::warn;
+
+static CHAOS_RNG: OnceLock<Option<Mutex<StdRng>>> = OnceLock::new();
+
+/// Turns chaos mode on or off for the process. Must be called at most once,
+/// before the first upstream call; called from `main` with the loaded
+/// [`crate::config::Config`]. Calling it more than once is a bug and panics.
+pub fn configure(enabled: bool, seed: u64) {
+    let rng = if enabled {
+        warn!("chaos mode enabled with seed {seed}");
+        Some(Mutex::new(StdRng::seed_from_u64(seed)))
+    } else {
+        None
+    };
+
+    CHAOS_RNG
+        .set(rng)
+        .unwrap_or_else(|_| panic!("chaos::configure called more than once"));
+}
+
+fn rng() -> Option<&'static Mutex<StdRng>> {
+    CHAOS_RNG.get_or_init(|| None).as_ref()
+}
+
+/// A single kind of upstream failure to simulate.
+pub enum Fault {
+    /// The upstream never responds in time.
+    Timeout,
+    /// The upstream returns a 5xx as if the Unisys backend behind it fell over.
+    UnisysError,
+    /// The upstream returns an HTML error page instead of the expected JSON body.
+    MalformedHtml,
+}
+
+/// Rolls the dice for whether this upstream call should be faulted, and if so, how.
+/// Returns `None` whenever chaos mode is disabled, so this is a no-op in production.
+pub fn sample_fault() -> Option<Fault> {
+    let rng = rng()?;
+    let mut rng = rng.lock().unwrap();
+
+    if !rng.random_bool(0.1) {
+        return None;
+    }
+
+    Some(match rng.random_range(0..3) {
+        0 => Fault::Timeout,
+        1 => Fault::UnisysError,
+        _ => Fault::MalformedHtml,
+    })
+}
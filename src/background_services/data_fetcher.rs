@@ -1,24 +1,50 @@
 //! Responsible for fetching and saving routes
+use super::chaos;
+use super::monitor_control::MonitorControl;
 use crate::model::{
     db_model::{RouteDb, StationDb},
     hzpp_api_model::{HzppRoute, HzppStation},
 };
-use anyhow::Context;
+use anyhow::{bail, Context};
 use chrono::{DateTime, Days};
 use chrono_tz::{Europe::Zagreb, Tz};
 use itertools::Itertools;
-use sqlx::{postgres::PgRow, Postgres, QueryBuilder, Row};
-use std::{backtrace::Backtrace, collections::HashSet};
+use sqlx::Postgres;
+use std::{backtrace::Backtrace, collections::HashSet, time::Duration};
 use tokio::sync::mpsc::Sender;
-use tracing::{error, info, info_span, Instrument};
+use tokio::time::sleep;
+use tracing::{error, info, info_span, warn, Instrument};
 
 /// Gets todays routes and saves them to the DB.
 /// If a duplicate route is already in the DB then it's discarded.
 /// After saving to DB sends them to the delay checker.
-#[tracing::instrument(err)]
+///
+/// A no-op while `control` is paused (set from the admin panel), and records
+/// any failure into `control`'s recent-failures log for the panel to display.
+#[tracing::instrument(err, skip(control))]
 pub async fn get_todays_data(
     pool: &sqlx::Pool<Postgres>,
     delay_checker_sender: Sender<Vec<RouteDb>>,
+    control: &MonitorControl,
+) -> Result<(), anyhow::Error> {
+    if control.is_paused() {
+        info!("skipping fetch: monitoring is paused");
+        return Ok(());
+    }
+
+    let result = get_todays_data_impl(pool, delay_checker_sender).await;
+
+    match &result {
+        Ok(()) => control.record_success().await,
+        Err(e) => control.record_failure(e.to_string()).await,
+    }
+
+    result
+}
+
+async fn get_todays_data_impl(
+    pool: &sqlx::Pool<Postgres>,
+    delay_checker_sender: Sender<Vec<RouteDb>>,
 ) -> Result<(), anyhow::Error> {
     let today = chrono::Local::now().with_timezone(&Zagreb);
 
@@ -59,13 +85,24 @@ pub async fn get_todays_data(
 
     let saved_routes = save_data(db_routes, stations, pool.clone()).await?;
 
-    delay_checker_sender.send(saved_routes).await?;
+    let monitorable_routes = saved_routes
+        .into_iter()
+        .filter(|r| !r.schedule_only)
+        .collect_vec();
+
+    delay_checker_sender.send(monitorable_routes).await?;
 
     Ok(())
 }
 
 /// Returns the saved routes. If a route is already present in the DB it isn't saved.
 /// Does not save real times.
+///
+/// Binds one array per column and lets Postgres fan them out with `UNNEST`
+/// instead of building a `VALUES` list with a bind per cell. A single query
+/// plan gets reused regardless of how many rows are in the batch, rather than
+/// sqlx (and the planner) seeing a differently-shaped statement every time the
+/// row count changes.
 #[tracing::instrument(err, skip(routes))]
 async fn save_data(
     routes: Vec<RouteDb>,
@@ -74,35 +111,24 @@ async fn save_data(
 ) -> Result<Vec<RouteDb>, anyhow::Error> {
     let transaction = pool.begin().await?;
 
-    let mut query_builder = QueryBuilder::new(
-        "INSERT into stations (
-                id,
-                code,
-                name,
-                latitude,
-                longitude
-            )",
-    );
-
-    query_builder.push_values(&stations, |mut b, station| {
-        b.push_bind(station.id.clone())
-            .push_bind(station.code)
-            .push_bind(station.name.clone())
-            .push_bind(station.latitude)
-            .push_bind(station.longitude);
-    });
-
-    query_builder.push(" ON CONFLICT ( id ) DO NOTHING");
-
-    query_builder
-        .build()
-        .execute(&pool)
-        .instrument(info_span!("Inserting stations"))
-        .await?;
-
-    let mut query_builder = QueryBuilder::new(
+    sqlx::query(
+        "INSERT INTO stations (id, code, name, latitude, longitude)
+         SELECT * FROM UNNEST($1::text[], $2::int4[], $3::text[], $4::float8[], $5::float8[])
+         ON CONFLICT ( id ) DO NOTHING",
+    )
+    .bind(stations.iter().map(|s| s.id.clone()).collect_vec())
+    .bind(stations.iter().map(|s| s.code).collect_vec())
+    .bind(stations.iter().map(|s| s.name.clone()).collect_vec())
+    .bind(stations.iter().map(|s| s.latitude).collect_vec())
+    .bind(stations.iter().map(|s| s.longitude).collect_vec())
+    .execute(&pool)
+    .instrument(info_span!("Inserting stations"))
+    .await?;
+
+    let saved_route_nums: Vec<i32> = sqlx::query_scalar(
         "INSERT INTO routes (
             id,
+            slug,
             route_number,
             source,
             destination,
@@ -110,74 +136,86 @@ async fn save_data(
             wheelchair_accessible,
             route_type,
             expected_start_time,
-            expected_end_time
-        )",
-    );
-
-    query_builder.push_values(&routes, |mut b, route| {
-        b.push_bind(&route.id)
-            .push_bind(route.route_number)
-            .push_bind(&route.source)
-            .push_bind(&route.destination)
-            .push_bind(route.bikes_allowed as i16)
-            .push_bind(route.wheelchair_accessible as i16)
-            .push_bind(route.route_type as i16)
-            .push_bind(route.expected_start_time)
-            .push_bind(route.expected_end_time);
-    });
-
-    query_builder
-        .push(" ON CONFLICT ( expected_start_time, id ) DO NOTHING RETURNING route_number");
-
-    let query = query_builder.build();
-
-    let saved_route_nums = query
-        .map(|row: PgRow| {
-            let route_number: i32 = row.try_get(0).unwrap();
-
-            route_number
-        })
-        .fetch_all(&pool)
-        .instrument(info_span!("Inserting routes"))
-        .await?;
+            expected_end_time,
+            schedule_only
+        )
+        SELECT * FROM UNNEST(
+            $1::text[],
+            $2::text[],
+            $3::int4[],
+            $4::text[],
+            $5::text[],
+            $6::int2[],
+            $7::int2[],
+            $8::int2[],
+            $9::timestamptz[],
+            $10::timestamptz[],
+            $11::bool[]
+        )
+        ON CONFLICT ( expected_start_time, id ) DO NOTHING
+        RETURNING route_number",
+    )
+    .bind(routes.iter().map(|r| r.id.clone()).collect_vec())
+    .bind(routes.iter().map(|r| r.slug.clone()).collect_vec())
+    .bind(routes.iter().map(|r| r.route_number).collect_vec())
+    .bind(routes.iter().map(|r| r.source.clone()).collect_vec())
+    .bind(routes.iter().map(|r| r.destination.clone()).collect_vec())
+    .bind(routes.iter().map(|r| r.bikes_allowed as i16).collect_vec())
+    .bind(
+        routes
+            .iter()
+            .map(|r| r.wheelchair_accessible as i16)
+            .collect_vec(),
+    )
+    .bind(routes.iter().map(|r| r.route_type as i16).collect_vec())
+    .bind(routes.iter().map(|r| r.expected_start_time).collect_vec())
+    .bind(routes.iter().map(|r| r.expected_end_time).collect_vec())
+    .bind(routes.iter().map(|r| r.schedule_only).collect_vec())
+    .fetch_all(&pool)
+    .instrument(info_span!("Inserting routes"))
+    .await?;
 
     let all_stops = routes.iter().flat_map(|r| &r.stops).collect_vec();
-    let stops_chunks = all_stops.chunks(1024).collect_vec();
-
-    for stops in stops_chunks {
-        let mut query_builder = QueryBuilder::new(
-            "INSERT into stops (
-                station_id,
-                route_id,
-                route_expected_start_time,
-                sequence,
-                real_arrival,
-                expected_arrival,
-                real_departure,
-                expected_departure
-            )",
-        );
-
-        query_builder.push_values(stops, |mut b, stop| {
-            b.push_bind(&stop.station_id)
-                .push_bind(&stop.route_id)
-                .push_bind(stop.route_expected_start_time)
-                .push_bind(stop.sequence)
-                .push_bind(stop.real_arrival)
-                .push_bind(stop.expected_arrival)
-                .push_bind(stop.real_departure)
-                .push_bind(stop.expected_departure);
-        });
-
-        query_builder
-            .push(" ON CONFLICT ( route_id, route_expected_start_time, sequence ) DO NOTHING");
-
-        query_builder
-            .build()
-            .execute(&pool)
-            .instrument(info_span!("Inserting stops"))
-            .await?;
-    }
+
+    sqlx::query(
+        "INSERT INTO stops (
+            station_id,
+            route_id,
+            route_expected_start_time,
+            sequence,
+            real_arrival,
+            expected_arrival,
+            real_departure,
+            expected_departure
+        )
+        SELECT * FROM UNNEST(
+            $1::text[],
+            $2::text[],
+            $3::timestamptz[],
+            $4::int2[],
+            $5::timestamptz[],
+            $6::timestamptz[],
+            $7::timestamptz[],
+            $8::timestamptz[]
+        )
+        ON CONFLICT ( route_id, route_expected_start_time, sequence ) DO NOTHING",
+    )
+    .bind(all_stops.iter().map(|s| s.station_id.clone()).collect_vec())
+    .bind(all_stops.iter().map(|s| s.route_id.clone()).collect_vec())
+    .bind(
+        all_stops
+            .iter()
+            .map(|s| s.route_expected_start_time)
+            .collect_vec(),
+    )
+    .bind(all_stops.iter().map(|s| s.sequence).collect_vec())
+    .bind(all_stops.iter().map(|s| s.real_arrival).collect_vec())
+    .bind(all_stops.iter().map(|s| s.expected_arrival).collect_vec())
+    .bind(all_stops.iter().map(|s| s.real_departure).collect_vec())
+    .bind(all_stops.iter().map(|s| s.expected_departure).collect_vec())
+    .execute(&pool)
+    .instrument(info_span!("Inserting stops"))
+    .await?;
 
     transaction.commit().await?;
 
@@ -193,6 +231,10 @@ async fn save_data(
 
 #[tracing::instrument(err)]
 async fn fetch_routes(date: DateTime<Tz>) -> Result<Vec<HzppRoute>, GetRoutesError> {
+    if let Some(fault) = chaos::sample_fault() {
+        return inject_route_fault(fault).await;
+    }
+
     let request = format!(
         "https://josipsalkovic.com/hzpp/planer/v3/getRoutes.php?date={}",
         date.format("%Y%m%d")
@@ -222,6 +264,10 @@ async fn fetch_routes(date: DateTime<Tz>) -> Result<Vec<HzppRoute>, GetRoutesErr
 
 #[tracing::instrument(err)]
 async fn fetch_stations() -> Result<Vec<HzppStation>, anyhow::Error> {
+    if let Some(fault) = chaos::sample_fault() {
+        return inject_station_fault(fault).await;
+    }
+
     let request = format!("https://josipsalkovic.com/hzpp/planer/v3/getStops.php");
 
     let response = reqwest::get(&request)
@@ -242,6 +288,51 @@ async fn fetch_stations() -> Result<Vec<HzppStation>, anyhow::Error> {
     Ok(stations)
 }
 
+/// Simulates `fault` in place of an actual `getRoutes.php` call, reusing the
+/// normal error variants so downstream retry/circuit-breaker code can't tell
+/// the difference from a real outage.
+async fn inject_route_fault(fault: chaos::Fault) -> Result<Vec<HzppRoute>, GetRoutesError> {
+    warn!("chaos mode injecting a fault into fetch_routes");
+    match fault {
+        chaos::Fault::Timeout => {
+            sleep(Duration::from_secs(30)).await;
+            Err(GetRoutesError::SimulatedFault(
+                "chaos: simulated upstream timeout".to_string(),
+            ))
+        }
+        chaos::Fault::UnisysError => Err(GetRoutesError::SimulatedFault(
+            "chaos: simulated Unisys 500 error".to_string(),
+        )),
+        chaos::Fault::MalformedHtml => {
+            let routes_string = "<html><body>500 Internal Server Error</body></html>".to_string();
+            serde_json::from_str::<Vec<HzppRoute>>(&routes_string).map_err(|e| {
+                GetRoutesError::ParsingError {
+                    source: e,
+                    backtrace: Backtrace::capture(),
+                    routes: routes_string,
+                }
+            })
+        }
+    }
+}
+
+/// Simulates `fault` in place of an actual `getStops.php` call.
+async fn inject_station_fault(fault: chaos::Fault) -> Result<Vec<HzppStation>, anyhow::Error> {
+    warn!("chaos mode injecting a fault into fetch_stations");
+    match fault {
+        chaos::Fault::Timeout => {
+            sleep(Duration::from_secs(30)).await;
+            bail!("chaos: simulated upstream timeout");
+        }
+        chaos::Fault::UnisysError => bail!("chaos: simulated Unisys 500 error"),
+        chaos::Fault::MalformedHtml => {
+            let stations_string = "<html><body>500 Internal Server Error</body></html>";
+            serde_json::from_str::<Vec<HzppStation>>(stations_string)
+                .context("Error parsing stations")
+        }
+    }
+}
+
 #[derive(thiserror::Error, Debug)]
 enum GetRoutesError {
     #[error("error fetching the routes \n{} \n{}", source, backtrace)]
@@ -257,4 +348,7 @@ enum GetRoutesError {
         backtrace: Backtrace,
         routes: String,
     },
+
+    #[error("{0}")]
+    SimulatedFault(String),
 }
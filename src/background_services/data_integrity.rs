@@ -0,0 +1,189 @@
+//! Nightly consistency sweep between `routes`, `stops` and `stations`.
+//! Findings replace the previous run's in `data_issues`, so the table always
+//! reflects the current state of the data rather than accumulating history.
+use std::time::Duration;
+
+use serde_json::{json, Value};
+use sqlx::{prelude::FromRow, query, Pool, Postgres};
+use tokio::time::sleep;
+use tracing::{error, info};
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Runs forever, re-checking data integrity every [`CHECK_INTERVAL`].
+pub async fn run_checks_periodically(pool: Pool<Postgres>) {
+    loop {
+        if let Err(e) = run_checks_once(&pool).await {
+            error!("error running data integrity checks: {e:?}");
+        }
+
+        sleep(CHECK_INTERVAL).await;
+    }
+}
+
+async fn run_checks_once(pool: &Pool<Postgres>) -> Result<(), anyhow::Error> {
+    let mut issues = Vec::new();
+    issues.extend(find_orphan_stops(pool).await?);
+    issues.extend(find_stopless_runs(pool).await?);
+    issues.extend(find_stops_with_unknown_stations(pool).await?);
+    issues.extend(find_overlapping_runs(pool).await?);
+
+    let mut transaction = pool.begin().await?;
+
+    query("DELETE FROM data_issues").execute(&mut *transaction).await?;
+
+    for (kind, detail) in &issues {
+        query("INSERT INTO data_issues (kind, detail) VALUES ($1, $2)")
+            .bind(kind)
+            .bind(detail)
+            .execute(&mut *transaction)
+            .await?;
+    }
+
+    transaction.commit().await?;
+
+    info!("data integrity check found {} issue(s)", issues.len());
+
+    Ok(())
+}
+
+#[derive(FromRow)]
+struct OrphanStop {
+    route_id: String,
+    route_expected_start_time: chrono::DateTime<chrono::Utc>,
+    sequence: i16,
+}
+
+/// Stops whose run doesn't exist in `routes` (e.g. left behind after a route
+/// row was deleted out of band).
+async fn find_orphan_stops(pool: &Pool<Postgres>) -> Result<Vec<(&'static str, Value)>, anyhow::Error> {
+    let rows: Vec<OrphanStop> = sqlx::query_as(
+        "SELECT s.route_id, s.route_expected_start_time, s.sequence
+         FROM stops s
+         LEFT JOIN routes r ON r.id = s.route_id AND r.expected_start_time = s.route_expected_start_time
+         WHERE r.id IS NULL",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|r| {
+            (
+                "orphan_stop",
+                json!({
+                    "route_id": r.route_id,
+                    "route_expected_start_time": r.route_expected_start_time,
+                    "sequence": r.sequence,
+                }),
+            )
+        })
+        .collect())
+}
+
+#[derive(FromRow)]
+struct StoplessRun {
+    id: String,
+    expected_start_time: chrono::DateTime<chrono::Utc>,
+}
+
+/// Runs the planner gave us with no stops at all, which the delay checker
+/// can't monitor anything meaningful for.
+async fn find_stopless_runs(pool: &Pool<Postgres>) -> Result<Vec<(&'static str, Value)>, anyhow::Error> {
+    let rows: Vec<StoplessRun> = sqlx::query_as(
+        "SELECT r.id, r.expected_start_time
+         FROM routes r
+         LEFT JOIN stops s ON s.route_id = r.id AND s.route_expected_start_time = r.expected_start_time
+         WHERE s.station_id IS NULL",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|r| {
+            (
+                "stopless_run",
+                json!({ "route_id": r.id, "expected_start_time": r.expected_start_time }),
+            )
+        })
+        .collect())
+}
+
+#[derive(FromRow)]
+struct UnknownStationStop {
+    route_id: String,
+    route_expected_start_time: chrono::DateTime<chrono::Utc>,
+    sequence: i16,
+    station_id: String,
+}
+
+/// Stops referencing a `station_id` that isn't in `stations`.
+async fn find_stops_with_unknown_stations(
+    pool: &Pool<Postgres>,
+) -> Result<Vec<(&'static str, Value)>, anyhow::Error> {
+    let rows: Vec<UnknownStationStop> = sqlx::query_as(
+        "SELECT s.route_id, s.route_expected_start_time, s.sequence, s.station_id
+         FROM stops s
+         LEFT JOIN stations st ON st.id = s.station_id
+         WHERE st.id IS NULL",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|r| {
+            (
+                "unknown_station",
+                json!({
+                    "route_id": r.route_id,
+                    "route_expected_start_time": r.route_expected_start_time,
+                    "sequence": r.sequence,
+                    "station_id": r.station_id,
+                }),
+            )
+        })
+        .collect())
+}
+
+#[derive(FromRow)]
+struct OverlappingRuns {
+    route_number: i32,
+    first_id: String,
+    first_expected_start_time: chrono::DateTime<chrono::Utc>,
+    second_id: String,
+    second_expected_start_time: chrono::DateTime<chrono::Utc>,
+}
+
+/// Two runs of the same numbered service whose scheduled windows overlap,
+/// which shouldn't happen for a single physical train.
+async fn find_overlapping_runs(pool: &Pool<Postgres>) -> Result<Vec<(&'static str, Value)>, anyhow::Error> {
+    let rows: Vec<OverlappingRuns> = sqlx::query_as(
+        "SELECT
+            a.route_number,
+            a.id as first_id, a.expected_start_time as first_expected_start_time,
+            b.id as second_id, b.expected_start_time as second_expected_start_time
+         FROM routes a
+         JOIN routes b ON a.route_number = b.route_number
+             AND (a.id, a.expected_start_time) < (b.id, b.expected_start_time)
+         WHERE a.expected_start_time < b.expected_end_time
+             AND b.expected_start_time < a.expected_end_time",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|r| {
+            (
+                "overlapping_runs",
+                json!({
+                    "route_number": r.route_number,
+                    "first": { "route_id": r.first_id, "expected_start_time": r.first_expected_start_time },
+                    "second": { "route_id": r.second_id, "expected_start_time": r.second_expected_start_time },
+                }),
+            )
+        })
+        .collect())
+}
@@ -0,0 +1,66 @@
+//! Broadcasts a message every time the delay checker records a new stop
+//! arrival/departure or a change to a run's observed delay, so `/ws/delays`
+//! can push updates to connected clients instead of them polling the API.
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tokio::sync::broadcast;
+
+/// Bounded so a burst of updates with no subscribers can't grow unbounded;
+/// a lagging subscriber just misses the oldest ones (see
+/// [`broadcast::Receiver::recv`]'s `Lagged` case) rather than blocking senders.
+const CHANNEL_CAPACITY: usize = 256;
+
+#[derive(Clone, Debug, Serialize)]
+pub struct DelayUpdate {
+    pub route_id: String,
+    pub route_number: i32,
+    /// `None` for a delay-only update (e.g. a new worst-delay-so-far) not
+    /// tied to a particular stop.
+    pub sequence: Option<i16>,
+    /// The stop this update is about, mirroring `sequence` — `None` under
+    /// the same circumstances.
+    pub station_id: Option<String>,
+    /// What kind of observation this is: `"arrival"`, `"departure"`, or
+    /// `"delay"` for the sequence-less worst-delay-so-far case.
+    pub event: &'static str,
+    pub minutes_late: i32,
+    /// Server clock at the moment this update was recorded.
+    pub updated_at: DateTime<Utc>,
+    /// When HŽ's own page says it last refreshed this train's status, as
+    /// opposed to `updated_at` (our fetch/record time) — lets a consumer
+    /// tell a position HŽ itself hasn't updated in a while from a live one.
+    pub upstream_updated_at: DateTime<Utc>,
+}
+
+#[derive(Clone, Debug)]
+pub struct DelayUpdates {
+    sender: broadcast::Sender<DelayUpdate>,
+}
+
+impl DelayUpdates {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(CHANNEL_CAPACITY);
+        Self { sender }
+    }
+
+    /// Sends `update` to every current subscriber. No subscribers isn't an
+    /// error, it just means nobody's connected to `/ws/delays` right now.
+    pub fn send(&self, update: DelayUpdate) {
+        let _ = self.sender.send(update);
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<DelayUpdate> {
+        self.sender.subscribe()
+    }
+
+    /// How many `/ws/delays` clients are currently subscribed.
+    pub fn subscriber_count(&self) -> usize {
+        self.sender.receiver_count()
+    }
+}
+
+impl Default for DelayUpdates {
+    fn default() -> Self {
+        Self::new()
+    }
+}
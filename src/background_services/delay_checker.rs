@@ -2,32 +2,107 @@
 
 use std::{
     collections::{hash_map::RandomState, HashMap},
-    time::Duration,
+    sync::Arc,
+    time::{Duration, Instant},
     vec,
 };
 
-use anyhow::{anyhow, bail, Context};
-use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
-use chrono_tz::Europe::Zagreb;
+use anyhow::{bail, Context};
+use chrono::{DateTime, Utc};
 use itertools::Itertools;
 use reqwest::{header::HeaderValue, Client, Method, Url};
 use sqlx::{query, query_as, Pool, Postgres};
-use tokio::{spawn, sync::mpsc::Receiver, task::JoinSet, time::sleep};
+use tokio::{
+    spawn,
+    sync::{mpsc::Receiver, Mutex},
+    task::JoinSet,
+    time::sleep,
+};
 use tracing::{error, info, info_span, Instrument};
 
 use crate::{
+    background_services::{
+        active_monitors::ActiveMonitors,
+        delay_broadcast::{DelayUpdate, DelayUpdates},
+        live_comparison::{LiveComparisons, RouteComparison},
+        wake_schedule_stats, watchlist::WatchList,
+    },
     model::db_model::{RouteDb, StationDb, StopDb},
-    utils::str_between_str,
 };
 
+mod parsers;
+
+/// Gap used while [`WatchList::is_watched`] is true for the route, overriding
+/// [`adaptive_poll_interval_secs`], so a user-flagged "watch tonight" run gets
+/// caught sooner after each change regardless of where it is in its run.
+const WATCHED_POLL_INTERVAL_SECS: u64 = 15;
+/// Gap used before the train has formed/departed, where there's nothing new
+/// to observe between polls.
+const NOT_STARTED_POLL_INTERVAL_SECS: u64 = 300;
+/// Gap used close to a scheduled stop event (its own arrival/departure, or
+/// just after departing the previous one), when a status change is likely.
+const NEAR_STOP_POLL_INTERVAL_SECS: u64 = 30;
+/// Gap used mid-segment, away from any scheduled stop event, to cut load on
+/// `traindelay.hzpp.hr` while the train isn't expected to do anything new.
+const MID_SEGMENT_POLL_INTERVAL_SECS: u64 = 240;
+/// How close to a stop's scheduled arrival/departure counts as "imminent".
+const NEAR_STOP_WINDOW_MINUTES: i64 = 5;
+/// How long after a departure we keep polling at [`NEAR_STOP_POLL_INTERVAL_SECS`].
+const JUST_DEPARTED_WINDOW_MINUTES: i64 = 3;
+
+/// How often to poll `route`'s status next, based on how far it is from a
+/// scheduled stop event. Closer to an arrival/departure (or just after one)
+/// a status change is likely, so we poll often; mid-segment or before the
+/// train has even formed, nothing's going to change between polls, so we
+/// back off.
+fn adaptive_poll_interval_secs(
+    route: &RouteDb,
+    train_has_started: bool,
+    last_status: Option<&TrainStatus>,
+) -> u64 {
+    if !train_has_started {
+        return NOT_STARTED_POLL_INTERVAL_SECS;
+    }
+
+    let now = Utc::now();
+
+    if let Some(TrainStatus { status: Status::DepartingFromStation(departed_at), .. }) = last_status {
+        if now - *departed_at < chrono::Duration::try_minutes(JUST_DEPARTED_WINDOW_MINUTES).unwrap() {
+            return NEAR_STOP_POLL_INTERVAL_SECS;
+        }
+    }
+
+    let near_stop_window = chrono::Duration::try_minutes(NEAR_STOP_WINDOW_MINUTES).unwrap();
+    let near_scheduled_stop = route.stops.iter().any(|s| {
+        (s.real_arrival.is_none() && (s.expected_arrival - now).abs() < near_stop_window)
+            || (s.real_departure.is_none() && (s.expected_departure - now).abs() < near_stop_window)
+    });
+
+    if near_scheduled_stop {
+        NEAR_STOP_POLL_INTERVAL_SECS
+    } else {
+        MID_SEGMENT_POLL_INTERVAL_SECS
+    }
+}
+
 /// Checks the delays of the routes from the given channel and saves them to the DB
 pub async fn check_delays(
     delay_checker_receiver: &mut Receiver<Vec<RouteDb>>,
     pool: &Pool<Postgres>,
+    live_comparisons: &LiveComparisons,
+    delay_response_cache: &DelayResponseCache,
+    delay_updates: &DelayUpdates,
+    watch_list: &WatchList,
+    active_monitors: &ActiveMonitors,
 ) -> Result<(), anyhow::Error> {
     let mut buffer: Vec<Vec<RouteDb>> = vec![];
 
     let pool1 = pool.clone();
+    let live_comparisons1 = live_comparisons.clone();
+    let delay_response_cache1 = delay_response_cache.clone();
+    let delay_updates1 = delay_updates.clone();
+    let watch_list1 = watch_list.clone();
+    let active_monitors1 = active_monitors.clone();
     spawn(
         async move {
             let unfinished_routes = match get_unfinished_routes(&pool1).await {
@@ -37,7 +112,16 @@ pub async fn check_delays(
                     return;
                 }
             };
-            spawn_route_delay_tasks(unfinished_routes, &pool1).await;
+            spawn_route_delay_tasks(
+                unfinished_routes,
+                &pool1,
+                &live_comparisons1,
+                &delay_response_cache1,
+                &delay_updates1,
+                &watch_list1,
+                &active_monitors1,
+            )
+            .await;
         }
         .instrument(info_span!("spawn_unfinished_route_tasks")),
     );
@@ -45,7 +129,16 @@ pub async fn check_delays(
     while delay_checker_receiver.recv_many(&mut buffer, 32).await != 0 {
         let routes = buffer.drain(..).flatten().collect_vec();
 
-        spawn_route_delay_tasks(routes, pool).await;
+        spawn_route_delay_tasks(
+            routes,
+            pool,
+            live_comparisons,
+            delay_response_cache,
+            delay_updates,
+            watch_list,
+            active_monitors,
+        )
+        .await;
     }
 
     info!("Channel closed");
@@ -53,7 +146,16 @@ pub async fn check_delays(
     Ok(())
 }
 
-async fn spawn_route_delay_tasks(routes: Vec<RouteDb>, pool: &Pool<Postgres>) {
+#[allow(clippy::too_many_arguments)]
+async fn spawn_route_delay_tasks(
+    routes: Vec<RouteDb>,
+    pool: &Pool<Postgres>,
+    live_comparisons: &LiveComparisons,
+    delay_response_cache: &DelayResponseCache,
+    delay_updates: &DelayUpdates,
+    watch_list: &WatchList,
+    active_monitors: &ActiveMonitors,
+) {
     for route in routes {
         let secs_until_end = route.expected_end_time.timestamp() - Utc::now().timestamp();
         if secs_until_end < 0 {
@@ -63,15 +165,30 @@ async fn spawn_route_delay_tasks(routes: Vec<RouteDb>, pool: &Pool<Postgres>) {
         }
 
         let delay_pool = pool.clone();
-        spawn(monitor_route(route, delay_pool));
+        let live_comparisons = live_comparisons.clone();
+        let delay_response_cache = delay_response_cache.clone();
+        let delay_updates = delay_updates.clone();
+        let watch_list = watch_list.clone();
+        let active_monitors = active_monitors.clone();
+        spawn(monitor_route(
+            route,
+            delay_pool,
+            live_comparisons,
+            delay_response_cache,
+            delay_updates,
+            watch_list,
+            active_monitors,
+        ));
     }
 }
 
 #[tracing::instrument(err, skip(pool))]
 async fn get_unfinished_routes(pool: &Pool<Postgres>) -> Result<Vec<RouteDb>, anyhow::Error> {
     let routes: Vec<RouteDb> = query_as(
-        "SELECT 
+        "SELECT
         id,
+        numeric_id,
+        slug,
         route_number,
         source,
         destination,
@@ -81,8 +198,14 @@ async fn get_unfinished_routes(pool: &Pool<Postgres>) -> Result<Vec<RouteDb>, an
         expected_start_time,
         expected_end_time,
         real_start_time,
-        real_end_time
-        from routes where real_end_time IS NULL or real_start_time IS NULL",
+        real_start_time_inferred,
+        real_end_time,
+        max_delay_minutes,
+        final_delay_minutes,
+        schedule_only,
+        narrative_summary
+        from routes where (real_end_time IS NULL or real_start_time IS NULL)
+        and schedule_only = false",
     )
     .fetch_all(pool)
     .await?;
@@ -92,12 +215,11 @@ async fn get_unfinished_routes(pool: &Pool<Postgres>) -> Result<Vec<RouteDb>, an
     for mut route in routes.into_iter() {
         let pool = pool.clone();
         set.spawn(async move {
-            let stops: Vec<StopDb> = query_as!(
-                StopDb,
+            let stops: Vec<StopDb> = query_as(
                 "SELECT * from stops where route_id = $1 and route_expected_start_time = $2",
-                route.id.clone(),
-                route.expected_start_time
             )
+            .bind(route.id.clone())
+            .bind(route.expected_start_time)
             .fetch_all(&pool)
             .await?;
 
@@ -117,7 +239,41 @@ async fn get_unfinished_routes(pool: &Pool<Postgres>) -> Result<Vec<RouteDb>, an
     Ok(routes)
 }
 
-async fn monitor_route(route: RouteDb, pool: Pool<Postgres>) -> Result<(), anyhow::Error> {
+/// Monitors a single route regardless of where it came from. Used both for
+/// routes coming from the planner API and for ad-hoc routes created through
+/// the admin API/CLI.
+#[allow(clippy::too_many_arguments)]
+pub async fn monitor_adhoc_route(
+    route: RouteDb,
+    pool: Pool<Postgres>,
+    live_comparisons: LiveComparisons,
+    delay_response_cache: DelayResponseCache,
+    delay_updates: DelayUpdates,
+    watch_list: WatchList,
+    active_monitors: ActiveMonitors,
+) -> Result<(), anyhow::Error> {
+    monitor_route(
+        route,
+        pool,
+        live_comparisons,
+        delay_response_cache,
+        delay_updates,
+        watch_list,
+        active_monitors,
+    )
+    .await
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn monitor_route(
+    route: RouteDb,
+    pool: Pool<Postgres>,
+    live_comparisons: LiveComparisons,
+    delay_response_cache: DelayResponseCache,
+    delay_updates: DelayUpdates,
+    watch_list: WatchList,
+    active_monitors: ActiveMonitors,
+) -> Result<(), anyhow::Error> {
     let secs_until_start = route.expected_start_time.timestamp() - Utc::now().timestamp();
     let secs_until_end = route.expected_end_time.timestamp() - Utc::now().timestamp();
 
@@ -134,7 +290,16 @@ async fn monitor_route(route: RouteDb, pool: Pool<Postgres>) -> Result<(), anyho
     if secs_until_start <= 0 {
         info!("Got ongoing route, starting monitoring");
 
-        check_delay_until_route_completion(route, pool).await?;
+        check_delay_until_route_completion(
+            route,
+            pool,
+            live_comparisons,
+            delay_response_cache,
+            delay_updates,
+            watch_list,
+            active_monitors,
+        )
+        .await?;
 
         return Ok(());
     }
@@ -145,17 +310,50 @@ async fn monitor_route(route: RouteDb, pool: Pool<Postgres>) -> Result<(), anyho
     ))
     .await;
 
-    check_delay_until_route_completion(route, pool).await?;
+    check_delay_until_route_completion(
+        route,
+        pool,
+        live_comparisons,
+        delay_response_cache,
+        delay_updates,
+        watch_list,
+        active_monitors,
+    )
+    .await?;
 
     Ok(())
 }
 
-#[tracing::instrument(err, fields(route_number=route.route_number))]
+#[allow(clippy::too_many_arguments)]
+#[tracing::instrument(err, skip(active_monitors), fields(route_number=route.route_number))]
 async fn check_delay_until_route_completion(
     mut route: RouteDb,
     pool: Pool<Postgres>,
+    live_comparisons: LiveComparisons,
+    delay_response_cache: DelayResponseCache,
+    delay_updates: DelayUpdates,
+    watch_list: WatchList,
+    active_monitors: ActiveMonitors,
 ) -> Result<(), anyhow::Error> {
+    let _active_monitor_guard = active_monitors.track();
+    let woke_at = Utc::now();
+    let mut first_observation_recorded = false;
+
     let mut train_has_started = route.real_start_time.is_some();
+    let mut last_status: Option<TrainStatus> = None;
+
+    if !train_has_started {
+        // Picks up any admin corrections (fixed sequence, removed phantom
+        // stop, ...) made to this run's stops while it was waiting to start.
+        route.stops = query_as(
+            "SELECT * from stops where route_id = $1 and route_expected_start_time = $2",
+        )
+        .bind(&route.id)
+        .bind(route.expected_start_time)
+        .fetch_all(&pool)
+        .await?;
+    }
+
     let stations: HashMap<String, StationDb, RandomState> = HashMap::from_iter(
         get_stations(pool.clone())
             .await?
@@ -163,9 +361,23 @@ async fn check_delay_until_route_completion(
             .map(|s| (s.id.clone(), s)),
     );
 
+    let baseline = get_baseline_delays_by_sequence(&pool, route.route_number)
+        .await
+        .unwrap_or_else(|e| {
+            error!("error loading historical baseline for route {}: {e:?}", route.route_number);
+            HashMap::new()
+        });
+
     loop {
-        sleep(Duration::from_secs(60))
-            .instrument(info_span!("Waiting 60 seconds"))
+        let watched = watch_list.is_watched(route.route_number).await;
+        let poll_interval_secs = if watched {
+            WATCHED_POLL_INTERVAL_SECS
+        } else {
+            adaptive_poll_interval_secs(&route, train_has_started, last_status.as_ref())
+        };
+
+        sleep(Duration::from_secs(poll_interval_secs))
+            .instrument(info_span!("Waiting for next poll"))
             .await;
 
         if Utc::now() > route.expected_end_time + chrono::Duration::try_hours(12).unwrap() {
@@ -182,7 +394,7 @@ async fn check_delay_until_route_completion(
             return Ok(());
         }
 
-        let status: TrainStatus = match get_route_status(&route).await {
+        let status: TrainStatus = match delay_response_cache.get_or_fetch(&route).await {
             Ok(dr) => match dr {
                 StatusResponse::TrainStatus(ts) => ts,
                 StatusResponse::TrainNotEvidented => {
@@ -200,6 +412,12 @@ async fn check_delay_until_route_completion(
             }
         };
 
+        if watched {
+            info!(?status, "watched route status");
+        }
+
+        last_status = Some(status.clone());
+
         let minutes_late = status.get_minutes_late();
 
         if minutes_late.is_none() {
@@ -207,50 +425,126 @@ async fn check_delay_until_route_completion(
         }
         let minutes_late = minutes_late.unwrap();
 
+        if !first_observation_recorded {
+            first_observation_recorded = true;
+            wake_schedule_stats::record_first_observation(route.route_number, woke_at, Utc::now());
+        }
+
+        if minutes_late > route.max_delay_minutes.unwrap_or(0) {
+            route.max_delay_minutes = Some(minutes_late);
+            update_route_real_times(&route, &pool).await?;
+
+            delay_updates.send(DelayUpdate {
+                route_id: route.id.clone(),
+                route_number: route.route_number,
+                sequence: None,
+                station_id: None,
+                event: "delay",
+                minutes_late,
+                updated_at: Utc::now(),
+                upstream_updated_at: status.page_updated_at,
+            });
+        }
+
         match status.status {
             Status::Formed(_) => {
                 continue;
             }
             Status::DepartingFromStation(_) => {
+                let is_origin_departure = is_status_at_origin_stop(&route, &stations, &status);
+
                 if route.real_start_time.is_none() {
                     train_has_started = true;
                     route.real_start_time = Some(
                         route.expected_start_time
                             + chrono::Duration::try_minutes(minutes_late.into()).unwrap(),
                     );
+                    route.real_start_time_inferred = !is_origin_departure;
+                    update_route_real_times(&route, &pool).await?;
+                } else if route.real_start_time_inferred && is_origin_departure {
+                    route.real_start_time = Some(
+                        route.expected_start_time
+                            + chrono::Duration::try_minutes(minutes_late.into()).unwrap(),
+                    );
+                    route.real_start_time_inferred = false;
                     update_route_real_times(&route, &pool).await?;
                 }
 
-                update_current_stop_departure(&mut route, &stations, &status, minutes_late, &pool)
-                    .await?;
+                update_current_stop_departure(
+                    &mut route,
+                    &stations,
+                    &status,
+                    minutes_late,
+                    &pool,
+                    &baseline,
+                    &live_comparisons,
+                    &delay_updates,
+                )
+                .await?;
             }
             Status::Arriving(_) => {
+                let is_origin_departure = is_status_at_origin_stop(&route, &stations, &status);
+
                 if route.real_start_time.is_none() {
                     train_has_started = true;
                     route.real_start_time = Some(
                         route.expected_start_time
                             + chrono::Duration::try_minutes(minutes_late.into()).unwrap(),
                     );
+                    route.real_start_time_inferred = !is_origin_departure;
+                    update_route_real_times(&route, &pool).await?;
+                } else if route.real_start_time_inferred && is_origin_departure {
+                    route.real_start_time = Some(
+                        route.expected_start_time
+                            + chrono::Duration::try_minutes(minutes_late.into()).unwrap(),
+                    );
+                    route.real_start_time_inferred = false;
                     update_route_real_times(&route, &pool).await?;
                 }
 
-                update_current_stop_arrival(&mut route, &stations, &status, minutes_late, &pool)
-                    .await?;
+                update_current_stop_arrival(
+                    &mut route,
+                    &stations,
+                    &status,
+                    minutes_late,
+                    &pool,
+                    &baseline,
+                    &live_comparisons,
+                    &delay_updates,
+                )
+                .await?;
             }
             Status::FinishedDriving(datetime) => {
-                if datetime < Utc::now() - chrono::Duration::try_hours(12).unwrap() {
+                // Compared against when HZ's own page says it was last refreshed
+                // rather than our poll time, so a poll that lands right after a
+                // polling gap doesn't mistake a genuinely-just-finished train for
+                // stale leftover data (or vice versa, accept a stale page as fresh
+                // just because we happened to poll soon after fetching it).
+                if datetime < status.page_updated_at - chrono::Duration::try_hours(12).unwrap() {
                     continue;
                 }
 
-                update_current_stop_arrival(&mut route, &stations, &status, minutes_late, &pool)
-                    .await?;
+                update_current_stop_arrival(
+                    &mut route,
+                    &stations,
+                    &status,
+                    minutes_late,
+                    &pool,
+                    &baseline,
+                    &live_comparisons,
+                    &delay_updates,
+                )
+                .await?;
 
                 if train_has_started {
                     route.real_end_time = Some(
                         route.expected_end_time
                             + chrono::Duration::try_minutes(minutes_late.into()).unwrap(),
                     );
+                    route.final_delay_minutes = Some(minutes_late);
+                    route.narrative_summary = crate::narrative::generate(&route, &stations);
                     update_route_real_times(&route, &pool).await?;
+                    live_comparisons.clear(&route.id).await;
                     return Ok(());
                 }
             }
@@ -258,17 +552,176 @@ async fn check_delay_until_route_completion(
     }
 }
 
+#[derive(thiserror::Error, Debug)]
+pub enum RecheckError {
+    #[error("no unfinished run found for that route number")]
+    RouteNotFound,
+    #[error("upstream reported a Unisys error")]
+    UnisysError,
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
+}
+
+/// What a [`recheck_route_now`] poll found, for the admin endpoint to report back.
+pub enum RecheckOutcome {
+    /// The train isn't in HZPP's delay system at all (e.g. hasn't been formed yet).
+    TrainNotEvidented,
+    /// The train is evidented but HZPP hasn't published a delay figure for it yet.
+    NoDelayData,
+    /// A status was fetched and persisted.
+    Updated { minutes_late: i32 },
+}
+
+/// Performs a single immediate status poll for the most relevant unfinished
+/// run of `route_number` and persists it, instead of waiting for
+/// [`check_delay_until_route_completion`]'s next scheduled poll. Meant for an
+/// operator debugging the scraper against a train that's running right now,
+/// so unlike the regular loop it doesn't infer `real_start_time`/`real_end_time`
+/// from this one observation — it only records the stop arrival/departure and
+/// the overall delay, the same way the regular loop does on every poll.
+pub async fn recheck_route_now(
+    route_number: i32,
+    pool: &Pool<Postgres>,
+    live_comparisons: &LiveComparisons,
+    delay_response_cache: &DelayResponseCache,
+    delay_updates: &DelayUpdates,
+) -> Result<RecheckOutcome, RecheckError> {
+    let mut route: RouteDb = query_as(
+        "SELECT
+        id,
+        numeric_id,
+        slug,
+        route_number,
+        source,
+        destination,
+        bikes_allowed,
+        wheelchair_accessible,
+        route_type,
+        expected_start_time,
+        expected_end_time,
+        real_start_time,
+        real_start_time_inferred,
+        real_end_time,
+        max_delay_minutes,
+        final_delay_minutes,
+        schedule_only,
+        narrative_summary
+        from routes
+        where route_number = $1 and (real_end_time IS NULL or real_start_time IS NULL) and schedule_only = false
+        order by expected_start_time desc
+        limit 1",
+    )
+    .bind(route_number)
+    .fetch_optional(pool)
+    .await
+    .map_err(anyhow::Error::from)?
+    .ok_or(RecheckError::RouteNotFound)?;
+
+    route.stops = query_as("SELECT * from stops where route_id = $1 and route_expected_start_time = $2")
+        .bind(&route.id)
+        .bind(route.expected_start_time)
+        .fetch_all(pool)
+        .await
+        .map_err(anyhow::Error::from)?;
+
+    let stations: HashMap<String, StationDb, RandomState> = HashMap::from_iter(
+        get_stations(pool.clone())
+            .await?
+            .into_iter()
+            .map(|s| (s.id.clone(), s)),
+    );
+
+    let baseline = get_baseline_delays_by_sequence(pool, route.route_number)
+        .await
+        .unwrap_or_else(|e| {
+            error!("error loading historical baseline for route {}: {e:?}", route.route_number);
+            HashMap::new()
+        });
+
+    let status: TrainStatus = match delay_response_cache.get_or_fetch(&route).await? {
+        StatusResponse::TrainStatus(ts) => ts,
+        StatusResponse::TrainNotEvidented => return Ok(RecheckOutcome::TrainNotEvidented),
+        StatusResponse::UnisysError => return Err(RecheckError::UnisysError),
+    };
+
+    let Some(minutes_late) = status.get_minutes_late() else {
+        return Ok(RecheckOutcome::NoDelayData);
+    };
+
+    if minutes_late > route.max_delay_minutes.unwrap_or(0) {
+        route.max_delay_minutes = Some(minutes_late);
+        update_route_real_times(&route, pool).await?;
+
+        delay_updates.send(DelayUpdate {
+            route_id: route.id.clone(),
+            route_number: route.route_number,
+            sequence: None,
+            station_id: None,
+            event: "delay",
+            minutes_late,
+            updated_at: Utc::now(),
+            upstream_updated_at: status.page_updated_at,
+        });
+    }
+
+    match status.status {
+        Status::Formed(_) => {}
+        Status::DepartingFromStation(_) => {
+            update_current_stop_departure(
+                &mut route, &stations, &status, minutes_late, pool, &baseline, live_comparisons, delay_updates,
+            )
+            .await?;
+        }
+        Status::Arriving(_) | Status::FinishedDriving(_) => {
+            update_current_stop_arrival(
+                &mut route, &stations, &status, minutes_late, pool, &baseline, live_comparisons, delay_updates,
+            )
+            .await?;
+        }
+    }
+
+    Ok(RecheckOutcome::Updated { minutes_late })
+}
+
+/// Median observed delay at each stop sequence, computed over past runs of
+/// the same numbered service. Empty for a route number with no completed
+/// history yet.
+async fn get_baseline_delays_by_sequence(
+    pool: &Pool<Postgres>,
+    route_number: i32,
+) -> Result<HashMap<i16, f64>, anyhow::Error> {
+    let rows: Vec<(i16, f64)> = query_as(
+        "SELECT s.sequence,
+                percentile_cont(0.5) WITHIN GROUP (
+                    ORDER BY extract(epoch from (s.real_arrival - s.expected_arrival)) / 60
+                ) as median_minutes_late
+         FROM stops s
+         JOIN routes r ON r.id = s.route_id AND r.expected_start_time = s.route_expected_start_time
+         WHERE r.route_number = $1 AND s.real_arrival IS NOT NULL
+         GROUP BY s.sequence",
+    )
+    .bind(route_number)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.into_iter().collect())
+}
+
 /// How many minutes the `minutes late` and `status time` are allowed to differ from one another.
 ///
 /// If the data is correct these two should always be the same. but we don't know if the data is correct...
 const ALLOWED_TIME_DIFF: i64 = 10;
 
+#[allow(clippy::too_many_arguments)]
 async fn update_current_stop_arrival(
     route: &mut RouteDb,
     stations: &HashMap<String, StationDb>,
     status: &TrainStatus,
     minutes_late: i32,
     pool: &Pool<Postgres>,
+    baseline: &HashMap<i16, f64>,
+    live_comparisons: &LiveComparisons,
+    delay_updates: &DelayUpdates,
 ) -> Result<(), anyhow::Error> {
     let current_stop = get_current_stop(&mut route.stops, &stations, &status);
     info!(current_stop = ?current_stop);
@@ -291,6 +744,13 @@ async fn update_current_stop_arrival(
         bail!("times don't add up");
     }
 
+    if status.platform.is_some() {
+        current_stop.platform = status.platform.clone();
+    }
+
+    let sequence = current_stop.sequence;
+    let station_id = current_stop.station_id.clone();
+
     update_stop_arrival(
         current_stop,
         route.expected_start_time,
@@ -299,15 +759,53 @@ async fn update_current_stop_arrival(
     )
     .await?;
 
+    if let Some(platform) = &current_stop.platform {
+        update_stop_platform(
+            current_stop,
+            platform,
+            route.expected_start_time,
+            &route.id,
+            pool.clone(),
+        )
+        .await?;
+    }
+
+    live_comparisons
+        .set(RouteComparison {
+            route_id: route.id.clone(),
+            route_number: route.route_number,
+            sequence,
+            minutes_late,
+            usual_minutes_late: baseline.get(&sequence).copied(),
+            updated_at: Utc::now(),
+            upstream_updated_at: status.page_updated_at,
+        })
+        .await;
+
+    delay_updates.send(DelayUpdate {
+        route_id: route.id.clone(),
+        route_number: route.route_number,
+        sequence: Some(sequence),
+        station_id: Some(station_id),
+        event: "arrival",
+        minutes_late,
+        updated_at: Utc::now(),
+        upstream_updated_at: status.page_updated_at,
+    });
+
     Ok(())
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn update_current_stop_departure(
     route: &mut RouteDb,
     stations: &HashMap<String, StationDb>,
     status: &TrainStatus,
     minutes_late: i32,
     pool: &Pool<Postgres>,
+    baseline: &HashMap<i16, f64>,
+    live_comparisons: &LiveComparisons,
+    delay_updates: &DelayUpdates,
 ) -> Result<(), anyhow::Error> {
     let current_stop = get_current_stop(&mut route.stops, &stations, &status);
     info!(current_stop = ?current_stop);
@@ -331,6 +829,13 @@ async fn update_current_stop_departure(
         bail!("times don't add up");
     }
 
+    if status.platform.is_some() {
+        current_stop.platform = status.platform.clone();
+    }
+
+    let sequence = current_stop.sequence;
+    let station_id = current_stop.station_id.clone();
+
     update_stop_departure(
         current_stop,
         route.expected_start_time,
@@ -339,6 +844,40 @@ async fn update_current_stop_departure(
     )
     .await?;
 
+    if let Some(platform) = &current_stop.platform {
+        update_stop_platform(
+            current_stop,
+            platform,
+            route.expected_start_time,
+            &route.id,
+            pool.clone(),
+        )
+        .await?;
+    }
+
+    live_comparisons
+        .set(RouteComparison {
+            route_id: route.id.clone(),
+            route_number: route.route_number,
+            sequence,
+            minutes_late,
+            usual_minutes_late: baseline.get(&sequence).copied(),
+            updated_at: Utc::now(),
+            upstream_updated_at: status.page_updated_at,
+        })
+        .await;
+
+    delay_updates.send(DelayUpdate {
+        route_id: route.id.clone(),
+        route_number: route.route_number,
+        sequence: Some(sequence),
+        station_id: Some(station_id),
+        event: "departure",
+        minutes_late,
+        updated_at: Utc::now(),
+        upstream_updated_at: status.page_updated_at,
+    });
+
     Ok(())
 }
 
@@ -377,6 +916,31 @@ fn get_current_stop<'a>(
     current_stop
 }
 
+/// Whether `status` is reporting on the run's first stop (by `sequence`),
+/// i.e. an actual observation of the origin departure rather than a delay
+/// backed out from some later stop.
+fn is_status_at_origin_stop(
+    route: &RouteDb,
+    stations: &HashMap<String, StationDb>,
+    status: &TrainStatus,
+) -> bool {
+    let Some(origin_stop) = route.stops.iter().min_by_key(|s| s.sequence) else {
+        return false;
+    };
+
+    let Some(origin_station) = stations.get(&origin_stop.station_id) else {
+        error!("Got unknown stop id");
+        return false;
+    };
+
+    is_delay_station_similar_to_stop_name(&status.station, &origin_station.name).unwrap_or_else(
+        |e| {
+            error!("{:?}", e);
+            false
+        },
+    )
+}
+
 async fn get_stations(pool: Pool<Postgres>) -> Result<Vec<StationDb>, anyhow::Error> {
     let res = query_as!(StationDb, "Select * from stations")
         .fetch_all(&pool)
@@ -457,6 +1021,74 @@ where route_expected_start_time = $2 and route_id = $3 and sequence = $4
     Ok(())
 }
 
+/// Persists a stop's observed platform/track. Kept as its own runtime query
+/// (rather than folded into [`update_stop_arrival`]/[`update_stop_departure`])
+/// since `platform` is newer than those two's compile-time checked queries
+/// and this sandbox has no way to refresh the offline query cache.
+#[tracing::instrument(skip(stop), err)]
+async fn update_stop_platform(
+    stop: &StopDb,
+    platform: &str,
+    route_expected_start_time: DateTime<Utc>,
+    route_id: &str,
+    pool: Pool<Postgres>,
+) -> Result<(), anyhow::Error> {
+    query(
+        "
+    UPDATE stops
+    SET platform = $1
+    where route_expected_start_time = $2 and route_id = $3 and sequence = $4
+    ",
+    )
+    .bind(platform)
+    .bind(route_expected_start_time)
+    .bind(route_id)
+    .bind(stop.sequence)
+    .execute(&pool)
+    .await?;
+
+    Ok(())
+}
+
+const DELAY_RESPONSE_CACHE_TTL: Duration = Duration::from_secs(20);
+
+/// Shares one upstream request across concurrent monitors of the same train
+/// number, so two overlapping runs of the same numbered service (or the same
+/// run accidentally monitored twice) don't double the hit rate against
+/// HZPP's delay endpoint.
+#[derive(Clone, Default, Debug)]
+pub(crate) struct DelayResponseCache {
+    entries: Arc<Mutex<HashMap<i32, (Instant, StatusResponse)>>>,
+}
+
+impl DelayResponseCache {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    async fn get_or_fetch(&self, route: &RouteDb) -> Result<StatusResponse, anyhow::Error> {
+        if let Some(response) = self.get_fresh(route.route_number).await {
+            return Ok(response);
+        }
+
+        let response = get_route_status(route).await?;
+
+        self.entries
+            .lock()
+            .await
+            .insert(route.route_number, (Instant::now(), response.clone()));
+
+        Ok(response)
+    }
+
+    async fn get_fresh(&self, route_number: i32) -> Option<StatusResponse> {
+        let entries = self.entries.lock().await;
+        let (fetched_at, response) = entries.get(&route_number)?;
+
+        (fetched_at.elapsed() < DELAY_RESPONSE_CACHE_TTL).then(|| response.clone())
+    }
+}
+
 #[tracing::instrument(ret, err)]
 async fn get_route_status(route: &RouteDb) -> Result<StatusResponse, anyhow::Error> {
     let url = format!(
@@ -483,98 +1115,11 @@ async fn get_route_status(route: &RouteDb) -> Result<StatusResponse, anyhow::Err
         .await
         .context("Error getting response text")?;
 
-    let delay_response = parse_delay_html(content)?;
+    let delay_response = parsers::parse_delay_html(&content)?;
 
     Ok(delay_response)
 }
 
-#[tracing::instrument(ret, err)]
-fn parse_delay_html(html: String) -> Result<StatusResponse, anyhow::Error> {
-    if html.contains("Unisys") {
-        return Ok(StatusResponse::UnisysError);
-    }
-
-    if html.contains("Vlak nije u evidenciji") {
-        return Ok(StatusResponse::TrainNotEvidented);
-    }
-
-    let lines = html.lines().collect_vec();
-
-    let station_line = *lines
-        .iter()
-        .filter(|l| l.contains("Kolodvor:"))
-        .collect_vec()
-        .first()
-        .ok_or_else(|| anyhow!("Couldn't locate station line"))?;
-
-    let station = str_between_str(station_line, "</I><strong>", "<br>")
-        .ok_or_else(|| anyhow!("Couldn't locate station"))?
-        .to_string()
-        .replace("+", " ");
-
-    let status_line = *lines
-        .iter()
-        .enumerate()
-        .filter(|l| {
-            l.1.contains("Završio")
-                || l.1.contains("Odlazak")
-                || l.1.contains("Formiran")
-                || l.1.contains("Dolazak")
-        })
-        .collect_vec()
-        .first()
-        .ok_or_else(|| anyhow!("Couldn't locate status line"))?;
-    let status_time_line = lines
-        .get(status_line.0 + 1)
-        .ok_or_else(|| anyhow!("couldn't locate status time line"))?;
-
-    let status_date = NaiveDate::parse_from_str(&status_time_line[..9], "%d.%m.%y.")
-        .context("Couldn't parse status_date")?;
-    let status_time = NaiveTime::parse_from_str(&status_time_line[12..17], "%H:%M")
-        .context("Couldn't parse status_time")?;
-    let status_datetime: DateTime<Utc> = status_date
-        .and_time(status_time)
-        .and_local_timezone(Zagreb)
-        .earliest()
-        .ok_or_else(|| anyhow!("invalid date"))?
-        .with_timezone(&Utc);
-
-    let status = match status_line {
-        ref sl if sl.1.contains("Završio") => Status::FinishedDriving(status_datetime),
-        ref sl if sl.1.contains("Odlazak") => Status::DepartingFromStation(status_datetime),
-        ref sl if sl.1.contains("Formiran") => Status::Formed(status_datetime),
-        ref sl if sl.1.contains("Dolazak") => Status::Arriving(status_datetime),
-        _ => return Err(anyhow!("Couldn't construct status"))?,
-    };
-
-    let delay = if html.contains("Kasni") {
-        let minutes_late: i32 = str_between_str(&html, "Kasni", "min.")
-            .ok_or_else(|| anyhow!("Couldn't find delay number"))?
-            .trim()
-            .parse()
-            .context("Couldn't parse delay number")?;
-        Delay::Late { minutes_late }
-    } else if html.contains("Vlak ceka polazak") {
-        Delay::WaitingToDepart
-    } else if html.contains("Vlak je redovit") {
-        Delay::OnTime
-    } else if lines
-        .get(20)
-        .ok_or_else(|| anyhow!("couldn't find delay line"))?
-        .contains("<BLINK>                                                  </BLINK>")
-    {
-        Delay::NoData
-    } else {
-        bail!("Unknown delay response");
-    };
-
-    Ok(StatusResponse::TrainStatus(TrainStatus {
-        delay,
-        station,
-        status,
-    }))
-}
-
 #[derive(Clone, Debug)]
 enum StatusResponse {
     TrainStatus(TrainStatus),
@@ -587,6 +1132,13 @@ struct TrainStatus {
     pub station: String,
     pub status: Status,
     pub delay: Delay,
+    /// When HŽ's own page says it last updated this train's status (the
+    /// "Stanje vlaka od ..." line), as opposed to `status`'s per-stop time or
+    /// when we actually fetched the page — lets a consumer tell a position
+    /// HŽ itself hasn't refreshed in a while from a genuinely live one.
+    pub page_updated_at: DateTime<Utc>,
+    /// Announced platform/track, when the delay page happened to include one.
+    pub platform: Option<String>,
 }
 
 #[derive(Copy, Clone, Debug)]
@@ -632,17 +1184,21 @@ async fn update_route_real_times(
     route: &RouteDb,
     pool: &Pool<Postgres>,
 ) -> Result<(), anyhow::Error> {
-    query!(
+    query(
         "
     UPDATE routes
-    SET real_start_time = $1, real_end_time=$2
-    where expected_start_time = $3 and id = $4
+    SET real_start_time = $1, real_start_time_inferred = $2, real_end_time = $3, max_delay_minutes = $4, final_delay_minutes = $5, narrative_summary = $6
+    where expected_start_time = $7 and id = $8
     ",
-        route.real_start_time,
-        route.real_end_time,
-        route.expected_start_time,
-        route.id
     )
+    .bind(route.real_start_time)
+    .bind(route.real_start_time_inferred)
+    .bind(route.real_end_time)
+    .bind(route.max_delay_minutes)
+    .bind(route.final_delay_minutes)
+    .bind(&route.narrative_summary)
+    .bind(route.expected_start_time)
+    .bind(&route.id)
     .execute(pool)
     .await?;
 
@@ -683,322 +1239,4 @@ mod tests {
         assert!(super::is_delay_station_similar_to_stop_name(str1, str2).unwrap());
     }
 
-    #[test]
-    fn test_parse_html() -> Result<(), anyhow::Error> {
-        let html = r##"<HTML>
-<HEAD>
-<TITLE>Trenutna pozicija vlaka</TITLE>
-<meta name="viewport" content="width=device-width, initial-scale=1.0" charset=windows-1250">
-</HEAD>
-<BODY BACKGROUND=Images/slika.jpg><TABLE align="CENTER"><TR>
-<TD><FONT COLOR="#333399"><FONT FACE=Verdana,Arial,Helvetica COLOR="#333399">
-<H3 ALIGN=center>HŽ Infrastruktura<BR>                                  </H3></FONT>
-</TR></TABLE>
-<HR>
-<FORM METHOD="GET" ACTION="http://10.215.0.117/hzinfo/Default.asp?">
-<P ALIGN=CENTER>
-<FONT SIZE=6 FACE=Arial,Helvetica COLOR="#333399">
-<TABLE ALIGN=CENETR WIDTH=110%>
-<TD BGCOLOR=#bbddff><I>Trenutna pozicija<br>vlak: </I>  8067 <br>
-Relacija:<br> SAVSKI-MAR>DUGO-SELO- </strong></TD><TR>
-<TD BGCOLOR=#bbddff><I>Kolodvor: </I><strong>DUGO+SELO<br> </TD><TR>
-<TD BGCOLOR=#bbddff><I>Završio vožnju      </I><cr>
-26.01.24. u 18:58 sati</TD><TR>
-<TD><FONT FACE=Arial,Helvetica COLOR=#ff00b0>
-Vlak je redovit                                   <BR>
-<FONT SIZE=4 FACE=Verdana,Arial,Helvetica COLOR="#333399">
- <BR>
-</TD><TR><TD>
-</TD></TABLE><HR><FONT SIZE=1 FACE=Arial,Helvetica COLOR=009FFF>
-Stanje vlaka od 26/01/24   u 23:33   <HR>
-<INPUT TYPE="HIDDEN" NAME="Category" VALUE="hzinfo">
-<INPUT TYPE="HIDDEN" NAME="Service" VALUE="tpvl">
-<INPUT TYPE="HIDDEN" NAME="SCREEN" VALUE="1">
-<INPUT TYPE="SUBMIT" VALUE="Povrat">
-</FORM>
-</BODY>
-</HTML>"##;
-
-        super::parse_delay_html(html.to_string())?;
-
-        Ok(())
-    }
-
-    #[test]
-    fn test_parse_html2() -> Result<(), anyhow::Error> {
-        let html2 = r##"<HTML>
-<HEAD>
-<TITLE>Trenutna pozicija vlaka</TITLE>
-<meta name="viewport" content="width=device-width, initial-scale=1.0" charset=windows-1250">
-</HEAD>
-<BODY BACKGROUND=Images/slika.jpg><TABLE align="CENTER"><TR>
-<TD><FONT COLOR="#333399"><FONT FACE=Verdana,Arial,Helvetica COLOR="#333399">
-<H3 ALIGN=center>HŽ Infrastruktura<BR>                                  </H3></FONT>
-</TR></TABLE>
-<HR>
-<FORM METHOD="GET" ACTION="http://10.215.0.117/hzinfo/Default.asp?">
-<P ALIGN=CENTER>
-<FONT SIZE=6 FACE=Arial,Helvetica COLOR="#333399">
-<TABLE ALIGN=CENETR WIDTH=110%>
-<TD BGCOLOR=#bbddff><I>Trenutna pozicija<br>vlak: </I>  2303 <br>
-Relacija:<br>  >  </strong></TD><TR>
-<TD BGCOLOR=#bbddff><I>Kolodvor: </I><strong>SV.+IVAN+ŽABNO<br> </TD><TR>
-<TD BGCOLOR=#bbddff><I>Odlazak  </I><cr>
-27.01.24. u 00:03 sati</TD><TR>
-<TD><FONT FACE=Arial,Helvetica COLOR=#ff00b0>
-Vlak je redovit                                   <BR>
-<FONT SIZE=4 FACE=Verdana,Arial,Helvetica COLOR="#333399">
-<predv><BR>
-</TD><TR><TD>
-</TD></TABLE><HR><FONT SIZE=1 FACE=Arial,Helvetica COLOR=009FFF>
-Stanje vlaka od 27/01/24   u 00:09   <HR>
-<INPUT TYPE="HIDDEN" NAME="Category" VALUE="hzinfo">
-<INPUT TYPE="HIDDEN" NAME="Service" VALUE="tpvl">
-<INPUT TYPE="HIDDEN" NAME="SCREEN" VALUE="1">
-<INPUT TYPE="SUBMIT" VALUE="Povrat">
-</FORM>
-</BODY>
-</HTML>
-"##;
-
-        super::parse_delay_html(html2.to_string())?;
-
-        Ok(())
-    }
-
-    #[test]
-    fn test_parse_html3() -> Result<(), anyhow::Error> {
-        let html3 = r##"<HTML>
-<HEAD>
-<TITLE>Trenutna pozicija vlaka</TITLE>
-<meta name="viewport" content="width=device-width, initial-scale=1.0" charset=windows-1250">
-</HEAD>
-<BODY BACKGROUND=Images/slika.jpg><TABLE align="CENTER"><TR>
-<TD><FONT COLOR="#333399"><FONT FACE=Verdana,Arial,Helvetica COLOR="#333399">
-<H3 ALIGN=center>HŽ Infrastruktura<BR>                                  </H3></FONT>
-</TR></TABLE>
-<HR>
-<FORM METHOD="GET" ACTION="http://10.215.0.117/hzinfo/Default.asp?">
-<P ALIGN=CENTER>
-<FONT SIZE=6 FACE=Arial,Helvetica COLOR="#333399">
-<TABLE ALIGN=CENETR WIDTH=110%>
-<TD BGCOLOR=#bbddff><I>Trenutna pozicija<br>vlak: </I>  2111 <br>
-Relacija:<br> ZAGREB-GLA>NOVSKA---- </strong></TD><TR>
-<TD BGCOLOR=#bbddff><I>Kolodvor: </I><strong>LIPOVLJANI<br> </TD><TR>
-<TD BGCOLOR=#bbddff><I>Odlazak  </I><cr>
-27.01.24. u 01:07 sati</TD><TR>
-<TD><FONT FACE=Arial,Helvetica COLOR=#FF000A>
-<BLINK>Kasni    6 min.                                   </BLINK><BR>
-<FONT SIZE=4 FACE=Verdana,Arial,Helvetica COLOR="#333399">
- <BR>
-</TD><TR><TD>
-</TD></TABLE><HR><FONT SIZE=1 FACE=Arial,Helvetica COLOR=009FFF>
-Stanje vlaka od 27/01/24   u 01:55   <HR>
-<INPUT TYPE="HIDDEN" NAME="Category" VALUE="hzinfo">
-<INPUT TYPE="HIDDEN" NAME="Service" VALUE="tpvl">
-<INPUT TYPE="HIDDEN" NAME="SCREEN" VALUE="1">
-<INPUT TYPE="SUBMIT" VALUE="Povrat">
-</FORM>
-</BODY>
-</HTML>
-"##;
-
-        super::parse_delay_html(html3.to_string())?;
-
-        Ok(())
-    }
-
-    #[test]
-    fn test_parse_html4() -> Result<(), anyhow::Error> {
-        let html4 = r##"<HTML>
-<HEAD>
-<meta name="viewport" content="width=device-width, initial-scale=1.0" charset=windows-1250">
-<TITLE>Trenutna pozicija putničkog vlaka</TITLE>
-</HEAD>
-<BODY BACKGROUND=Images/slika.jpg><TABLE align="CENTER"><TR>
-<TD><FONT COLOR="#333399"><FONT FACE=Verdana,Arial,Helvetica COLOR="#333399">
-<H5 ALIGN=center>HŽ Infrastruktura<BR>Trenutna pozicija putničkog vlaka</H5></FONT>
-</TR></TABLE>
-<HR>
-<FORM METHOD="GET" ACTION="http://10.215.0.117/hzinfo/Default.asp?"><P><FONT FACE=Arial,Helvetica COLOR="#333399" ALIGN=center  >
-<STRONG>Broj vlaka: </STRONG>
-<INPUT NAME="VL" TYPE="TEXT" SIZE="5" MAXLENGTH="5">
-<P>
-<P><STRONG>Vlak nije u evidenciji.                                     </STRONG></P>
-<INPUT TYPE="HIDDEN" NAME="Category" VALUE="hzinfo">
-<INPUT TYPE="HIDDEN" NAME="Service" VALUE="tpvl">
-<INPUT TYPE="HIDDEN" NAME="SCREEN" VALUE="2">
-<INPUT TYPE="SUBMIT" VALUE=" OK ">
-</FORM>
-<PRE><P>
-<STRONG><P>
-<STRONG><P>
-<STRONG><P></PRE>
-</BODY>
-</HTML>
-"##;
-
-        super::parse_delay_html(html4.to_string())?;
-
-        Ok(())
-    }
-
-    #[test]
-    fn test_parse_html5() -> Result<(), anyhow::Error> {
-        let html5 = r##"<HTML>
-<HEAD>
-<TITLE>Trenutna pozicija vlaka</TITLE>
-<meta name="viewport" content="width=device-width, initial-scale=1.0" charset=windows-1250">
-</HEAD>
-<BODY BACKGROUND=Images/slika.jpg><TABLE align="CENTER"><TR>
-<TD><FONT COLOR="#333399"><FONT FACE=Verdana,Arial,Helvetica COLOR="#333399">
-<H3 ALIGN=center>HŽ Infrastruktura<BR>                                  </H3></FONT>
-</TR></TABLE>
-<HR>
-<FORM METHOD="GET" ACTION="http://10.215.0.117/hzinfo/Default.asp?">
-<P ALIGN=CENTER>
-<FONT SIZE=6 FACE=Arial,Helvetica COLOR="#333399">
-<TABLE ALIGN=CENETR WIDTH=110%>
-<TD BGCOLOR=#bbddff><I>Trenutna pozicija<br>vlak: </I>  2023 <br>
-Relacija:<br> ZAGREB-GLA>VINKOVCI-- </strong></TD><TR>
-<TD BGCOLOR=#bbddff><I>Kolodvor: </I><strong>ZAGREB+GL.+KOL.<br> </TD><TR>
-<TD BGCOLOR=#bbddff><I>Formiran </I><cr>
-27.01.24. u 17:34 sati</TD><TR>
-<TD><FONT FACE=Arial,Helvetica COLOR=#FF000A>
-<BLINK>                                                  </BLINK><BR>
-<FONT SIZE=4 FACE=Verdana,Arial,Helvetica COLOR="#333399">
-Vlak ceka polazak                                 <BR>
-</TD><TR><TD>
-</TD></TABLE><HR><FONT SIZE=1 FACE=Arial,Helvetica COLOR=009FFF>
-Stanje vlaka od 27/01/24   u 18:54   <HR>
-<INPUT TYPE="HIDDEN" NAME="Category" VALUE="hzinfo">
-<INPUT TYPE="HIDDEN" NAME="Service" VALUE="tpvl">
-<INPUT TYPE="HIDDEN" NAME="SCREEN" VALUE="1">
-<INPUT TYPE="SUBMIT" VALUE="Povrat">
-</FORM>
-</BODY>
-</HTML>
-"##;
-
-        super::parse_delay_html(html5.to_string())?;
-
-        Ok(())
-    }
-
-    #[test]
-    fn test_parse_html6() -> Result<(), anyhow::Error> {
-        let html6 = r##"<!DOCTYPE HTML PUBLIC \"-//W3C//DTD HTML 4.01 Transitional//EN\"
-\"http://www.w3.org/TR/html4/loose.dtd\">
-<html><head><title>Unisys Internet Commerce Enabler Error Message</title></head>
-<body>
-<table width=\"100%\" border=0><tr><td rowspan=2>
-<img src=\"/CISystem/Images/Globe.gif\" width=147 height=55 alt=\"\"/>
-</td><td colspan=2 width=\"85%\">
-<font face=\"georgia, times-new-roman\" size=4 color=\"#0033FF\">
-<a href=\"http://www.unisys.com/sw/web/ice\">
-<img src=\"/CISystem/Images/ICEPower-Img.gif\" width=160 height=43 align=\"right\" border=0
- alt=\"Click here for information about Unisys Internet Commerce Enabler\"/></a>
-<b><i>Unisys Internet Commerce Enabler</i></b></font></td>
-</tr><tr><td colspan=2 bgcolor=\"#0033FF\" height=16 width=\"85%\">
-</td></tr></table>
-<br><br><font size=5><b>Error Description:</b></font>
-<hr>
-<font size=4 color=\"#FF0000\"><b>The maximum number of available Cool ICE sessions has been exceeded.  Please try again later.</b></font>
-<hr>
-<br><font size=5><b>Error Code:</b></font>
-<hr>
-<font size=4 color=\"#FF0000\"><b>800417D9</b></font>
-<hr><br><br><br>
-Please report this error to the Webmaster, or System Administrator
-<hr>
-</body></html>"##;
-
-        super::parse_delay_html(html6.to_string())?;
-
-        Ok(())
-    }
-
-    #[test]
-    fn test_parse_html7() -> Result<(), anyhow::Error> {
-        let html7 = r##"<HTML>
-<HEAD>
-<TITLE>Trenutna pozicija vlaka</TITLE>
-<meta name="viewport" content="width=device-width, initial-scale=1.0" charset=windows-1250">
-</HEAD>
-<BODY BACKGROUND=Images/slika.jpg><TABLE align="CENTER"><TR>
-<TD><FONT COLOR="#333399"><FONT FACE=Verdana,Arial,Helvetica COLOR="#333399">
-<H3 ALIGN=center>HŽ Infrastruktura<BR>                                  </H3></FONT>
-</TR></TABLE>
-<HR>
-<FORM METHOD="GET" ACTION="http://10.215.0.117/hzinfo/Default.asp?">
-<P ALIGN=CENTER>
-<FONT SIZE=6 FACE=Arial,Helvetica COLOR="#333399">
-<TABLE ALIGN=CENETR WIDTH=110%>
-<TD BGCOLOR=#bbddff><I>Trenutna pozicija<br>vlak: </I>  5121 <br>
-Relacija:<br> ZAGREB-GLA>SISAK-CAPR </strong></TD><TR>
-<TD BGCOLOR=#bbddff><I>Kolodvor: </I><strong>ZAGREB+GL.+KOL.<br> </TD><TR>
-<TD BGCOLOR=#bbddff><I>Formiran </I><cr>
-31.01.24. u 20:11 sati</TD><TR>
-<TD><FONT FACE=Arial,Helvetica COLOR=#FF000A>
-<BLINK>                                                  </BLINK><BR>
-<FONT SIZE=4 FACE=Verdana,Arial,Helvetica COLOR="#333399">
- <BR>
-</TD><TR><TD>
-</TD></TABLE><HR><FONT SIZE=1 FACE=Arial,Helvetica COLOR=009FFF>
-Stanje vlaka od 31/01/24   u 20:19   <HR>
-<INPUT TYPE="HIDDEN" NAME="Category" VALUE="hzinfo">
-<INPUT TYPE="HIDDEN" NAME="Service" VALUE="tpvl">
-<INPUT TYPE="HIDDEN" NAME="SCREEN" VALUE="1">
-<INPUT TYPE="SUBMIT" VALUE="Povrat">
-</FORM>
-</BODY>
-</HTML>
-"##;
-
-        super::parse_delay_html(html7.to_string())?;
-
-        Ok(())
-    }
-
-    #[test]
-    fn test_parse_html8() -> Result<(), anyhow::Error> {
-        let html8 = r##"<HTML>
-<HEAD>
-<TITLE>Trenutna pozicija vlaka</TITLE>
-<meta name=\"viewport\" content=\"width=device-width, initial-scale=1.0\" charset=windows-1250\">
-</HEAD>
-<BODY BACKGROUND=Images/slika.jpg><TABLE align=\"CENTER\"><TR>
-<TD><FONT COLOR=\"#333399\"><FONT FACE=Verdana,Arial,Helvetica COLOR=\"#333399\">
-<H3 ALIGN=center>HŽ Infrastruktura<BR>                                  </H3></FONT>
-</TR></TABLE>
-<HR>
-<FORM METHOD=\"GET\" ACTION=\"http://10.215.0.117/hzinfo/Default.asp?\">
-<P ALIGN=CENTER>
-<FONT SIZE=6 FACE=Arial,Helvetica COLOR=\"#333399\">
-<TABLE ALIGN=CENETR WIDTH=110%>
-<TD BGCOLOR=#bbddff><I>Trenutna pozicija<br>vlak: </I>  3136 <br>
-Relacija:<br> ZABOK----->DJURMANEC- </strong></TD><TR>
-<TD BGCOLOR=#bbddff><I>Kolodvor: </I><strong>KRAPINA<br> </TD><TR>
-<TD BGCOLOR=#bbddff><I>Formiran </I><cr>
-02.02.24. u 18:09 sati</TD><TR>
-<TD><FONT FACE=Arial,Helvetica COLOR=#FF000A>
-<BLINK>                                                  </BLINK><BR>
-<FONT SIZE=4 FACE=Verdana,Arial,Helvetica COLOR=\"#333399\">
- <BR>
-</TD><TR><TD>
-</TD></TABLE><HR><FONT SIZE=1 FACE=Arial,Helvetica COLOR=009FFF>
-Stanje vlaka od 02/02/24   u 18:29   <HR>
-<INPUT TYPE=\"HIDDEN\" NAME=\"Category\" VALUE=\"hzinfo\">
-<INPUT TYPE=\"HIDDEN\" NAME=\"Service\" VALUE=\"tpvl\">
-<INPUT TYPE=\"HIDDEN\" NAME=\"SCREEN\" VALUE=\"1\">
-<INPUT TYPE=\"SUBMIT\" VALUE=\"Povrat\">
-</FORM>
-</BODY>
-</HTML>
-"##;
-
-        super::parse_delay_html(html8.to_string())?;
-
-        Ok(())
-    }
 }
@@ -0,0 +1,113 @@
+//! HŽ's delay endpoint serves more than one response variant behind the same
+//! URL: the usual position table, a bare "train not in system" page, and an
+//! occasional Unisys error frame with no run data at all. Rather than one
+//! function branching on all of them, each variant is recognised by a small
+//! fingerprint (a marker string in the response) and parsed by its own
+//! submodule with its own fixtures, so a newly-observed variant can be added
+//! without touching the others.
+mod standard;
+
+use super::StatusResponse;
+
+type Fingerprint = fn(&str) -> bool;
+type Parse = fn(&str) -> Result<StatusResponse, anyhow::Error>;
+
+/// Tried in order; the first fingerprint that matches wins. `standard` is
+/// last since its fingerprint (the position-table markup) is the most
+/// expensive check and the most common case, so ordering barely matters in
+/// practice, but the short-circuit cases read more clearly first.
+const PARSERS: &[(&str, Fingerprint, Parse)] = &[
+    ("unisys_error", |html| html.contains("Unisys"), |_| Ok(StatusResponse::UnisysError)),
+    (
+        "train_not_evidented",
+        |html| html.contains("Vlak nije u evidenciji"),
+        |_| Ok(StatusResponse::TrainNotEvidented),
+    ),
+    ("standard", standard::fingerprint, standard::parse),
+];
+
+#[tracing::instrument(ret, err)]
+pub fn parse_delay_html(html: &str) -> Result<StatusResponse, anyhow::Error> {
+    for (name, fingerprint, parse) in PARSERS {
+        if fingerprint(html) {
+            return parse(html)
+                .map_err(|e| e.context(format!("parser '{name}' matched but failed to parse")));
+        }
+    }
+
+    Err(anyhow::anyhow!("no delay parser recognised this response"))
+}
+
+mod tests {
+    #[test]
+    fn test_parse_html4() -> Result<(), anyhow::Error> {
+        let html4 = r##"<HTML>
+<HEAD>
+<meta name="viewport" content="width=device-width, initial-scale=1.0" charset=windows-1250">
+<TITLE>Trenutna pozicija putničkog vlaka</TITLE>
+</HEAD>
+<BODY BACKGROUND=Images/slika.jpg><TABLE align="CENTER"><TR>
+<TD><FONT COLOR="#333399"><FONT FACE=Verdana,Arial,Helvetica COLOR="#333399">
+<H5 ALIGN=center>HŽ Infrastruktura<BR>Trenutna pozicija putničkog vlaka</H5></FONT>
+</TR></TABLE>
+<HR>
+<FORM METHOD="GET" ACTION="http://10.215.0.117/hzinfo/Default.asp?"><P><FONT FACE=Arial,Helvetica COLOR="#333399" ALIGN=center  >
+<STRONG>Broj vlaka: </STRONG>
+<INPUT NAME="VL" TYPE="TEXT" SIZE="5" MAXLENGTH="5">
+<P>
+<P><STRONG>Vlak nije u evidenciji.                                     </STRONG></P>
+<INPUT TYPE="HIDDEN" NAME="Category" VALUE="hzinfo">
+<INPUT TYPE="HIDDEN" NAME="Service" VALUE="tpvl">
+<INPUT TYPE="HIDDEN" NAME="SCREEN" VALUE="2">
+<INPUT TYPE="SUBMIT" VALUE=" OK ">
+</FORM>
+<PRE><P>
+<STRONG><P>
+<STRONG><P>
+<STRONG><P></PRE>
+</BODY>
+</HTML>
+"##;
+
+        let status = super::parse_delay_html(html4)?;
+
+        assert!(matches!(status, super::StatusResponse::TrainNotEvidented));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_html6() -> Result<(), anyhow::Error> {
+        let html6 = r##"<!DOCTYPE HTML PUBLIC \"-//W3C//DTD HTML 4.01 Transitional//EN\"
+\"http://www.w3.org/TR/html4/loose.dtd\">
+<html><head><title>Unisys Internet Commerce Enabler Error Message</title></head>
+<body>
+<table width=\"100%\" border=0><tr><td rowspan=2>
+<img src=\"/CISystem/Images/Globe.gif\" width=147 height=55 alt=\"\"/>
+</td><td colspan=2 width=\"85%\">
+<font face=\"georgia, times-new-roman\" size=4 color=\"#0033FF\">
+<a href=\"http://www.unisys.com/sw/web/ice\">
+<img src=\"/CISystem/Images/ICEPower-Img.gif\" width=160 height=43 align=\"right\" border=0
+ alt=\"Click here for information about Unisys Internet Commerce Enabler\"/></a>
+<b><i>Unisys Internet Commerce Enabler</i></b></font></td>
+</tr><tr><td colspan=2 bgcolor=\"#0033FF\" height=16 width=\"85%\">
+</td></tr></table>
+<br><br><font size=5><b>Error Description:</b></font>
+<hr>
+<font size=4 color=\"#FF0000\"><b>The maximum number of available Cool ICE sessions has been exceeded.  Please try again later.</b></font>
+<hr>
+<br><font size=5><b>Error Code:</b></font>
+<hr>
+<font size=4 color=\"#FF0000\"><b>800417D9</b></font>
+<hr><br><br><br>
+Please report this error to the Webmaster, or System Administrator
+<hr>
+</body></html>"##;
+
+        let status = super::parse_delay_html(html6)?;
+
+        assert!(matches!(status, super::StatusResponse::UnisysError));
+
+        Ok(())
+    }
+}
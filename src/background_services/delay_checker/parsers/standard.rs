@@ -0,0 +1,384 @@
+//! The ordinary `traindelay.hzpp.hr` position table: a fixed-format HTML page
+//! giving the train's current/last known station, its status line (formed,
+//! departing, arriving, finished), its delay in minutes, and (not on every
+//! page) the platform/track it's using.
+use anyhow::{anyhow, bail, Context};
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+use chrono_tz::Europe::Zagreb;
+use itertools::Itertools;
+
+use crate::utils::str_between_str;
+
+use super::super::{Delay, Status, StatusResponse, TrainStatus};
+
+pub(super) fn fingerprint(html: &str) -> bool {
+    html.contains("Trenutna pozicija vlaka")
+}
+
+pub(super) fn parse(html: &str) -> Result<StatusResponse, anyhow::Error> {
+    let lines = html.lines().collect_vec();
+
+    let station_line = *lines
+        .iter()
+        .filter(|l| l.contains("Kolodvor:"))
+        .collect_vec()
+        .first()
+        .ok_or_else(|| anyhow!("Couldn't locate station line"))?;
+
+    let station = str_between_str(station_line, "</I><strong>", "<br>")
+        .ok_or_else(|| anyhow!("Couldn't locate station"))?
+        .to_string()
+        .replace("+", " ");
+
+    let status_line = *lines
+        .iter()
+        .enumerate()
+        .filter(|l| {
+            l.1.contains("Završio")
+                || l.1.contains("Odlazak")
+                || l.1.contains("Formiran")
+                || l.1.contains("Dolazak")
+        })
+        .collect_vec()
+        .first()
+        .ok_or_else(|| anyhow!("Couldn't locate status line"))?;
+    let status_time_line = lines
+        .get(status_line.0 + 1)
+        .ok_or_else(|| anyhow!("couldn't locate status time line"))?;
+
+    let status_date = NaiveDate::parse_from_str(&status_time_line[..9], "%d.%m.%y.")
+        .context("Couldn't parse status_date")?;
+    let status_time = NaiveTime::parse_from_str(&status_time_line[12..17], "%H:%M")
+        .context("Couldn't parse status_time")?;
+    let status_datetime: DateTime<Utc> = status_date
+        .and_time(status_time)
+        .and_local_timezone(Zagreb)
+        .earliest()
+        .ok_or_else(|| anyhow!("invalid date"))?
+        .with_timezone(&Utc);
+
+    let status = match status_line {
+        ref sl if sl.1.contains("Završio") => Status::FinishedDriving(status_datetime),
+        ref sl if sl.1.contains("Odlazak") => Status::DepartingFromStation(status_datetime),
+        ref sl if sl.1.contains("Formiran") => Status::Formed(status_datetime),
+        ref sl if sl.1.contains("Dolazak") => Status::Arriving(status_datetime),
+        _ => return Err(anyhow!("Couldn't construct status"))?,
+    };
+
+    let delay = if html.contains("Kasni") {
+        let minutes_late: i32 = str_between_str(html, "Kasni", "min.")
+            .ok_or_else(|| anyhow!("Couldn't find delay number"))?
+            .trim()
+            .parse()
+            .context("Couldn't parse delay number")?;
+        Delay::Late { minutes_late }
+    } else if html.contains("Vlak ceka polazak") {
+        Delay::WaitingToDepart
+    } else if html.contains("Vlak je redovit") {
+        Delay::OnTime
+    } else if lines
+        .get(20)
+        .ok_or_else(|| anyhow!("couldn't find delay line"))?
+        .contains("<BLINK>                                                  </BLINK>")
+    {
+        Delay::NoData
+    } else {
+        bail!("Unknown delay response");
+    };
+
+    let platform = lines
+        .iter()
+        .filter(|l| l.contains("Kolosijek:"))
+        .collect_vec()
+        .first()
+        .and_then(|l| str_between_str(l, "</I><strong>", "<br>"))
+        .map(|p| p.trim().replace("+", " "));
+
+    let page_updated_line = *lines
+        .iter()
+        .filter(|l| l.contains("Stanje vlaka od"))
+        .collect_vec()
+        .first()
+        .ok_or_else(|| anyhow!("Couldn't locate page-updated line"))?;
+
+    let page_updated_date = NaiveDate::parse_from_str(
+        str_between_str(page_updated_line, "Stanje vlaka od", "u")
+            .ok_or_else(|| anyhow!("Couldn't locate page-updated date"))?
+            .trim(),
+        "%d/%m/%y",
+    )
+    .context("Couldn't parse page_updated_date")?;
+    let page_updated_time = NaiveTime::parse_from_str(
+        str_between_str(page_updated_line, "u", "<HR>")
+            .ok_or_else(|| anyhow!("Couldn't locate page-updated time"))?
+            .trim(),
+        "%H:%M",
+    )
+    .context("Couldn't parse page_updated_time")?;
+    let page_updated_at: DateTime<Utc> = page_updated_date
+        .and_time(page_updated_time)
+        .and_local_timezone(Zagreb)
+        .earliest()
+        .ok_or_else(|| anyhow!("invalid page-updated date"))?
+        .with_timezone(&Utc);
+
+    Ok(StatusResponse::TrainStatus(TrainStatus {
+        delay,
+        station,
+        status,
+        page_updated_at,
+        platform,
+    }))
+}
+
+mod tests {
+    #[test]
+    fn test_parse_html() -> Result<(), anyhow::Error> {
+        let html = r##"<HTML>
+<HEAD>
+<TITLE>Trenutna pozicija vlaka</TITLE>
+<meta name="viewport" content="width=device-width, initial-scale=1.0" charset=windows-1250">
+</HEAD>
+<BODY BACKGROUND=Images/slika.jpg><TABLE align="CENTER"><TR>
+<TD><FONT COLOR="#333399"><FONT FACE=Verdana,Arial,Helvetica COLOR="#333399">
+<H3 ALIGN=center>HŽ Infrastruktura<BR>                                  </H3></FONT>
+</TR></TABLE>
+<HR>
+<FORM METHOD="GET" ACTION="http://10.215.0.117/hzinfo/Default.asp?">
+<P ALIGN=CENTER>
+<FONT SIZE=6 FACE=Arial,Helvetica COLOR="#333399">
+<TABLE ALIGN=CENETR WIDTH=110%>
+<TD BGCOLOR=#bbddff><I>Trenutna pozicija<br>vlak: </I>  8067 <br>
+Relacija:<br> SAVSKI-MAR>DUGO-SELO- </strong></TD><TR>
+<TD BGCOLOR=#bbddff><I>Kolodvor: </I><strong>DUGO+SELO<br> </TD><TR>
+<TD BGCOLOR=#bbddff><I>Završio vožnju      </I><cr>
+26.01.24. u 18:58 sati</TD><TR>
+<TD><FONT FACE=Arial,Helvetica COLOR=#ff00b0>
+Vlak je redovit                                   <BR>
+<FONT SIZE=4 FACE=Verdana,Arial,Helvetica COLOR="#333399">
+ <BR>
+</TD><TR><TD>
+</TD></TABLE><HR><FONT SIZE=1 FACE=Arial,Helvetica COLOR=009FFF>
+Stanje vlaka od 26/01/24   u 23:33   <HR>
+<INPUT TYPE="HIDDEN" NAME="Category" VALUE="hzinfo">
+<INPUT TYPE="HIDDEN" NAME="Service" VALUE="tpvl">
+<INPUT TYPE="HIDDEN" NAME="SCREEN" VALUE="1">
+<INPUT TYPE="SUBMIT" VALUE="Povrat">
+</FORM>
+</BODY>
+</HTML>"##;
+
+        super::parse(html)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_html2() -> Result<(), anyhow::Error> {
+        let html2 = r##"<HTML>
+<HEAD>
+<TITLE>Trenutna pozicija vlaka</TITLE>
+<meta name="viewport" content="width=device-width, initial-scale=1.0" charset=windows-1250">
+</HEAD>
+<BODY BACKGROUND=Images/slika.jpg><TABLE align="CENTER"><TR>
+<TD><FONT COLOR="#333399"><FONT FACE=Verdana,Arial,Helvetica COLOR="#333399">
+<H3 ALIGN=center>HŽ Infrastruktura<BR>                                  </H3></FONT>
+</TR></TABLE>
+<HR>
+<FORM METHOD="GET" ACTION="http://10.215.0.117/hzinfo/Default.asp?">
+<P ALIGN=CENTER>
+<FONT SIZE=6 FACE=Arial,Helvetica COLOR="#333399">
+<TABLE ALIGN=CENETR WIDTH=110%>
+<TD BGCOLOR=#bbddff><I>Trenutna pozicija<br>vlak: </I>  2303 <br>
+Relacija:<br>  >  </strong></TD><TR>
+<TD BGCOLOR=#bbddff><I>Kolodvor: </I><strong>SV.+IVAN+ŽABNO<br> </TD><TR>
+<TD BGCOLOR=#bbddff><I>Odlazak  </I><cr>
+27.01.24. u 00:03 sati</TD><TR>
+<TD><FONT FACE=Arial,Helvetica COLOR=#ff00b0>
+Vlak je redovit                                   <BR>
+<FONT SIZE=4 FACE=Verdana,Arial,Helvetica COLOR="#333399">
+ <BR>
+</TD><TR><TD>
+</TD></TABLE><HR><FONT SIZE=1 FACE=Arial,Helvetica COLOR=009FFF>
+Stanje vlaka od 27/01/24   u 00:05   <HR>
+<INPUT TYPE="HIDDEN" NAME="Category" VALUE="hzinfo">
+<INPUT TYPE="HIDDEN" NAME="Service" VALUE="tpvl">
+<INPUT TYPE="HIDDEN" NAME="SCREEN" VALUE="1">
+<INPUT TYPE="SUBMIT" VALUE="Povrat">
+</FORM>
+</BODY>
+</HTML>"##;
+
+        super::parse(html2)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_html3() -> Result<(), anyhow::Error> {
+        let html3 = r##"<HTML>
+<HEAD>
+<TITLE>Trenutna pozicija vlaka</TITLE>
+<meta name="viewport" content="width=device-width, initial-scale=1.0" charset=windows-1250">
+</HEAD>
+<BODY BACKGROUND=Images/slika.jpg><TABLE align="CENTER"><TR>
+<TD><FONT COLOR="#333399"><FONT FACE=Verdana,Arial,Helvetica COLOR="#333399">
+<H3 ALIGN=center>HŽ Infrastruktura<BR>                                  </H3></FONT>
+</TR></TABLE>
+<HR>
+<FORM METHOD="GET" ACTION="http://10.215.0.117/hzinfo/Default.asp?">
+<P ALIGN=CENTER>
+<FONT SIZE=6 FACE=Arial,Helvetica COLOR="#333399">
+<TABLE ALIGN=CENETR WIDTH=110%>
+<TD BGCOLOR=#bbddff><I>Trenutna pozicija<br>vlak: </I>  2111 <br>
+Relacija:<br> ZAGREB-GLA>NOVSKA---- </strong></TD><TR>
+<TD BGCOLOR=#bbddff><I>Kolodvor: </I><strong>LIPOVLJANI<br> </TD><TR>
+<TD BGCOLOR=#bbddff><I>Odlazak  </I><cr>
+27.01.24. u 01:07 sati</TD><TR>
+<TD><FONT FACE=Arial,Helvetica COLOR=#FF000A>
+<BLINK>Kasni    6 min.                                   </BLINK><BR>
+<FONT SIZE=4 FACE=Verdana,Arial,Helvetica COLOR="#333399">
+ <BR>
+</TD><TR><TD>
+</TD></TABLE><HR><FONT SIZE=1 FACE=Arial,Helvetica COLOR=009FFF>
+Stanje vlaka od 27/01/24   u 01:55   <HR>
+<INPUT TYPE="HIDDEN" NAME="Category" VALUE="hzinfo">
+<INPUT TYPE="HIDDEN" NAME="Service" VALUE="tpvl">
+<INPUT TYPE="HIDDEN" NAME="SCREEN" VALUE="1">
+<INPUT TYPE="SUBMIT" VALUE="Povrat">
+</FORM>
+</BODY>
+</HTML>
+"##;
+
+        super::parse(html3)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_html5() -> Result<(), anyhow::Error> {
+        let html5 = r##"<HTML>
+<HEAD>
+<TITLE>Trenutna pozicija vlaka</TITLE>
+<meta name="viewport" content="width=device-width, initial-scale=1.0" charset=windows-1250">
+</HEAD>
+<BODY BACKGROUND=Images/slika.jpg><TABLE align="CENTER"><TR>
+<TD><FONT COLOR="#333399"><FONT FACE=Verdana,Arial,Helvetica COLOR="#333399">
+<H3 ALIGN=center>HŽ Infrastruktura<BR>                                  </H3></FONT>
+</TR></TABLE>
+<HR>
+<FORM METHOD="GET" ACTION="http://10.215.0.117/hzinfo/Default.asp?">
+<P ALIGN=CENTER>
+<FONT SIZE=6 FACE=Arial,Helvetica COLOR="#333399">
+<TABLE ALIGN=CENETR WIDTH=110%>
+<TD BGCOLOR=#bbddff><I>Trenutna pozicija<br>vlak: </I>  2023 <br>
+Relacija:<br> ZAGREB-GLA>VINKOVCI-- </strong></TD><TR>
+<TD BGCOLOR=#bbddff><I>Kolodvor: </I><strong>ZAGREB+GL.+KOL.<br> </TD><TR>
+<TD BGCOLOR=#bbddff><I>Formiran </I><cr>
+27.01.24. u 17:34 sati</TD><TR>
+<TD><FONT FACE=Arial,Helvetica COLOR=#FF000A>
+<BLINK>                                                  </BLINK><BR>
+<FONT SIZE=4 FACE=Verdana,Arial,Helvetica COLOR="#333399">
+Vlak ceka polazak                                 <BR>
+</TD><TR><TD>
+</TD></TABLE><HR><FONT SIZE=1 FACE=Arial,Helvetica COLOR=009FFF>
+Stanje vlaka od 27/01/24   u 18:54   <HR>
+<INPUT TYPE="HIDDEN" NAME="Category" VALUE="hzinfo">
+<INPUT TYPE="HIDDEN" NAME="Service" VALUE="tpvl">
+<INPUT TYPE="HIDDEN" NAME="SCREEN" VALUE="1">
+<INPUT TYPE="SUBMIT" VALUE="Povrat">
+</FORM>
+</BODY>
+</HTML>
+"##;
+
+        super::parse(html5)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_html7() -> Result<(), anyhow::Error> {
+        let html7 = r##"<HTML>
+<HEAD>
+<TITLE>Trenutna pozicija vlaka</TITLE>
+<meta name="viewport" content="width=device-width, initial-scale=1.0" charset=windows-1250">
+</HEAD>
+<BODY BACKGROUND=Images/slika.jpg><TABLE align="CENTER"><TR>
+<TD><FONT COLOR="#333399"><FONT FACE=Verdana,Arial,Helvetica COLOR="#333399">
+<H3 ALIGN=center>HŽ Infrastruktura<BR>                                  </H3></FONT>
+</TR></TABLE>
+<HR>
+<FORM METHOD="GET" ACTION="http://10.215.0.117/hzinfo/Default.asp?">
+<P ALIGN=CENTER>
+<FONT SIZE=6 FACE=Arial,Helvetica COLOR="#333399">
+<TABLE ALIGN=CENETR WIDTH=110%>
+<TD BGCOLOR=#bbddff><I>Trenutna pozicija<br>vlak: </I>  5121 <br>
+Relacija:<br> ZAGREB-GLA>SISAK-CAPR </strong></TD><TR>
+<TD BGCOLOR=#bbddff><I>Kolodvor: </I><strong>ZAGREB+GL.+KOL.<br> </TD><TR>
+<TD BGCOLOR=#bbddff><I>Formiran </I><cr>
+31.01.24. u 20:11 sati</TD><TR>
+<TD><FONT FACE=Arial,Helvetica COLOR=#FF000A>
+<BLINK>                                                  </BLINK><BR>
+<FONT SIZE=4 FACE=Verdana,Arial,Helvetica COLOR="#333399">
+ <BR>
+</TD><TR><TD>
+</TD></TABLE><HR><FONT SIZE=1 FACE=Arial,Helvetica COLOR=009FFF>
+Stanje vlaka od 31/01/24   u 20:19   <HR>
+<INPUT TYPE="HIDDEN" NAME="Category" VALUE="hzinfo">
+<INPUT TYPE="HIDDEN" NAME="Service" VALUE="tpvl">
+<INPUT TYPE="HIDDEN" NAME="SCREEN" VALUE="1">
+<INPUT TYPE="SUBMIT" VALUE="Povrat">
+</FORM>
+</BODY>
+</HTML>
+"##;
+
+        super::parse(html7)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_html8() -> Result<(), anyhow::Error> {
+        let html8 = r##"<HTML>
+<HEAD>
+<TITLE>Trenutna pozicija vlaka</TITLE>
+<meta name=\"viewport\" content=\"width=device-width, initial-scale=1.0\" charset=windows-1250\">
+</HEAD>
+<BODY BACKGROUND=Images/slika.jpg><TABLE align=\"CENTER\"><TR>
+<TD><FONT COLOR=\"#333399\"><FONT FACE=Verdana,Arial,Helvetica COLOR=\"#333399\">
+<H3 ALIGN=center>HŽ Infrastruktura<BR>                                  </H3></FONT>
+</TR></TABLE>
+<HR>
+<FORM METHOD=\"GET\" ACTION=\"http://10.215.0.117/hzinfo/Default.asp?\">
+<P ALIGN=CENTER>
+<FONT SIZE=6 FACE=Arial,Helvetica COLOR=\"#333399\">
+<TABLE ALIGN=CENETR WIDTH=110%>
+<TD BGCOLOR=#bbddff><I>Trenutna pozicija<br>vlak: </I>  3136 <br>
+Relacija:<br> ZABOK----->DJURMANEC- </strong></TD><TR>
+<TD BGCOLOR=#bbddff><I>Kolodvor: </I><strong>KRAPINA<br> </TD><TR>
+<TD BGCOLOR=#bbddff><I>Formiran </I><cr>
+02.02.24. u 18:09 sati</TD><TR>
+<TD><FONT FACE=Arial,Helvetica COLOR=#FF000A>
+<BLINK>                                                  </BLINK><BR>
+<FONT SIZE=4 FACE=Verdana,Arial,Helvetica COLOR=\"#333399\">
+ <BR>
+</TD><TR><TD>
+</TD></TABLE><HR><FONT SIZE=1 FACE=Arial,Helvetica COLOR=009FFF>
+Stanje vlaka od 02/02/24   u 18:29   <HR>
+<INPUT TYPE=\"HIDDEN\" NAME=\"Category\" VALUE=\"hzinfo\">
+<INPUT TYPE=\"HIDDEN\" NAME=\"Service\" VALUE=\"tpvl\">
+<INPUT TYPE=\"HIDDEN\" NAME=\"SCREEN\" VALUE=\"1\">
+<INPUT TYPE=\"SUBMIT\" VALUE=\"Povrat\">
+</FORM>
+</BODY>
+</HTML>
+"##;
+
+        super::parse(html8)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,89 @@
+//! Groups [`DelayUpdate`]s into windowed digests instead of forwarding each
+//! one individually, so a network-wide disruption doesn't flood whoever's
+//! watching with a storm of per-train alerts. This repo has no outbound
+//! notification channel (Slack, email, ...) yet, so a digest's "channel" is
+//! just a named log line for now; wiring one of those up can subscribe here
+//! the same way the delay checker itself does.
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::time::interval;
+use tracing::info;
+
+use super::delay_broadcast::{DelayUpdate, DelayUpdates};
+
+/// One channel's digesting window. Each channel watches the same broadcast
+/// feed independently, so a channel that wants immediate per-event alerts
+/// can use a short window while a noisier one batches longer.
+pub struct DigestChannel {
+    pub name: &'static str,
+    pub window: Duration,
+}
+
+pub struct Digest {
+    pub channel: &'static str,
+    pub window_start: DateTime<Utc>,
+    pub window_end: DateTime<Utc>,
+    pub event_count: usize,
+    pub worst_offender: Option<DelayUpdate>,
+}
+
+/// Buffers events for `channel.window`, then emits one [`Digest`] summarizing
+/// everything seen — even if it's a single event, since the point isn't to
+/// require a minimum batch size but to cap every channel to at most one
+/// message per window.
+pub async fn run_digest(channel: DigestChannel, delay_updates: DelayUpdates) {
+    let mut rx = delay_updates.subscribe();
+    let mut ticker = interval(channel.window);
+    let mut buffered: Vec<DelayUpdate> = Vec::new();
+    let mut window_start = Utc::now();
+
+    loop {
+        tokio::select! {
+            update = rx.recv() => {
+                match update {
+                    Ok(update) => buffered.push(update),
+                    Err(RecvError::Lagged(_)) => continue,
+                    Err(RecvError::Closed) => break,
+                }
+            }
+            _ = ticker.tick() => {
+                let window_end = Utc::now();
+
+                if !buffered.is_empty() {
+                    let digest = summarize(channel.name, window_start, window_end, &buffered);
+                    info!(
+                        channel = digest.channel,
+                        window_start = %digest.window_start,
+                        window_end = %digest.window_end,
+                        event_count = digest.event_count,
+                        worst_offender_route = ?digest.worst_offender.as_ref().map(|w| w.route_number),
+                        worst_offender_minutes_late = ?digest.worst_offender.as_ref().map(|w| w.minutes_late),
+                        "delay digest"
+                    );
+                    buffered.clear();
+                }
+
+                window_start = window_end;
+            }
+        }
+    }
+}
+
+fn summarize(
+    channel: &'static str,
+    window_start: DateTime<Utc>,
+    window_end: DateTime<Utc>,
+    events: &[DelayUpdate],
+) -> Digest {
+    let worst_offender = events.iter().max_by_key(|e| e.minutes_late).cloned();
+
+    Digest {
+        channel,
+        window_start,
+        window_end,
+        event_count: events.len(),
+        worst_offender,
+    }
+}
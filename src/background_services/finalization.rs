@@ -0,0 +1,117 @@
+//! Nightly end-of-day finalization: once a UTC day has fully passed, every
+//! run that started that day is assigned a `final_status` so it stops
+//! depending on whatever the delay checker happened to observe last. Unlike
+//! [`super::data_integrity`], which only reports, this writes — but only
+//! once per day, gated by `finalized_days`, so a run's status never flips
+//! back and forth as the service restarts.
+use std::time::Duration;
+
+use chrono::{NaiveDate, Utc};
+use sqlx::{query, query_scalar, Pool, Postgres};
+use tokio::time::sleep;
+use tracing::{error, info};
+
+/// How often to check whether yesterday still needs finalizing. Short
+/// enough to catch up quickly after a restart near midnight UTC, without
+/// being expensive — the check is a single indexed lookup once the day is
+/// already finalized.
+const CHECK_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Runs forever, finalizing the previous UTC day once it's fully elapsed.
+pub async fn run_periodically(pool: Pool<Postgres>) {
+    loop {
+        if let Err(e) = finalize_if_due(&pool).await {
+            error!("error running end-of-day finalization: {e:?}");
+        }
+
+        sleep(CHECK_INTERVAL).await;
+    }
+}
+
+async fn finalize_if_due(pool: &Pool<Postgres>) -> Result<(), anyhow::Error> {
+    let yesterday = Utc::now().date_naive() - chrono::Duration::days(1);
+
+    if is_finalized(pool, yesterday).await? {
+        return Ok(());
+    }
+
+    let route_count = finalize_day(pool, yesterday).await?;
+    info!(%yesterday, route_count, "finalized runs for the day");
+
+    Ok(())
+}
+
+/// Whether `date` has already gone through [`finalize_day`]. Once true, its
+/// runs' `final_status` won't change again, so callers (caching, export) can
+/// treat anything computed over that day as immutable.
+pub async fn is_finalized(pool: &Pool<Postgres>, date: NaiveDate) -> Result<bool, sqlx::Error> {
+    query_scalar("SELECT EXISTS(SELECT 1 FROM finalized_days WHERE date = $1)")
+        .bind(date)
+        .fetch_one(pool)
+        .await
+}
+
+/// Assigns a `final_status` to every still-open run whose `expected_start_time`
+/// falls on `date`, then marks the day finalized. Idempotent: a run that
+/// already has a `final_status` (e.g. re-run after a partial failure) is
+/// left alone, and re-finalizing an already-finalized date is a no-op.
+pub async fn finalize_day(pool: &Pool<Postgres>, date: NaiveDate) -> Result<i64, anyhow::Error> {
+    let mut tx = pool.begin().await?;
+
+    // Already closed out normally by the delay checker.
+    query(
+        "UPDATE routes SET final_status = 'completed'
+         WHERE expected_start_time::date = $1 AND final_status IS NULL AND real_end_time IS NOT NULL",
+    )
+    .bind(date)
+    .execute(&mut *tx)
+    .await?;
+
+    // Never seen running at all: no real start, and not one stop was
+    // actually observed arriving or departing.
+    query(
+        "UPDATE routes r SET final_status = 'cancelled'
+         WHERE r.expected_start_time::date = $1 AND r.final_status IS NULL AND r.real_start_time IS NULL
+               AND NOT EXISTS (
+                   SELECT 1 FROM stops s
+                   WHERE s.route_id = r.id AND s.route_expected_start_time = r.expected_start_time
+                         AND (s.real_arrival IS NOT NULL OR s.real_departure IS NOT NULL)
+               )",
+    )
+    .bind(date)
+    .execute(&mut *tx)
+    .await?;
+
+    // Observed running for at least part of the journey, but the checker
+    // never saw it reach its last stop before the day closed out.
+    query(
+        "UPDATE routes SET final_status = 'partial'
+         WHERE expected_start_time::date = $1 AND final_status IS NULL AND real_start_time IS NOT NULL",
+    )
+    .bind(date)
+    .execute(&mut *tx)
+    .await?;
+
+    // Catch-all for whatever doesn't fit the above (e.g. real_start_time
+    // unset but a stop has a real timestamp anyway) rather than leaving it
+    // unfinalized forever.
+    query("UPDATE routes SET final_status = 'unknown' WHERE expected_start_time::date = $1 AND final_status IS NULL")
+        .bind(date)
+        .execute(&mut *tx)
+        .await?;
+
+    let route_count: i64 = query_scalar("SELECT count(*) FROM routes WHERE expected_start_time::date = $1")
+        .bind(date)
+        .fetch_one(&mut *tx)
+        .await?;
+
+    query("INSERT INTO finalized_days (date, route_count) VALUES ($1, $2) ON CONFLICT (date) DO NOTHING")
+        .bind(date)
+        .bind(route_count)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+
+    Ok(route_count)
+}
@@ -0,0 +1,72 @@
+//! Pushes each [`DelayUpdate`] to an Influx-line-protocol-compatible HTTP
+//! endpoint (InfluxDB 1.x/2.x, VictoriaMetrics both accept `/write`), so
+//! existing TSDB/Grafana users can graph delays alongside their other
+//! metrics instead of only through this app's own API. Disabled unless
+//! `INFLUX_WRITE_URL` is configured.
+use reqwest::Client;
+use tokio::sync::broadcast::error::RecvError;
+use tracing::warn;
+
+use super::delay_broadcast::{DelayUpdate, DelayUpdates};
+
+#[derive(Clone)]
+pub struct InfluxExporterConfig {
+    /// Full write endpoint, e.g. `http://localhost:8086/write?db=hzpp`.
+    pub write_url: String,
+    /// Sent as `Authorization: Token <token>` when set, matching InfluxDB's
+    /// own scheme (also accepted by VictoriaMetrics).
+    pub token: Option<String>,
+}
+
+/// Subscribes to `delay_updates` and pushes each one as a line-protocol point
+/// until the channel closes. A failed push is logged and dropped rather than
+/// retried, since losing one metrics point isn't worth blocking the next one.
+pub async fn run_influx_exporter(config: InfluxExporterConfig, delay_updates: DelayUpdates) {
+    let client = Client::new();
+    let mut rx = delay_updates.subscribe();
+
+    loop {
+        let update = match rx.recv().await {
+            Ok(update) => update,
+            Err(RecvError::Lagged(_)) => continue,
+            Err(RecvError::Closed) => break,
+        };
+
+        let mut request = client.post(&config.write_url).body(to_line_protocol(&update));
+
+        if let Some(token) = &config.token {
+            request = request.header("Authorization", format!("Token {token}"));
+        }
+
+        if let Err(e) = request.send().await.and_then(|r| r.error_for_status()) {
+            warn!("error pushing delay update to influx endpoint: {e}");
+        }
+    }
+}
+
+/// `measurement,tag=value,... field=value,... timestamp`, one point per
+/// `DelayUpdate`. `route_number` and `event` are tags (indexed, low
+/// cardinality); `station_id` is a tag too when present, since it's also
+/// bounded by the station list; `route_id`/`minutes_late` are fields.
+fn to_line_protocol(update: &DelayUpdate) -> String {
+    let mut tags = format!("route_number={},event={}", update.route_number, update.event);
+
+    if let Some(station_id) = &update.station_id {
+        tags.push_str(&format!(",station_id={}", escape_tag(station_id)));
+    }
+
+    format!(
+        "train_delay,{tags} minutes_late={}i,route_id=\"{}\" {}",
+        update.minutes_late,
+        escape_field_string(&update.route_id),
+        update.updated_at.timestamp_nanos_opt().unwrap_or_default()
+    )
+}
+
+fn escape_tag(value: &str) -> String {
+    value.replace('\\', "\\\\").replace(' ', "\\ ").replace(',', "\\,").replace('=', "\\=")
+}
+
+fn escape_field_string(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
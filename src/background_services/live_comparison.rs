@@ -0,0 +1,52 @@
+//! In-memory "currently X min late, usually Y" snapshots per active run, kept
+//! up to date by the delay checker as it observes each stop, so the live API
+//! can answer instantly instead of recomputing a historical baseline per request.
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tokio::sync::Mutex;
+
+#[derive(Clone, Debug, Serialize)]
+pub struct RouteComparison {
+    pub route_id: String,
+    pub route_number: i32,
+    pub sequence: i16,
+    pub minutes_late: i32,
+    /// Median delay observed at this stop across past runs of the same
+    /// numbered service, or `None` if there isn't enough history yet.
+    pub usual_minutes_late: Option<f64>,
+    /// Server clock at the moment this comparison was recorded.
+    pub updated_at: DateTime<Utc>,
+    /// When HŽ's own page says it last refreshed this train's status, as
+    /// opposed to `updated_at` (our fetch/record time) — lets a consumer
+    /// tell a position HŽ itself hasn't updated in a while from a live one.
+    pub upstream_updated_at: DateTime<Utc>,
+}
+
+#[derive(Clone, Default, Debug)]
+pub struct LiveComparisons {
+    comparisons: Arc<Mutex<HashMap<String, RouteComparison>>>,
+}
+
+impl LiveComparisons {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub async fn set(&self, comparison: RouteComparison) {
+        self.comparisons
+            .lock()
+            .await
+            .insert(comparison.route_id.clone(), comparison);
+    }
+
+    pub async fn clear(&self, route_id: &str) {
+        self.comparisons.lock().await.remove(route_id);
+    }
+
+    pub async fn snapshot(&self) -> Vec<RouteComparison> {
+        self.comparisons.lock().await.values().cloned().collect()
+    }
+}
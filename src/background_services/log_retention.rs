@@ -0,0 +1,156 @@
+//! Keeps `LOGS_DIR` from growing forever: gzips each rotated log file once
+//! `tracing-appender` has moved on to a new one, then deletes anything older
+//! than the configured retention window or, if that still leaves too much on
+//! disk, the oldest survivors until it fits under the size budget.
+use std::path::{Path, PathBuf};
+use std::time::Duration as StdDuration;
+
+use chrono::{DateTime, Utc};
+use crc::{Crc, CRC_32_ISO_HDLC};
+use tokio::time::sleep;
+use tracing::{error, info, warn};
+
+const CHECK_INTERVAL: StdDuration = StdDuration::from_secs(60 * 60);
+const CRC32: Crc<u32> = Crc::<u32>::new(&CRC_32_ISO_HDLC);
+
+#[derive(Debug, Clone, Copy)]
+pub struct LogRetentionConfig {
+    pub retention_days: i64,
+    /// Deletes the oldest surviving logs past the retention window too, if
+    /// that's still not enough to get under this. `None` skips the check.
+    pub max_total_bytes: Option<u64>,
+}
+
+/// Runs forever, re-sweeping `logs_dir` every [`CHECK_INTERVAL`].
+pub async fn run_periodically(logs_dir: String, config: LogRetentionConfig) {
+    loop {
+        if let Err(e) = sweep_once(Path::new(&logs_dir), config).await {
+            error!("error sweeping {logs_dir}: {e:?}");
+        }
+
+        sleep(CHECK_INTERVAL).await;
+    }
+}
+
+struct LogFile {
+    path: PathBuf,
+    modified: DateTime<Utc>,
+    size_bytes: u64,
+}
+
+async fn list_log_files(dir: &Path) -> std::io::Result<Vec<LogFile>> {
+    let mut files = vec![];
+    let mut entries = tokio::fs::read_dir(dir).await?;
+
+    while let Some(entry) = entries.next_entry().await? {
+        if !entry.file_type().await?.is_file() {
+            continue;
+        }
+
+        let metadata = entry.metadata().await?;
+        files.push(LogFile {
+            path: entry.path(),
+            modified: DateTime::<Utc>::from(metadata.modified()?),
+            size_bytes: metadata.len(),
+        });
+    }
+
+    files.sort_by_key(|f| f.modified);
+    Ok(files)
+}
+
+async fn sweep_once(dir: &Path, config: LogRetentionConfig) -> anyhow::Result<()> {
+    if !tokio::fs::try_exists(dir).await? {
+        return Ok(());
+    }
+
+    let mut files = list_log_files(dir).await?;
+
+    // The most recently modified file is almost certainly the one
+    // tracing-appender is still writing to; never touch it.
+    let active = files.pop();
+
+    compress_rotated_files(&mut files).await?;
+
+    let cutoff = Utc::now() - chrono::Duration::days(config.retention_days);
+    let mut kept = vec![];
+    for file in files {
+        if file.modified < cutoff {
+            delete_log_file(&file, "past retention window").await?;
+        } else {
+            kept.push(file);
+        }
+    }
+
+    if let Some(max_total_bytes) = config.max_total_bytes {
+        enforce_size_budget(&mut kept, max_total_bytes).await?;
+    }
+
+    let mut total_bytes: u64 = kept.iter().map(|f| f.size_bytes).sum();
+    if let Some(active) = active {
+        total_bytes += active.size_bytes;
+        kept.push(active);
+    }
+
+    info!(file_count = kept.len(), total_bytes, "log retention sweep complete");
+
+    Ok(())
+}
+
+async fn compress_rotated_files(files: &mut [LogFile]) -> anyhow::Result<()> {
+    for file in files.iter_mut() {
+        if file.path.extension().is_some_and(|ext| ext == "gz") {
+            continue;
+        }
+
+        let contents = tokio::fs::read(&file.path).await?;
+
+        let mut gz_path = file.path.clone().into_os_string();
+        gz_path.push(".gz");
+        let gz_path = PathBuf::from(gz_path);
+
+        tokio::fs::write(&gz_path, gzip(&contents)).await?;
+        tokio::fs::remove_file(&file.path).await?;
+
+        file.size_bytes = tokio::fs::metadata(&gz_path).await?.len();
+        file.path = gz_path;
+    }
+
+    Ok(())
+}
+
+/// Minimal single-member gzip container: there's no `flate2` in this build,
+/// so the header/trailer are written by hand around `miniz_oxide`'s raw
+/// deflate stream (see RFC 1952 for the format).
+fn gzip(data: &[u8]) -> Vec<u8> {
+    let deflated = miniz_oxide::deflate::compress_to_vec(data, 6);
+    let crc = CRC32.checksum(data);
+
+    let mut out = Vec::with_capacity(deflated.len() + 18);
+    out.extend_from_slice(&[0x1f, 0x8b, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xff]);
+    out.extend_from_slice(&deflated);
+    out.extend_from_slice(&crc.to_le_bytes());
+    out.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    out
+}
+
+async fn delete_log_file(file: &LogFile, reason: &str) -> anyhow::Result<()> {
+    tokio::fs::remove_file(&file.path).await?;
+    info!(path = %file.path.display(), reason, "deleted log file");
+    Ok(())
+}
+
+async fn enforce_size_budget(kept: &mut Vec<LogFile>, max_total_bytes: u64) -> anyhow::Result<()> {
+    kept.sort_by_key(|f| f.modified);
+
+    let mut total: u64 = kept.iter().map(|f| f.size_bytes).sum();
+
+    while total > max_total_bytes && !kept.is_empty() {
+        let file = kept.remove(0);
+        total -= file.size_bytes;
+        warn!(path = %file.path.display(), "deleting log file to stay under the size budget");
+        delete_log_file(&file, "over size budget").await?;
+    }
+
+    Ok(())
+}
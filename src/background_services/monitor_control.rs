@@ -0,0 +1,77 @@
+//! Shared pause/resume flag and recent-failure log for the route fetcher, so
+//! the admin panel can observe and steer it without special-casing the CLI.
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::{Pool, Postgres};
+use tokio::sync::{mpsc::Sender, Mutex};
+
+use crate::model::db_model::RouteDb;
+
+use super::data_fetcher::get_todays_data;
+
+const MAX_RECENT_FAILURES: usize = 20;
+
+#[derive(Clone, Serialize)]
+pub struct ParseFailure {
+    pub message: String,
+    pub occurred_at: DateTime<Utc>,
+}
+
+#[derive(Clone)]
+pub struct MonitorControl {
+    paused: Arc<AtomicBool>,
+    failures: Arc<Mutex<VecDeque<ParseFailure>>>,
+    last_successful_fetch: Arc<Mutex<Option<DateTime<Utc>>>>,
+    pool: Pool<Postgres>,
+    delay_checker_sender: Sender<Vec<RouteDb>>,
+}
+
+impl MonitorControl {
+    pub fn new(pool: Pool<Postgres>, delay_checker_sender: Sender<Vec<RouteDb>>) -> Self {
+        MonitorControl {
+            paused: Arc::new(AtomicBool::new(false)),
+            failures: Arc::new(Mutex::new(VecDeque::new())),
+            last_successful_fetch: Arc::new(Mutex::new(None)),
+            pool,
+            delay_checker_sender,
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::Relaxed)
+    }
+
+    pub fn set_paused(&self, paused: bool) {
+        self.paused.store(paused, Ordering::Relaxed);
+    }
+
+    pub async fn record_failure(&self, message: String) {
+        let mut failures = self.failures.lock().await;
+        failures.push_front(ParseFailure {
+            message,
+            occurred_at: Utc::now(),
+        });
+        failures.truncate(MAX_RECENT_FAILURES);
+    }
+
+    pub async fn recent_failures(&self) -> Vec<ParseFailure> {
+        self.failures.lock().await.iter().cloned().collect()
+    }
+
+    pub async fn record_success(&self) {
+        *self.last_successful_fetch.lock().await = Some(Utc::now());
+    }
+
+    pub async fn last_successful_fetch(&self) -> Option<DateTime<Utc>> {
+        *self.last_successful_fetch.lock().await
+    }
+
+    /// Runs an out-of-cycle fetch, as if the route fetcher's timer had just fired.
+    pub async fn trigger_refetch(&self) -> anyhow::Result<()> {
+        get_todays_data(&self.pool, self.delay_checker_sender.clone(), self).await
+    }
+}
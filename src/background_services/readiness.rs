@@ -0,0 +1,26 @@
+//! Tracks whether the startup warm-up fetch has finished, so `/readyz` can
+//! tell a load balancer to hold off sending traffic until today's runs and
+//! stations are already loaded rather than making the first requests pay
+//! for it.
+use std::sync::Arc;
+
+use tokio::sync::RwLock;
+
+#[derive(Clone, Default)]
+pub struct Readiness {
+    ready: Arc<RwLock<bool>>,
+}
+
+impl Readiness {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub async fn mark_ready(&self) {
+        *self.ready.write().await = true;
+    }
+
+    pub async fn is_ready(&self) -> bool {
+        *self.ready.read().await
+    }
+}
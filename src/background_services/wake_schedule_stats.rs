@@ -0,0 +1,71 @@
+//! How far each route's monitor task was from a useful observation right
+//! after it woke up, so [`crate::api::admin::wake_schedule_report`] can
+//! suggest better pre-departure lead times instead of everyone guessing at
+//! [`super::delay_checker`]'s polling cadence.
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+static STATS: OnceLock<Mutex<HashMap<i32, Stats>>> = OnceLock::new();
+
+#[derive(Default, Clone, Copy)]
+struct Stats {
+    samples: u64,
+    total_lag_secs: i64,
+    max_lag_secs: i64,
+}
+
+fn stats() -> &'static Mutex<HashMap<i32, Stats>> {
+    STATS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Records how long `route_number`'s monitor task took, after waking at
+/// `woke_at`, to get its first useful delay observation at `observed_at`.
+/// Called once per run, the first time a poll returns an actual
+/// `minutes_late` value.
+pub fn record_first_observation(route_number: i32, woke_at: DateTime<Utc>, observed_at: DateTime<Utc>) {
+    let lag_secs = (observed_at - woke_at).num_seconds().max(0);
+
+    let mut stats = stats().lock().unwrap();
+    let entry = stats.entry(route_number).or_default();
+    entry.samples += 1;
+    entry.total_lag_secs += lag_secs;
+    entry.max_lag_secs = entry.max_lag_secs.max(lag_secs);
+}
+
+#[derive(Serialize)]
+pub struct RouteWakeStats {
+    pub route_number: i32,
+    pub samples: u64,
+    pub avg_lag_secs: f64,
+    pub max_lag_secs: i64,
+    /// How much earlier to start monitoring this route, rounded up to the
+    /// nearest polling interval's worth of seconds: the average observed lag,
+    /// since a run that consistently takes this long to produce its first
+    /// reading would have gotten it right at wake-up with this much more lead.
+    pub suggested_extra_lead_secs: i64,
+}
+
+/// Every route with at least one recorded wake-up, worst average lag first.
+pub fn snapshot() -> Vec<RouteWakeStats> {
+    let mut snapshot: Vec<_> = stats()
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|(route_number, stats)| {
+            let avg_lag_secs = stats.total_lag_secs as f64 / stats.samples as f64;
+            RouteWakeStats {
+                route_number: *route_number,
+                samples: stats.samples,
+                avg_lag_secs,
+                max_lag_secs: stats.max_lag_secs,
+                suggested_extra_lead_secs: avg_lag_secs.ceil() as i64,
+            }
+        })
+        .collect();
+
+    snapshot.sort_by(|a, b| b.avg_lag_secs.total_cmp(&a.avg_lag_secs));
+    snapshot
+}
@@ -0,0 +1,84 @@
+//! Lets a user flag a route number as "watch closely" ahead of its next run,
+//! so the delay checker polls it more frequently and logs every observed
+//! status in full instead of just the usual delay-change events. There's no
+//! concept of user accounts in this repo, so anonymous requests are accepted
+//! but capped to a handful per minute to keep this from becoming a free way
+//! to hammer the upstream status endpoint; a request carrying the admin
+//! token bypasses the cap.
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use tokio::sync::Mutex;
+
+/// How long a watch stays in effect once set.
+const WATCH_TTL: Duration = Duration::from_secs(12 * 60 * 60);
+const MAX_ANONYMOUS_WATCHES_PER_MINUTE: usize = 5;
+
+#[derive(Debug)]
+pub enum WatchError {
+    RateLimited,
+}
+
+#[derive(Clone, Default, Debug)]
+pub struct WatchList {
+    watched: Arc<Mutex<HashMap<i32, Instant>>>,
+    anonymous_requests: Arc<Mutex<VecDeque<Instant>>>,
+}
+
+impl WatchList {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Marks `route_number` as watched for the next [`WATCH_TTL`]. Rejects
+    /// an unauthenticated caller once the anonymous rate limit is hit;
+    /// an authenticated one always goes through.
+    pub async fn watch(&self, route_number: i32, authenticated: bool) -> Result<(), WatchError> {
+        if !authenticated {
+            let mut requests = self.anonymous_requests.lock().await;
+            let now = Instant::now();
+
+            while requests
+                .front()
+                .is_some_and(|t| now.duration_since(*t) > Duration::from_secs(60))
+            {
+                requests.pop_front();
+            }
+
+            if requests.len() >= MAX_ANONYMOUS_WATCHES_PER_MINUTE {
+                return Err(WatchError::RateLimited);
+            }
+
+            requests.push_back(now);
+        }
+
+        self.watched
+            .lock()
+            .await
+            .insert(route_number, Instant::now() + WATCH_TTL);
+
+        Ok(())
+    }
+
+    /// How many routes currently have an unexpired watch. Doesn't prune
+    /// expired entries itself, so this is an upper bound until the next
+    /// [`WatchList::is_watched`] check sweeps them out.
+    pub async fn count(&self) -> usize {
+        self.watched.lock().await.len()
+    }
+
+    /// Whether `route_number` currently has an unexpired watch.
+    pub async fn is_watched(&self, route_number: i32) -> bool {
+        let mut watched = self.watched.lock().await;
+
+        match watched.get(&route_number) {
+            Some(expires_at) if *expires_at > Instant::now() => true,
+            Some(_) => {
+                watched.remove(&route_number);
+                false
+            }
+            None => false,
+        }
+    }
+}
@@ -0,0 +1,330 @@
+//! Typed application configuration, loaded from `config/default.toml` layered
+//! with `config/{APP_ENV}.toml` (`APP_ENV` defaults to `dev`), with real
+//! environment variables taking precedence over both. Replaces the scattering
+//! of `env::var().unwrap()` calls with a single place that reports every
+//! missing/invalid key at once instead of failing on the first one.
+use std::{collections::HashMap, fs, path::Path};
+
+const ENV_KEYS: [&str; 26] = [
+    "DATABASE_URL",
+    "OTLP_ENDPOINT",
+    "SENTRY_DSN",
+    "CHAOS_MODE_ENABLED",
+    "CHAOS_SEED",
+    "ADMIN_TOKEN",
+    "REDIS_URL",
+    "RATE_LIMIT_PER_MINUTE",
+    "RATE_LIMIT_API_KEY_PER_MINUTE",
+    "API_KEYS",
+    "CORS_ALLOWED_ORIGINS",
+    "INFLUX_WRITE_URL",
+    "INFLUX_TOKEN",
+    "ADMIN_MTLS_BIND_ADDR",
+    "ADMIN_MTLS_CERT_PATH",
+    "ADMIN_MTLS_KEY_PATH",
+    "ADMIN_MTLS_CLIENT_CA_PATH",
+    "TLS_BIND_ADDR",
+    "TLS_CERT_PATH",
+    "TLS_KEY_PATH",
+    "LOG_RETENTION_DAYS",
+    "LOG_MAX_TOTAL_MB",
+    "USAGE_METRICS_ENABLED",
+    "QUERY_STATEMENT_TIMEOUT_MS",
+    "CLIENT_DIST_DIR",
+    "EMBED_SIGNING_SECRET",
+];
+
+#[derive(Debug)]
+pub struct Config {
+    pub database_url: String,
+    pub otlp_endpoint: String,
+    pub sentry_dsn: Option<String>,
+    pub chaos_mode_enabled: bool,
+    pub chaos_seed: u64,
+    /// Bearer token gating the `/admin` panel. The panel is disabled (404) unless set.
+    pub admin_token: Option<String>,
+    /// When running multiple replicas, `ResponseCache`, `LiveComparisons` and
+    /// `DelayUpdates` each only see the state of the instance they're on. Set
+    /// this to share them across replicas instead; unset runs every instance
+    /// with its own in-process state, fine for a single-replica deployment.
+    pub redis_url: Option<String>,
+    /// Requests per minute an unrecognized caller (identified by IP) gets.
+    pub rate_limit_per_minute: u64,
+    /// Requests per minute a caller presenting a key from `api_keys` gets,
+    /// meant to be generous enough for a trusted integration.
+    pub rate_limit_api_key_per_minute: u64,
+    /// Keys accepted via the `X-Api-Key` header for the higher rate limit
+    /// bucket above. Empty means no caller gets the higher bucket.
+    pub api_keys: Vec<String>,
+    /// Origins the Svelte client is allowed to call the API from. Empty
+    /// means no `Access-Control-Allow-Origin` is ever sent, which is fine
+    /// when the client is served from the same origin as the API.
+    pub cors_allowed_origins: Vec<String>,
+    /// Full Influx-line-protocol write endpoint delay updates get pushed to,
+    /// e.g. `http://localhost:8086/write?db=hzpp`. Unset disables the export.
+    pub influx_write_url: Option<String>,
+    pub influx_token: Option<String>,
+    /// Serves the admin routes over mutual TLS on a separate listener instead
+    /// of gating them with `admin_token` — for deployments that expose the
+    /// admin surface on the network and want client-cert auth rather than a
+    /// shared bearer token. Unset keeps the existing bearer-token behavior.
+    pub admin_mtls: Option<AdminMtlsConfig>,
+    /// Terminates TLS on the public listener directly instead of expecting a
+    /// reverse proxy in front of it. Unset serves plain HTTP only, same as
+    /// before this existed.
+    pub tls: Option<TlsConfig>,
+    /// How long rotated, gzipped log files are kept before
+    /// `background_services::log_retention` deletes them.
+    pub log_retention_days: i64,
+    /// Optional cap on total log directory size; the oldest surviving logs
+    /// are deleted past this even if still within `log_retention_days`.
+    /// Unset leaves retention purely age-based.
+    pub log_max_total_bytes: Option<u64>,
+    /// Tracks per-endpoint request counts/latencies for the admin panel.
+    /// Opt-in since it costs a lock on every request even when nobody's
+    /// looking at the numbers.
+    pub usage_metrics_enabled: bool,
+    /// `statement_timeout` set on every pooled connection, so a pathological
+    /// stats query gets cancelled with a typed "too broad" error instead of
+    /// running for minutes. There's only the one pool, so this also bounds
+    /// the delay checker's own queries, but those are small single-row
+    /// reads/writes that are nowhere near this budget.
+    pub query_statement_timeout_ms: u64,
+    /// Directory the built Svelte client (`npm run build` in `client/`) is
+    /// served from at `/`, with unmatched paths falling back to its
+    /// `index.html` for client-side routing. Lets a single binary + database
+    /// serve the whole app without a separate static host in front of it.
+    pub client_dist_dir: String,
+    /// Signs `/embed/route/:n` widget URLs so only origins we've actually
+    /// handed a link to can embed it. Unset disables the endpoint (404),
+    /// the same way an unset `admin_token` disables the admin panel.
+    pub embed_signing_secret: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct AdminMtlsConfig {
+    pub bind_addr: String,
+    pub cert_path: String,
+    pub key_path: String,
+    /// PEM file of CA certificates a client cert must chain to.
+    pub client_ca_path: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct TlsConfig {
+    pub bind_addr: String,
+    pub cert_path: String,
+    pub key_path: String,
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum ConfigError {
+    #[error("invalid configuration:\n{}", .0.join("\n"))]
+    Invalid(Vec<String>),
+}
+
+impl Config {
+    pub fn load() -> Result<Self, ConfigError> {
+        let app_env = std::env::var("APP_ENV").unwrap_or_else(|_| "dev".to_string());
+
+        let mut values = HashMap::new();
+        load_profile(Path::new("config/default.toml"), &mut values)?;
+        load_profile(Path::new(&format!("config/{app_env}.toml")), &mut values)?;
+
+        for key in ENV_KEYS {
+            if let Ok(value) = std::env::var(key) {
+                values.insert(key.to_string(), value);
+            }
+        }
+
+        let mut errors = Vec::new();
+
+        let database_url = require(&values, "DATABASE_URL", &mut errors);
+        let otlp_endpoint = values
+            .get("OTLP_ENDPOINT")
+            .cloned()
+            .unwrap_or_else(|| "http://localhost:4317".to_string());
+        let sentry_dsn = values.get("SENTRY_DSN").cloned();
+        let chaos_mode_enabled = parse_bool(&values, "CHAOS_MODE_ENABLED", false, &mut errors);
+        let chaos_seed = parse_u64(&values, "CHAOS_SEED", 0, &mut errors);
+        let admin_token = values.get("ADMIN_TOKEN").cloned();
+        let redis_url = values.get("REDIS_URL").cloned();
+        let rate_limit_per_minute = parse_u64(&values, "RATE_LIMIT_PER_MINUTE", 120, &mut errors);
+        let rate_limit_api_key_per_minute =
+            parse_u64(&values, "RATE_LIMIT_API_KEY_PER_MINUTE", 600, &mut errors);
+        let api_keys = values
+            .get("API_KEYS")
+            .map(|keys| {
+                keys.split(',')
+                    .map(str::trim)
+                    .filter(|k| !k.is_empty())
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default();
+        let cors_allowed_origins = values
+            .get("CORS_ALLOWED_ORIGINS")
+            .map(|origins| {
+                origins
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|o| !o.is_empty())
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default();
+        let influx_write_url = values.get("INFLUX_WRITE_URL").cloned();
+        let influx_token = values.get("INFLUX_TOKEN").cloned();
+        let admin_mtls = load_admin_mtls(&values, &mut errors);
+        let tls = load_tls(&values, &mut errors);
+        let log_retention_days = parse_u64(&values, "LOG_RETENTION_DAYS", 30, &mut errors) as i64;
+        let log_max_total_bytes = values
+            .get("LOG_MAX_TOTAL_MB")
+            .map(|_| parse_u64(&values, "LOG_MAX_TOTAL_MB", 0, &mut errors) * 1024 * 1024);
+        let usage_metrics_enabled = parse_bool(&values, "USAGE_METRICS_ENABLED", false, &mut errors);
+        let query_statement_timeout_ms = parse_u64(&values, "QUERY_STATEMENT_TIMEOUT_MS", 10_000, &mut errors);
+        let client_dist_dir = values
+            .get("CLIENT_DIST_DIR")
+            .cloned()
+            .unwrap_or_else(|| "client/build".to_string());
+        let embed_signing_secret = values.get("EMBED_SIGNING_SECRET").cloned();
+
+        if !errors.is_empty() {
+            return Err(ConfigError::Invalid(errors));
+        }
+
+        Ok(Config {
+            database_url: database_url.expect("checked above"),
+            otlp_endpoint,
+            sentry_dsn,
+            chaos_mode_enabled,
+            chaos_seed,
+            admin_token,
+            redis_url,
+            rate_limit_per_minute,
+            rate_limit_api_key_per_minute,
+            api_keys,
+            cors_allowed_origins,
+            influx_write_url,
+            influx_token,
+            admin_mtls,
+            tls,
+            log_retention_days,
+            log_max_total_bytes,
+            usage_metrics_enabled,
+            query_statement_timeout_ms,
+            client_dist_dir,
+            embed_signing_secret,
+        })
+    }
+}
+
+/// `ADMIN_MTLS_CERT_PATH`, `ADMIN_MTLS_KEY_PATH` and `ADMIN_MTLS_CLIENT_CA_PATH`
+/// must all be set together to turn mTLS on; any other combination is a
+/// config error rather than a silent fallback to bearer-token auth.
+fn load_admin_mtls(values: &HashMap<String, String>, errors: &mut Vec<String>) -> Option<AdminMtlsConfig> {
+    let cert_path = values.get("ADMIN_MTLS_CERT_PATH").cloned();
+    let key_path = values.get("ADMIN_MTLS_KEY_PATH").cloned();
+    let client_ca_path = values.get("ADMIN_MTLS_CLIENT_CA_PATH").cloned();
+
+    match (cert_path, key_path, client_ca_path) {
+        (None, None, None) => None,
+        (Some(cert_path), Some(key_path), Some(client_ca_path)) => Some(AdminMtlsConfig {
+            bind_addr: values
+                .get("ADMIN_MTLS_BIND_ADDR")
+                .cloned()
+                .unwrap_or_else(|| "0.0.0.0:3301".to_string()),
+            cert_path,
+            key_path,
+            client_ca_path,
+        }),
+        _ => {
+            errors.push(
+                "ADMIN_MTLS_CERT_PATH, ADMIN_MTLS_KEY_PATH and ADMIN_MTLS_CLIENT_CA_PATH must all be set together"
+                    .to_string(),
+            );
+            None
+        }
+    }
+}
+
+/// `TLS_CERT_PATH` and `TLS_KEY_PATH` must be set together to turn on TLS
+/// termination on the public listener; either alone is a config error.
+fn load_tls(values: &HashMap<String, String>, errors: &mut Vec<String>) -> Option<TlsConfig> {
+    let cert_path = values.get("TLS_CERT_PATH").cloned();
+    let key_path = values.get("TLS_KEY_PATH").cloned();
+
+    match (cert_path, key_path) {
+        (None, None) => None,
+        (Some(cert_path), Some(key_path)) => Some(TlsConfig {
+            bind_addr: values
+                .get("TLS_BIND_ADDR")
+                .cloned()
+                .unwrap_or_else(|| "0.0.0.0:3443".to_string()),
+            cert_path,
+            key_path,
+        }),
+        _ => {
+            errors.push("TLS_CERT_PATH and TLS_KEY_PATH must both be set together".to_string());
+            None
+        }
+    }
+}
+
+/// Loads a TOML profile file into `values`, uppercasing its keys to match the
+/// environment variable naming. Missing profile files are fine (only used as
+/// optional overlays); a present-but-unparseable file is a hard error.
+fn load_profile(path: &Path, values: &mut HashMap<String, String>) -> Result<(), ConfigError> {
+    let Ok(contents) = fs::read_to_string(path) else {
+        return Ok(());
+    };
+
+    let table: toml::Table = toml::from_str(&contents)
+        .map_err(|e| ConfigError::Invalid(vec![format!("{}: {e}", path.display())]))?;
+
+    for (key, value) in table {
+        let value = match value {
+            toml::Value::String(s) => s,
+            other => other.to_string(),
+        };
+        values.insert(key.to_uppercase(), value);
+    }
+
+    Ok(())
+}
+
+fn require(values: &HashMap<String, String>, key: &str, errors: &mut Vec<String>) -> Option<String> {
+    match values.get(key) {
+        Some(value) => Some(value.clone()),
+        None => {
+            errors.push(format!("{key} is missing"));
+            None
+        }
+    }
+}
+
+fn parse_bool(values: &HashMap<String, String>, key: &str, default: bool, errors: &mut Vec<String>) -> bool {
+    match values.get(key) {
+        None => default,
+        Some(value) => match value.parse() {
+            Ok(value) => value,
+            Err(_) => {
+                errors.push(format!("{key} must be true or false, got {value:?}"));
+                default
+            }
+        },
+    }
+}
+
+fn parse_u64(values: &HashMap<String, String>, key: &str, default: u64, errors: &mut Vec<String>) -> u64 {
+    match values.get(key) {
+        None => default,
+        Some(value) => match value.parse() {
+            Ok(value) => value,
+            Err(_) => {
+                errors.push(format!("{key} must be a non-negative integer, got {value:?}"));
+                default
+            }
+        },
+    }
+}
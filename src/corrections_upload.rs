@@ -0,0 +1,121 @@
+//! Bulk real-time-observation corrections uploaded as CSV or JSON, applied
+//! one row at a time through [`crate::admin::correct_real_time`] so a batch
+//! of known-wrong observations goes through the same validation/audit path
+//! as a single correction, instead of hand-written SQL.
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::admin::RealTimeCorrectionRequest;
+
+#[derive(Debug, Deserialize, PartialEq)]
+pub struct CorrectionRow {
+    pub numeric_id: i64,
+    pub sequence: i16,
+    pub real_arrival: Option<DateTime<Utc>>,
+    pub real_departure: Option<DateTime<Utc>>,
+    pub reason: String,
+}
+
+impl From<CorrectionRow> for RealTimeCorrectionRequest {
+    fn from(row: CorrectionRow) -> Self {
+        RealTimeCorrectionRequest {
+            numeric_id: row.numeric_id,
+            sequence: row.sequence,
+            real_arrival: row.real_arrival,
+            real_departure: row.real_departure,
+            reason: row.reason,
+        }
+    }
+}
+
+/// Parses `numeric_id,sequence,real_arrival,real_departure,reason` rows, one
+/// per line, skipping a matching header if present. Either time column may be
+/// left empty to mean "leave unchanged". No quoting/escaping support, same as
+/// `station_renames`'s CSV parser — the `reason` column is last so it can
+/// itself contain commas.
+pub fn parse_csv(csv: &str) -> Result<Vec<CorrectionRow>, anyhow::Error> {
+    let mut rows = vec![];
+
+    for (line_number, line) in csv.lines().enumerate() {
+        let line = line.trim();
+
+        if line.is_empty() || line == "numeric_id,sequence,real_arrival,real_departure,reason" {
+            continue;
+        }
+
+        let mut parts = line.splitn(5, ',');
+        let context = || format!("line {}: expected \"numeric_id,sequence,real_arrival,real_departure,reason\"", line_number + 1);
+
+        let numeric_id: i64 = parts
+            .next()
+            .with_context(context)?
+            .trim()
+            .parse()
+            .with_context(context)?;
+        let sequence: i16 = parts
+            .next()
+            .with_context(context)?
+            .trim()
+            .parse()
+            .with_context(context)?;
+        let real_arrival = parse_optional_timestamp(parts.next().with_context(context)?.trim())
+            .with_context(context)?;
+        let real_departure = parse_optional_timestamp(parts.next().with_context(context)?.trim())
+            .with_context(context)?;
+        let reason = parts.next().with_context(context)?.trim().to_string();
+
+        rows.push(CorrectionRow { numeric_id, sequence, real_arrival, real_departure, reason });
+    }
+
+    Ok(rows)
+}
+
+fn parse_optional_timestamp(field: &str) -> Result<Option<DateTime<Utc>>, anyhow::Error> {
+    if field.is_empty() {
+        return Ok(None);
+    }
+
+    Ok(Some(DateTime::parse_from_rfc3339(field)?.with_timezone(&Utc)))
+}
+
+#[derive(Serialize)]
+pub struct RowError {
+    pub numeric_id: i64,
+    pub sequence: i16,
+    pub error: String,
+}
+
+#[derive(Serialize)]
+pub struct UploadSummary {
+    pub applied: u64,
+    pub errors: Vec<RowError>,
+}
+
+mod tests {
+    #[test]
+    fn parses_rows_and_skips_the_header() {
+        let csv = "numeric_id,sequence,real_arrival,real_departure,reason\n\
+                    42,3,2024-05-01T08:05:00Z,,platform camera confirms actual arrival\n";
+
+        let rows = super::parse_csv(csv).unwrap();
+
+        assert_eq!(
+            rows,
+            vec![super::CorrectionRow {
+                numeric_id: 42,
+                sequence: 3,
+                real_arrival: Some(chrono::DateTime::parse_from_rfc3339("2024-05-01T08:05:00Z").unwrap().with_timezone(&chrono::Utc)),
+                real_departure: None,
+                reason: "platform camera confirms actual arrival".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn rejects_a_row_missing_columns() {
+        let csv = "42,3,2024-05-01T08:05:00Z";
+
+        assert!(super::parse_csv(csv).is_err());
+    }
+}
@@ -3,10 +3,10 @@
 #![feature(async_closure)]
 
 use anyhow::Result;
-use axum::routing::get;
 use axum::Router;
 use background_services::data_fetcher::get_todays_data;
 use background_services::delay_checker::check_delays;
+use background_services::digest::DigestChannel;
 use clap::{command, Parser, Subcommand};
 use dotenvy::dotenv;
 use model::db_model::RouteDb;
@@ -14,24 +14,54 @@ use opentelemetry::trace::TracerProvider as _;
 use opentelemetry::KeyValue;
 use opentelemetry_otlp::{SpanExporterBuilder, TonicExporterBuilder, WithExportConfig};
 use opentelemetry_sdk::runtime::Tokio;
-use opentelemetry_sdk::trace::{Config, TracerProvider};
+use opentelemetry_sdk::trace::{Config as OtelConfig, TracerProvider};
 use opentelemetry_sdk::Resource;
-use std::env;
 use std::process::Stdio;
 use std::time::Duration;
 use tokio::process::Command;
 use tokio::sync::mpsc::channel;
 use tokio::time::sleep;
 use tokio::{select, spawn};
+use tower_http::services::{ServeDir, ServeFile};
 use tracing::{error, info};
 use tracing_subscriber::filter::LevelFilter;
 use tracing_subscriber::layer::SubscriberExt;
 use tracing_subscriber::util::SubscriberInitExt;
 use tracing_subscriber::{EnvFilter, Registry};
 
+mod admin;
+mod api;
+mod backfill;
 mod background_services;
+mod config;
+mod corrections_upload;
 mod model;
+#[cfg(feature = "mtls")]
+mod mtls;
+mod narrative;
+mod query_stats;
+mod route_renumbering;
+mod route_tags;
+mod snapshot;
+mod station_renames;
+#[cfg(feature = "tls")]
+mod tls;
 mod utils;
+#[cfg(feature = "weather")]
+mod weather;
+mod zip_writer;
+
+/// Per-channel digesting windows, so a network-wide disruption collapses
+/// into at most one summarized message per channel per window instead of
+/// flooding it with a per-train alert for every observed delay change.
+const DIGEST_CHANNELS: [DigestChannel; 2] = [
+    DigestChannel { name: "ops-log", window: Duration::from_secs(60) },
+    DigestChannel { name: "ops-log-hourly-rollup", window: Duration::from_secs(60 * 60) },
+];
+
+/// Directory the rolling file appender writes to, shared with
+/// `api::diagnostics` so the self-diagnostics report can size it up.
+pub const LOGS_DIR: &str = "./logs";
 
 #[derive(Parser, Debug)]
 #[command(version)]
@@ -44,6 +74,52 @@ struct Cli {
 enum Commands {
     #[command(about = "Start up the frontend in dev mode for development purposes")]
     Front {},
+    #[command(about = "Freeze an immutable, checksummed snapshot of the dataset")]
+    Snapshot {
+        /// Name identifying this snapshot, e.g. "2024Q2"
+        #[arg(long)]
+        tag: String,
+    },
+    #[command(about = "Monitor a train missing from the planner API until it finishes")]
+    MonitorAdhoc {
+        #[arg(long)]
+        route_number: i32,
+        #[arg(long)]
+        source: String,
+        #[arg(long)]
+        destination: String,
+        #[arg(long)]
+        expected_start_time: chrono::DateTime<chrono::Utc>,
+        #[arg(long)]
+        expected_end_time: chrono::DateTime<chrono::Utc>,
+    },
+    #[command(about = "Apply a bulk station rename/merge from a CSV file")]
+    StationsApplyRenames {
+        /// Path to a CSV file of `station_id,new_name` rows.
+        csv_path: std::path::PathBuf,
+    },
+    #[command(about = "Detect route numbers renumbered at a timetable change and link them")]
+    DetectRouteSuccessors {},
+}
+
+#[cfg(feature = "sentry-reporting")]
+fn init_sentry(dsn: Option<String>) -> Option<sentry::ClientInitGuard> {
+    dsn.map(|dsn| {
+        let mut options = sentry::ClientOptions::default();
+        options.release = sentry::release_name!();
+        sentry::init((dsn, options))
+    })
+}
+
+#[cfg(not(feature = "sentry-reporting"))]
+fn init_sentry(dsn: Option<String>) -> Option<()> {
+    if dsn.is_some() {
+        tracing::warn!(
+            "SENTRY_DSN is set but this build was compiled without the sentry-reporting feature; \
+             panics and errors won't be reported"
+        );
+    }
+    None
 }
 
 #[tokio::main(flavor = "multi_thread")]
@@ -52,7 +128,7 @@ async fn main() -> Result<()> {
 
     let cli = Cli::parse();
     println!("{:?}", cli);
-    if cli.command.is_some() {
+    if matches!(cli.command, Some(Commands::Front {})) {
         let _ = Command::new("pwsh")
             .args(["-c ", "cd client; npm run dev -- --open"])
             .stdin(Stdio::null())
@@ -61,16 +137,28 @@ async fn main() -> Result<()> {
             .expect("Failed to execute command");
     }
 
-    info!("OTLP_ENDPOINT: {}", dotenvy::var("OTLP_ENDPOINT").unwrap());
+    let config = config::Config::load().unwrap_or_else(|e| {
+        eprintln!("{e}");
+        std::process::exit(1);
+    });
+
+    info!("OTLP_ENDPOINT: {}", config.otlp_endpoint);
+    // TODO: wire REDIS_URL into a shared ResponseCache/LiveComparisons/DelayUpdates
+    // backend for multi-replica deployments. Until then every replica keeps its
+    // own in-process state, so set this won't do anything but gets flagged here
+    // rather than silently ignored.
+    if config.redis_url.is_some() {
+        tracing::warn!(
+            "REDIS_URL is set but the Redis-backed cache/pub-sub backend isn't wired in yet; \
+             falling back to in-process state, which won't be shared across replicas"
+        );
+    }
     let provider = TracerProvider::builder()
         .with_batch_exporter(
             SpanExporterBuilder::Tonic(
                 TonicExporterBuilder::default()
                     .with_timeout(Duration::from_millis(1000))
-                    .with_endpoint(
-                        dotenvy::var("OTLP_ENDPOINT")
-                            .unwrap_or("http://localhost:4317".to_string()),
-                    )
+                    .with_endpoint(&config.otlp_endpoint)
                     .with_protocol(opentelemetry_otlp::Protocol::Grpc),
             )
             .build_span_exporter()
@@ -78,7 +166,7 @@ async fn main() -> Result<()> {
             Tokio,
         )
         .with_config(
-            Config::default().with_resource(Resource::new(vec![KeyValue::new(
+            OtelConfig::default().with_resource(Resource::new(vec![KeyValue::new(
                 "service.name",
                 "HZPP_delay_stats",
             )])),
@@ -93,7 +181,7 @@ async fn main() -> Result<()> {
         .with_default_directive(LevelFilter::INFO.into())
         .from_env_lossy();
 
-    let appender = tracing_appender::rolling::daily("./logs", "hzpp_delay_stats.log");
+    let appender = tracing_appender::rolling::daily(LOGS_DIR, "hzpp_delay_stats.log");
     let (non_blocking_appender, _guard) = tracing_appender::non_blocking(appender);
     let (non_blocking_stdout, _guard) = tracing_appender::non_blocking(std::io::stdout());
 
@@ -104,23 +192,142 @@ async fn main() -> Result<()> {
         .with_ansi(false)
         .pretty();
 
-    Registry::default()
-        .with(telemetry_layer)
-        .with(file_log)
-        .with(env_filter)
-        .init();
+    // Only forwards panics and error-level events (route/train context comes from
+    // the enclosing tracing spans) to Sentry when a DSN is configured; otherwise
+    // this is a no-op client.
+    let _sentry_guard = init_sentry(config.sentry_dsn.clone());
+
+    let registry = Registry::default().with(telemetry_layer).with(file_log).with(env_filter);
+    #[cfg(feature = "sentry-reporting")]
+    registry.with(sentry::integrations::tracing::layer()).init();
+    #[cfg(not(feature = "sentry-reporting"))]
+    registry.init();
+
+    info!(
+        mtls = cfg!(feature = "mtls"),
+        sentry_reporting = cfg!(feature = "sentry-reporting"),
+        tls = cfg!(feature = "tls"),
+        weather = cfg!(feature = "weather"),
+        "compiled-in optional subsystems"
+    );
+
+    background_services::chaos::configure(config.chaos_mode_enabled, config.chaos_seed);
+
+    let statement_timeout_ms = config.query_statement_timeout_ms;
+    let pool = sqlx::postgres::PgPoolOptions::new()
+        .after_connect(move |conn, _meta| {
+            Box::pin(async move {
+                sqlx::query(&format!("SET statement_timeout = {statement_timeout_ms}"))
+                    .execute(conn)
+                    .await?;
+                Ok(())
+            })
+        })
+        .connect(&config.database_url)
+        .await
+        .unwrap();
+    sqlx::migrate!("./migrations").run(&pool).await.unwrap();
 
-    let db_url = env::var("DATABASE_URL").unwrap();
+    if let Some(Commands::Snapshot { tag }) = &cli.command {
+        snapshot::create_snapshot(&pool, tag).await?;
+        return Ok(());
+    }
 
-    let pool = sqlx::PgPool::connect(&db_url).await.unwrap();
-    sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+    if let Some(Commands::StationsApplyRenames { csv_path }) = &cli.command {
+        let csv = tokio::fs::read_to_string(csv_path).await?;
+        let renamed = station_renames::apply_renames(&pool, &csv).await?;
+        info!(renamed, "station renames applied");
+        return Ok(());
+    }
+
+    if matches!(cli.command, Some(Commands::DetectRouteSuccessors {})) {
+        let linked = route_renumbering::detect_successors(&pool).await?;
+        info!(linked, "route renumbering links detected");
+        return Ok(());
+    }
+
+    if let Some(Commands::MonitorAdhoc {
+        route_number,
+        source,
+        destination,
+        expected_start_time,
+        expected_end_time,
+    }) = &cli.command
+    {
+        let route = admin::create_adhoc_route(
+            &pool,
+            admin::AdhocRouteRequest {
+                route_number: *route_number,
+                source: source.clone(),
+                destination: destination.clone(),
+                expected_start_time: *expected_start_time,
+                expected_end_time: *expected_end_time,
+            },
+        )
+        .await?;
+        background_services::delay_checker::monitor_adhoc_route(
+            route,
+            pool.clone(),
+            background_services::live_comparison::LiveComparisons::new(),
+            background_services::delay_checker::DelayResponseCache::new(),
+            background_services::delay_broadcast::DelayUpdates::new(),
+            background_services::watchlist::WatchList::new(),
+            background_services::active_monitors::ActiveMonitors::new(),
+        )
+        .await?;
+        return Ok(());
+    }
 
     let (delay_checker_sender, mut delay_checker_receiver) = channel::<Vec<RouteDb>>(32);
 
+    let live_comparisons = background_services::live_comparison::LiveComparisons::new();
+    let delay_response_cache = background_services::delay_checker::DelayResponseCache::new();
+    let delay_updates = background_services::delay_broadcast::DelayUpdates::new();
+    let watch_list = background_services::watchlist::WatchList::new();
+    let active_monitors = background_services::active_monitors::ActiveMonitors::new();
+    let readiness = background_services::readiness::Readiness::new();
+
+    let monitor_control = background_services::monitor_control::MonitorControl::new(
+        pool.clone(),
+        delay_checker_sender.clone(),
+    );
+
     let route_fetcher_pool = pool.clone();
+    let route_fetcher_control = monitor_control.clone();
+
+    // Preload today's routes and stations before starting the delay checker
+    // or serving traffic, so the first poll cycle and first requests don't
+    // each pay the cold-fetch cost on their own. `/readyz` stays unready
+    // until this completes. (HZPP doesn't expose tomorrow's schedule or
+    // station aliases, so only what `get_todays_data` already fetches is
+    // warmed here.)
+    info!("warming up: preloading today's routes and stations");
+    let warmup_result = get_todays_data(
+        &route_fetcher_pool,
+        delay_checker_sender.clone(),
+        &route_fetcher_control,
+    )
+    .await;
+    if let Err(e) = &warmup_result {
+        error!("warm-up fetch failed, starting anyway: {e}");
+    }
+    readiness.mark_ready().await;
+
+    let initial_route_fetcher_sleep = if warmup_result.is_ok() {
+        Duration::from_secs(60 * 60)
+    } else {
+        Duration::from_secs(60)
+    };
+
     let route_fetcher = spawn(async move {
+        sleep(initial_route_fetcher_sleep).await;
         loop {
-            if let Err(e) = get_todays_data(&route_fetcher_pool, delay_checker_sender.clone()).await
+            if let Err(e) = get_todays_data(
+                &route_fetcher_pool,
+                delay_checker_sender.clone(),
+                &route_fetcher_control,
+            )
+            .await
             {
                 error!("{e}");
                 sleep(Duration::from_secs(60)).await;
@@ -130,18 +337,148 @@ async fn main() -> Result<()> {
         }
     });
 
+    let api_state = api::AppState::new(
+        pool.clone(),
+        monitor_control,
+        config.admin_token.clone(),
+        live_comparisons.clone(),
+        delay_response_cache.clone(),
+        delay_updates.clone(),
+        watch_list.clone(),
+        active_monitors.clone(),
+        api::rate_limit::RateLimitConfig {
+            per_ip_per_minute: config.rate_limit_per_minute,
+            per_api_key_per_minute: config.rate_limit_api_key_per_minute,
+            api_keys: config.api_keys.clone(),
+        },
+        config.cors_allowed_origins.clone(),
+        readiness,
+        config.admin_mtls.is_some(),
+        config.usage_metrics_enabled,
+        config.embed_signing_secret.clone(),
+    );
+    let delay_checker_comparisons = live_comparisons.clone();
+    let delay_checker_response_cache = delay_response_cache.clone();
+    let delay_checker_updates = delay_updates.clone();
+    let delay_checker_watch_list = watch_list.clone();
+    let delay_checker_active_monitors = active_monitors.clone();
     let delay_checker = spawn(async move {
         loop {
-            if let Err(e) = check_delays(&mut delay_checker_receiver, &pool).await {
+            if let Err(e) = check_delays(
+                &mut delay_checker_receiver,
+                &pool,
+                &delay_checker_comparisons,
+                &delay_checker_response_cache,
+                &delay_checker_updates,
+                &delay_checker_watch_list,
+                &delay_checker_active_monitors,
+            )
+            .await
+            {
                 error!("{e}");
             }
         }
     });
 
-    let app = Router::new().route("/", get(root));
+    spawn(api::rate_limit::sweep_stale_buckets_periodically(
+        api_state.rate_limiter.clone(),
+    ));
+
+    let cache_refresher_pool = api_state.pool.clone();
+    let cache_refresher_cache = api_state.cache.clone();
+    spawn(background_services::cache_refresher::refresh_cached_payloads(
+        cache_refresher_pool,
+        cache_refresher_cache,
+    ));
+
+    spawn(background_services::data_integrity::run_checks_periodically(
+        api_state.pool.clone(),
+    ));
+
+    spawn(background_services::finalization::run_periodically(api_state.pool.clone()));
+
+    for channel in DIGEST_CHANNELS {
+        spawn(background_services::digest::run_digest(channel, delay_updates.clone()));
+    }
+
+    if let Some(write_url) = config.influx_write_url.clone() {
+        spawn(background_services::influx_exporter::run_influx_exporter(
+            background_services::influx_exporter::InfluxExporterConfig {
+                write_url,
+                token: config.influx_token.clone(),
+            },
+            delay_updates.clone(),
+        ));
+    }
+
+    spawn(background_services::log_retention::run_periodically(
+        LOGS_DIR.to_string(),
+        background_services::log_retention::LogRetentionConfig {
+            retention_days: config.log_retention_days,
+            max_total_bytes: config.log_max_total_bytes,
+        },
+    ));
+
+    let admin_mtls_config = config.admin_mtls.clone();
+    #[cfg(feature = "mtls")]
+    let admin_mtls_router = api::admin_panel_router().with_state(api_state.clone());
+    let tls_config = config.tls.clone();
+
+    let client_files = ServeDir::new(&config.client_dist_dir)
+        .not_found_service(ServeFile::new(format!("{}/index.html", config.client_dist_dir)));
+
+    let app = Router::new()
+        .merge(api::router(api_state))
+        .fallback_service(client_files);
+
+    #[cfg(feature = "tls")]
+    let tls_app = app.clone();
 
     let listener = tokio::net::TcpListener::bind("0.0.0.0:3300").await.unwrap();
-    let web_server = tokio::spawn(async { axum::serve(listener, app).await.unwrap() });
+    let web_server = tokio::spawn(async {
+        axum::serve(
+            listener,
+            app.into_make_service_with_connect_info::<std::net::SocketAddr>(),
+        )
+        .await
+        .unwrap()
+    });
+
+    #[cfg(feature = "tls")]
+    let tls_server = tokio::spawn(async move {
+        match tls_config {
+            Some(config) => tls::serve(tls_app, config).await,
+            None => std::future::pending().await,
+        }
+    });
+    #[cfg(not(feature = "tls"))]
+    let tls_server: tokio::task::JoinHandle<anyhow::Result<()>> = tokio::spawn(async move {
+        if tls_config.is_some() {
+            tracing::warn!(
+                "TLS_CERT_PATH/TLS_KEY_PATH are configured but this build was compiled without \
+                 the tls feature; the API is only reachable over plain HTTP"
+            );
+        }
+        std::future::pending().await
+    });
+
+    #[cfg(feature = "mtls")]
+    let admin_mtls_server = tokio::spawn(async move {
+        match admin_mtls_config {
+            Some(config) => mtls::serve(admin_mtls_router, config).await,
+            None => std::future::pending().await,
+        }
+    });
+    #[cfg(not(feature = "mtls"))]
+    let admin_mtls_server: tokio::task::JoinHandle<anyhow::Result<()>> = tokio::spawn(async move {
+        if admin_mtls_config.is_some() {
+            tracing::warn!(
+                "ADMIN_MTLS is configured but this build was compiled without the mtls feature; \
+                 the admin panel is only reachable over the plain HTTP listener"
+            );
+        }
+        std::future::pending().await
+    });
 
     select! {
     res = route_fetcher =>{
@@ -162,16 +499,26 @@ async fn main() -> Result<()> {
         match res{
             Ok(_) => unreachable!(),
             Err(err) => error!("{:?}",err),
+        }},
+
+    res = admin_mtls_server => {
+        match res{
+            Ok(Ok(())) => unreachable!(),
+            Ok(Err(err)) => error!("admin mTLS listener: {err:?}"),
+            Err(err) => error!("{:?}",err),
+        }},
+
+    res = tls_server => {
+        match res{
+            Ok(Ok(())) => unreachable!(),
+            Ok(Err(err)) => error!("TLS listener: {err:?}"),
+            Err(err) => error!("{:?}",err),
         }}
     }
 
     Ok(())
 }
 
-async fn root() -> &'static str {
-    "Hello, World!"
-}
-
 #[cfg(unix)]
 async fn wait_for_signal_impl() {
     use tokio::signal::unix::{signal, SignalKind};
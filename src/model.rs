@@ -1,2 +1,3 @@
 pub mod db_model;
 pub mod hzpp_api_model;
+pub mod stock_class;
@@ -1,15 +1,21 @@
 use anyhow::{anyhow, bail, Context, Error};
-use chrono::{DateTime, Days, Timelike, Utc};
+use chrono::{DateTime, Datelike, Days, Timelike, Utc, Weekday};
 use chrono_tz::Tz;
 use itertools::Itertools;
 use sqlx::prelude::FromRow;
 use tracing::error;
 
-use super::hzpp_api_model::{HzppRoute, HzppStation, HzppStop};
+use super::hzpp_api_model::{Calendar, HzppRoute, HzppStation, HzppStop};
 
 #[derive(FromRow)]
 pub struct RouteDb {
     pub id: String,
+    /// Surrogate key assigned by the database, stable even if `id`'s upstream
+    /// format changes. `None` until the row has actually been inserted.
+    pub numeric_id: Option<i64>,
+    /// Human-readable id derived from `route_number`/`source`/`destination` at
+    /// ingest time, e.g. `2111-zagreb-novska`. Used in URLs instead of `id`.
+    pub slug: String,
     pub route_number: i32,
     pub source: String,
     pub destination: String,
@@ -20,11 +26,26 @@ pub struct RouteDb {
     #[sqlx(try_from = "i16")]
     pub route_type: RouteType,
     pub real_start_time: Option<DateTime<Utc>>,
+    /// Whether `real_start_time` was backed out from a delay observed at some
+    /// other stop rather than an actual observation of the origin departure.
+    /// Set when we start monitoring a run already in progress; corrected to
+    /// `false` if an origin-station observation ever comes in.
+    pub real_start_time_inferred: bool,
     /// The departure time of the first stop
     pub expected_start_time: DateTime<Utc>,
     pub real_end_time: Option<DateTime<Utc>>,
     /// The arrival time of the last stop
     pub expected_end_time: DateTime<Utc>,
+    /// The largest delay (in minutes) observed at any point during the run so far.
+    pub max_delay_minutes: Option<i32>,
+    /// The delay (in minutes) the run finished with. Set once `real_end_time` is known.
+    pub final_delay_minutes: Option<i32>,
+    /// The route's calendar says it doesn't actually run on `expected_start_time`'s
+    /// weekday. Still stored for completeness, but not dispatched to the delay checker.
+    pub schedule_only: bool,
+    /// Human-readable recap of the run, generated once it finishes. See
+    /// [`crate::narrative::generate`].
+    pub narrative_summary: Option<String>,
     #[sqlx(skip)]
     pub stops: Vec<StopDb>,
 }
@@ -33,6 +54,8 @@ impl std::fmt::Debug for RouteDb {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("RouteDb")
             .field("id", &self.id)
+            .field("numeric_id", &self.numeric_id)
+            .field("slug", &self.slug)
             .field("route_number", &self.route_number)
             .field("source", &self.source)
             .field("destination", &self.destination)
@@ -40,9 +63,14 @@ impl std::fmt::Debug for RouteDb {
             .field("wheelchair_accessible", &self.wheelchair_accessible)
             .field("route_type", &self.route_type)
             .field("real_start_time", &self.real_start_time)
+            .field("real_start_time_inferred", &self.real_start_time_inferred)
             .field("expected_start_time", &self.expected_start_time)
             .field("real_end_time", &self.real_end_time)
             .field("expected_end_time", &self.expected_end_time)
+            .field("max_delay_minutes", &self.max_delay_minutes)
+            .field("final_delay_minutes", &self.final_delay_minutes)
+            .field("schedule_only", &self.schedule_only)
+            .field("narrative_summary", &self.narrative_summary)
             //.field("stops", &self.stops)
             .finish()
     }
@@ -80,8 +108,20 @@ impl RouteDb {
             bail!("Error turning HzppStop to StopDb");
         }
 
+        // An empty calendar means the API gave us no schedule info at all, which we
+        // don't treat as "never runs" — only an explicit calendar ruling out today
+        // marks the route schedule-only.
+        let schedule_only = !hzpp_route.calendar.is_empty()
+            && !calendar_permits(&hzpp_route.calendar, date.weekday());
+
         Ok(RouteDb {
             id: hzpp_route.route_id,
+            numeric_id: None,
+            slug: crate::utils::slugify(&[
+                &hzpp_route.route_number.to_string(),
+                &first_stop.stop_name,
+                &last_stop.stop_name,
+            ]),
             route_number: hzpp_route.route_number,
             source: first_stop.stop_name.clone(),
             destination: last_stop.stop_name.clone(),
@@ -89,14 +129,37 @@ impl RouteDb {
             wheelchair_accessible: hzpp_route.wheelchair_accessible.try_into()?,
             route_type: hzpp_route.route_type.try_into()?,
             real_start_time: None,
+            real_start_time_inferred: true,
             expected_start_time: expected_start_time.with_timezone(&Utc),
             real_end_time: None,
             expected_end_time: expected_end_time.with_timezone(&Utc),
+            max_delay_minutes: None,
+            final_delay_minutes: None,
+            schedule_only,
+            narrative_summary: None,
             stops,
         })
     }
 }
 
+/// Whether `calendar` says the route runs on `weekday`. A route can have several
+/// calendar entries (e.g. one for term-time, one for holidays); it's considered
+/// to run today if any of them says so.
+fn calendar_permits(calendar: &[Calendar], weekday: Weekday) -> bool {
+    calendar.iter().any(|c| {
+        let flag = match weekday {
+            Weekday::Mon => c.monday,
+            Weekday::Tue => c.tuesday,
+            Weekday::Wed => c.wednesday,
+            Weekday::Thu => c.thursday,
+            Weekday::Fri => c.friday,
+            Weekday::Sat => c.saturday,
+            Weekday::Sun => c.sunday,
+        };
+        flag == 1
+    })
+}
+
 fn convert_hzpp_time_to_utc(
     date: &DateTime<Tz>,
     expected_start_time: (u8, u8),
@@ -227,6 +290,10 @@ pub struct StopDb {
     pub expected_arrival: DateTime<Utc>,
     pub real_departure: Option<DateTime<Utc>>,
     pub expected_departure: DateTime<Utc>,
+    /// Announced platform/track, when the delay page happened to include
+    /// one for this observation. HŽ doesn't publish it in the planner feed,
+    /// so this is only ever filled in after the fact by the delay checker.
+    pub platform: Option<String>,
 }
 
 impl StopDb {
@@ -260,6 +327,7 @@ impl StopDb {
             expected_arrival: expected_arrival.to_utc(),
             real_departure: None,
             expected_departure: expected_departure.to_utc(),
+            platform: None,
         })
     }
 }
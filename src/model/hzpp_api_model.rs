@@ -1,5 +1,5 @@
+use anyhow::{anyhow, Context};
 use serde::{de, Deserialize, Deserializer, Serialize};
-use std::str::FromStr;
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct HzppRoute {
@@ -71,12 +71,237 @@ where
 {
     let s: String = Deserialize::deserialize(deserializer)?;
 
-    let res: anyhow::Result<(u8, u8)> = try {
-        let hour: u8 = String::from_str(&s[0..=1])?.parse()?;
-        let minute: u8 = String::from_str(&s[3..=4])?.parse()?;
+    parse_hzpp_time(&s).map_err(de::Error::custom)
+}
+
+/// Parses HZPP's `HH:MM:SS` time-of-day strings. `HH` isn't clamped to 24 —
+/// values like `48:05:00` mean "05 two days after the schedule's date" and
+/// are expected; callers turn the rollover into a day offset (see
+/// `convert_hzpp_time_to_utc`). Splits on `:` instead of slicing fixed byte
+/// ranges, so single-digit and multi-digit hours (`8:05:00`, `100:05:00`)
+/// parse the same as the usual zero-padded two-digit form.
+fn parse_hzpp_time(s: &str) -> anyhow::Result<(u8, u8)> {
+    let mut parts = s.splitn(3, ':');
+
+    let hour: u8 = parts
+        .next()
+        .ok_or_else(|| anyhow!("missing hour in HZPP time {s:?}"))?
+        .trim()
+        .parse()
+        .with_context(|| format!("invalid hour in HZPP time {s:?}"))?;
+
+    let minute: u8 = parts
+        .next()
+        .ok_or_else(|| anyhow!("missing minute in HZPP time {s:?}"))?
+        .trim()
+        .parse()
+        .with_context(|| format!("invalid minute in HZPP time {s:?}"))?;
+
+    Ok((hour, minute))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ROUTE_SAMPLE: &str = r##"{
+        "route_id": "1234_20240126",
+        "route_number": 1234,
+        "route_src": "ZAGREB GL.KOL.",
+        "route_desc": "RIJEKA",
+        "arrival_time": "14:32:00",
+        "departure_time": "08:10:00",
+        "bikes_allowed": 0,
+        "wheelchair_accessible": 1,
+        "route_type": 2,
+        "stops": [
+            {
+                "stop_id": "1",
+                "stop_name": "ZAGREB GL.KOL.",
+                "arrival_time": "08:10:00",
+                "departure_time": "08:10:00",
+                "latitude": 45.8034,
+                "longitude": 15.9773,
+                "sequence": 1
+            },
+            {
+                "stop_id": "2",
+                "stop_name": "RIJEKA",
+                "arrival_time": "14:32:00",
+                "departure_time": "14:32:00",
+                "latitude": 45.3271,
+                "longitude": 14.4422,
+                "sequence": 2
+            }
+        ],
+        "calendar": [
+            {
+                "monday": 1,
+                "tuesday": 1,
+                "wednesday": 1,
+                "thursday": 1,
+                "friday": 1,
+                "saturday": 0,
+                "sunday": 0
+            }
+        ]
+    }"##;
+
+    const STATION_SAMPLE: &str = r##"{
+        "stop_id": "1",
+        "stop_code": 100,
+        "stop_name": "ZAGREB GL.KOL.",
+        "stop_lat": 45.8034,
+        "stop_lng": 15.9773
+    }"##;
+
+    #[test]
+    fn hzpp_route_deserializes_canonical_sample() {
+        let route: HzppRoute = serde_json::from_str(ROUTE_SAMPLE).unwrap();
+
+        assert_eq!(route.route_number, 1234);
+        assert_eq!(route.stops.len(), 2);
+        assert_eq!(route.stops[0].arrival_time, (8, 10));
+    }
+
+    #[test]
+    fn hzpp_station_deserializes_canonical_sample() {
+        let station: HzppStation = serde_json::from_str(STATION_SAMPLE).unwrap();
+
+        assert_eq!(station.stop_code, 100);
+    }
+
+    #[test]
+    fn timestamp_from_hzpp_time_handles_hour_rollover() {
+        let route = ROUTE_SAMPLE.replace("\"14:32:00\"", "\"25:49:00\"");
+        let route: HzppRoute = serde_json::from_str(&route).unwrap();
+
+        assert_eq!(route.arrival_time, (25, 49));
+    }
+
+    #[test]
+    fn parse_hzpp_time_accepts_the_usual_two_digit_form() {
+        assert_eq!(parse_hzpp_time("08:10:00").unwrap(), (8, 10));
+    }
+
+    #[test]
+    fn parse_hzpp_time_accepts_a_single_digit_hour() {
+        assert_eq!(parse_hzpp_time("8:10:00").unwrap(), (8, 10));
+    }
+
+    #[test]
+    fn parse_hzpp_time_accepts_hours_past_a_full_day() {
+        assert_eq!(parse_hzpp_time("48:05:00").unwrap(), (48, 5));
+    }
+
+    #[test]
+    fn parse_hzpp_time_accepts_hours_past_several_days() {
+        assert_eq!(parse_hzpp_time("100:05:00").unwrap(), (100, 5));
+    }
+
+    #[test]
+    fn parse_hzpp_time_ignores_seconds() {
+        assert_eq!(parse_hzpp_time("08:10:59").unwrap(), (8, 10));
+    }
+
+    #[test]
+    fn parse_hzpp_time_rejects_missing_minute() {
+        assert!(parse_hzpp_time("08").is_err());
+    }
+
+    #[test]
+    fn parse_hzpp_time_rejects_empty_string() {
+        assert!(parse_hzpp_time("").is_err());
+    }
+
+    #[test]
+    fn parse_hzpp_time_rejects_non_numeric_hour() {
+        assert!(parse_hzpp_time("ab:10:00").is_err());
+    }
+
+    #[test]
+    fn parse_hzpp_time_rejects_hour_above_u8_range() {
+        assert!(parse_hzpp_time("256:10:00").is_err());
+    }
+
+    // Mirrors `HzppRoute`/`HzppStop`/`HzppStation` field-for-field but rejects
+    // unknown fields, so a schema change upstream (a field renamed or added)
+    // fails this test instead of silently getting ignored by `serde` at runtime.
+    #[derive(Deserialize)]
+    #[serde(deny_unknown_fields)]
+    #[allow(dead_code)]
+    struct StrictHzppRoute {
+        route_id: String,
+        route_number: i32,
+        route_src: String,
+        route_desc: String,
+        #[serde(deserialize_with = "timestamp_from_hzpp_time")]
+        arrival_time: (u8, u8),
+        #[serde(deserialize_with = "timestamp_from_hzpp_time")]
+        departure_time: (u8, u8),
+        bikes_allowed: i32,
+        wheelchair_accessible: i32,
+        route_type: i32,
+        stops: Vec<StrictHzppStop>,
+        calendar: Vec<StrictCalendar>,
+    }
+
+    #[derive(Deserialize)]
+    #[serde(deny_unknown_fields)]
+    #[allow(dead_code)]
+    struct StrictHzppStop {
+        stop_id: String,
+        stop_name: String,
+        #[serde(deserialize_with = "timestamp_from_hzpp_time")]
+        arrival_time: (u8, u8),
+        #[serde(deserialize_with = "timestamp_from_hzpp_time")]
+        departure_time: (u8, u8),
+        latitude: f64,
+        longitude: f64,
+        sequence: i32,
+    }
+
+    #[derive(Deserialize)]
+    #[serde(deny_unknown_fields)]
+    #[allow(dead_code)]
+    struct StrictCalendar {
+        monday: i32,
+        tuesday: i32,
+        wednesday: i32,
+        thursday: i32,
+        friday: i32,
+        saturday: i32,
+        sunday: i32,
+    }
+
+    #[derive(Deserialize)]
+    #[serde(deny_unknown_fields)]
+    #[allow(dead_code)]
+    struct StrictHzppStation {
+        stop_id: String,
+        stop_code: i32,
+        stop_name: String,
+        stop_lat: f64,
+        stop_lng: f64,
+    }
+
+    #[test]
+    fn canonical_route_sample_matches_known_shape() {
+        serde_json::from_str::<StrictHzppRoute>(ROUTE_SAMPLE).unwrap();
+    }
+
+    #[test]
+    fn canonical_station_sample_matches_known_shape() {
+        serde_json::from_str::<StrictHzppStation>(STATION_SAMPLE).unwrap();
+    }
 
-        (hour, minute)
-    };
+    #[test]
+    fn unexpected_field_is_caught_by_the_strict_shape() {
+        let route = ROUTE_SAMPLE.replace(
+            "\"route_id\": \"1234_20240126\",",
+            "\"route_id\": \"1234_20240126\", \"unexpected_new_field\": true,",
+        );
 
-    res.map_err(de::Error::custom)
+        assert!(serde_json::from_str::<StrictHzppRoute>(&route).is_err());
+    }
 }
@@ -0,0 +1,33 @@
+//! Whether a route was run with newer rolling stock, inferred from its
+//! train-number range since per-run composition isn't tracked anywhere. The
+//! 6100-6199 block is HZPP's 6111/6112-series EMUs; everything else is
+//! assumed to be older loco-hauled stock.
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StockClass {
+    Emu6112,
+    OlderStock,
+}
+
+impl StockClass {
+    pub fn from_route_number(route_number: i32) -> Self {
+        match route_number {
+            6100..=6199 => StockClass::Emu6112,
+            _ => StockClass::OlderStock,
+        }
+    }
+}
+
+mod tests {
+    #[test]
+    fn classifies_a_6112_series_train_number_as_emu() {
+        assert_eq!(super::StockClass::from_route_number(6112), super::StockClass::Emu6112);
+    }
+
+    #[test]
+    fn classifies_a_train_number_outside_the_emu_block_as_older_stock() {
+        assert_eq!(super::StockClass::from_route_number(1234), super::StockClass::OlderStock);
+    }
+}
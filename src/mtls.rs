@@ -0,0 +1,106 @@
+//! Serves the admin panel over mutual TLS, verifying the caller's client
+//! certificate against a configured CA, as an alternative to the bearer
+//! token [`crate::api::admin_ui::require_admin_token`] checks on the default
+//! listener. Optional, and entirely separate from the public API's plain
+//! HTTP listener — see [`crate::config::AdminMtlsConfig`]. There's no
+//! `axum-server`-style convenience crate in this build, so the TLS accept
+//! loop and hyper connection serving are done by hand with `tokio-rustls`
+//! and `hyper-util`.
+use std::{fs::File, io::BufReader, sync::Arc};
+
+use anyhow::{anyhow, Context, Result};
+use axum::Router;
+use hyper_util::{
+    rt::{TokioExecutor, TokioIo},
+    server::conn::auto,
+    service::TowerToHyperService,
+};
+use rustls::{server::WebPkiClientVerifier, RootCertStore, ServerConfig};
+use rustls_pemfile::{certs, private_key};
+use tokio::net::TcpListener;
+use tokio_rustls::TlsAcceptor;
+use tracing::{error, info, warn};
+
+use crate::config::AdminMtlsConfig;
+
+fn load_server_config(config: &AdminMtlsConfig) -> Result<ServerConfig> {
+    // rustls 0.23 needs a process-default crypto provider installed before
+    // any config can be built; ignore the error from a second install (e.g.
+    // sqlx's own rustls usage already installed one).
+    let _ = rustls::crypto::aws_lc_rs::default_provider().install_default();
+
+    let cert_chain = certs(&mut BufReader::new(
+        File::open(&config.cert_path).with_context(|| format!("opening {}", config.cert_path))?,
+    ))
+    .collect::<Result<Vec<_>, _>>()
+    .with_context(|| format!("parsing {}", config.cert_path))?;
+
+    let key = private_key(&mut BufReader::new(
+        File::open(&config.key_path).with_context(|| format!("opening {}", config.key_path))?,
+    ))
+    .with_context(|| format!("parsing {}", config.key_path))?
+    .ok_or_else(|| anyhow!("no private key found in {}", config.key_path))?;
+
+    let mut client_ca_roots = RootCertStore::empty();
+    for ca_cert in certs(&mut BufReader::new(
+        File::open(&config.client_ca_path).with_context(|| format!("opening {}", config.client_ca_path))?,
+    )) {
+        client_ca_roots
+            .add(ca_cert.with_context(|| format!("parsing {}", config.client_ca_path))?)
+            .context("adding client CA cert to root store")?;
+    }
+
+    let client_verifier = WebPkiClientVerifier::builder(Arc::new(client_ca_roots))
+        .build()
+        .context("building client cert verifier")?;
+
+    ServerConfig::builder()
+        .with_client_cert_verifier(client_verifier)
+        .with_single_cert(cert_chain, key)
+        .context("building TLS server config")
+}
+
+/// Accepts connections on `config.bind_addr`, requiring a client certificate
+/// that chains to `config.client_ca_path`, and serves `admin_router` to
+/// whoever presents one. Runs until the process is torn down; a single
+/// connection's TLS handshake failing (no cert, untrusted cert, ...) only
+/// drops that connection rather than the listener.
+pub async fn serve(admin_router: Router, config: AdminMtlsConfig) -> Result<()> {
+    let tls_config = load_server_config(&config)?;
+    let acceptor = TlsAcceptor::from(Arc::new(tls_config));
+    let listener = TcpListener::bind(&config.bind_addr)
+        .await
+        .with_context(|| format!("binding admin mTLS listener on {}", config.bind_addr))?;
+
+    info!(addr = %config.bind_addr, "admin mTLS listener ready");
+
+    loop {
+        let (stream, peer_addr) = match listener.accept().await {
+            Ok(accepted) => accepted,
+            Err(e) => {
+                warn!("accepting admin mTLS connection: {e}");
+                continue;
+            }
+        };
+
+        let acceptor = acceptor.clone();
+        let admin_router = admin_router.clone();
+
+        tokio::spawn(async move {
+            let tls_stream = match acceptor.accept(stream).await {
+                Ok(tls_stream) => tls_stream,
+                Err(e) => {
+                    warn!(%peer_addr, "admin mTLS handshake failed: {e}");
+                    return;
+                }
+            };
+
+            if let Err(e) = auto::Builder::new(TokioExecutor::new())
+                .serve_connection(TokioIo::new(tls_stream), TowerToHyperService::new(admin_router))
+                .await
+            {
+                error!(%peer_addr, "serving admin mTLS connection: {e}");
+            }
+        });
+    }
+}
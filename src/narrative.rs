@@ -0,0 +1,97 @@
+//! Turns a completed run's stop-by-stop timings into a short human-readable
+//! summary, e.g. "Departed Zagreb Glavni kolodvor 4 min late, lost 9 more
+//! minutes near Lipovljani, arrived Novska 13 min late."
+//!
+//! This is a small templated rules engine rather than a generic NLG library:
+//! a handful of ordered rules each either contribute a clause or don't, and
+//! the clauses are joined with ", ". Keeping it rule-based (rather than
+//! templating off a single score) makes it easy to add a new observation
+//! later without reshuffling the existing sentences.
+use std::collections::HashMap;
+
+use crate::model::db_model::{RouteDb, StationDb, StopDb};
+
+fn minutes_late(expected: chrono::DateTime<chrono::Utc>, real: chrono::DateTime<chrono::Utc>) -> i64 {
+    (real - expected).num_minutes()
+}
+
+fn station_name<'a>(stations: &'a HashMap<String, StationDb>, station_id: &str) -> &'a str {
+    stations
+        .get(station_id)
+        .map(|s| s.name.as_str())
+        .unwrap_or("an unknown station")
+}
+
+/// Builds the narrative for a finished run. Returns `None` if the run never
+/// actually started (no observed departures to narrate).
+pub fn generate(route: &RouteDb, stations: &HashMap<String, StationDb>) -> Option<String> {
+    let mut stops: Vec<&StopDb> = route.stops.iter().collect();
+    stops.sort_by_key(|s| s.sequence);
+
+    let first_stop = stops.first()?;
+    let departure_delay = minutes_late(first_stop.expected_departure, first_stop.real_departure?);
+
+    let mut clauses = vec![describe_departure(
+        station_name(stations, &first_stop.station_id),
+        departure_delay,
+    )];
+
+    if let Some((station_id, gained_minutes)) = worst_leg(&stops) {
+        clauses.push(format!(
+            "lost {gained_minutes} more minutes near {}",
+            station_name(stations, station_id)
+        ));
+    }
+
+    if let Some(last_stop) = stops.last() {
+        if let Some(real_arrival) = last_stop.real_arrival {
+            let arrival_delay = minutes_late(last_stop.expected_arrival, real_arrival);
+            clauses.push(describe_arrival(
+                station_name(stations, &last_stop.station_id),
+                arrival_delay,
+            ));
+        }
+    }
+
+    Some(format!("{}.", clauses.join(", ")))
+}
+
+fn describe_departure(station: &str, delay_minutes: i64) -> String {
+    if delay_minutes <= 0 {
+        format!("Departed {station} on time")
+    } else {
+        format!("Departed {station} {delay_minutes} min late")
+    }
+}
+
+fn describe_arrival(station: &str, delay_minutes: i64) -> String {
+    if delay_minutes <= 0 {
+        format!("arrived {station} on time")
+    } else {
+        format!("arrived {station} {delay_minutes} min late")
+    }
+}
+
+/// The stop where the run lost the most additional time relative to the
+/// delay it already had at the previous stop, if any stop made things worse.
+fn worst_leg<'a>(stops: &[&'a StopDb]) -> Option<(&'a str, i64)> {
+    let mut previous_delay = 0i64;
+    let mut worst: Option<(&str, i64)> = None;
+
+    for stop in stops {
+        let Some(real_arrival) = stop.real_arrival else {
+            break;
+        };
+
+        let delay = minutes_late(stop.expected_arrival, real_arrival);
+        let gained = delay - previous_delay;
+
+        if gained > 0 && worst.map(|(_, g)| gained > g).unwrap_or(true) {
+            worst = Some((stop.station_id.as_str(), gained));
+        }
+
+        previous_delay = delay;
+    }
+
+    worst
+}
@@ -0,0 +1,74 @@
+//! Lightweight per-named-query timing. Wrapping a query in [`timed`] records
+//! its wall-clock time under a name chosen at the call site; [`snapshot`]
+//! reports the running count/average/max for every name seen so far, so a
+//! slow query introduced by one of the stats endpoints shows up without
+//! reaching for an external metrics stack.
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+static STATS: OnceLock<Mutex<HashMap<&'static str, Stats>>> = OnceLock::new();
+
+#[derive(Default, Clone, Copy)]
+struct Stats {
+    count: u64,
+    total: Duration,
+    max: Duration,
+}
+
+fn stats() -> &'static Mutex<HashMap<&'static str, Stats>> {
+    STATS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Runs `fut`, recording its wall-clock time under `name` whether it succeeds
+/// or fails, then returns its result unchanged.
+pub async fn timed<T, E>(name: &'static str, fut: impl Future<Output = Result<T, E>>) -> Result<T, E> {
+    let start = Instant::now();
+    let result = fut.await;
+    let elapsed = start.elapsed();
+
+    let mut stats = stats().lock().unwrap();
+    let entry = stats.entry(name).or_default();
+    entry.count += 1;
+    entry.total += elapsed;
+    entry.max = entry.max.max(elapsed);
+
+    result
+}
+
+/// Whether `error` is Postgres cancelling a query for exceeding
+/// `statement_timeout` (SQLSTATE `57014`), as opposed to any other query
+/// failure, so a caller can tell "the server is unreachable" apart from "the
+/// request was too broad to answer in time".
+pub fn is_statement_timeout(error: &sqlx::Error) -> bool {
+    matches!(error, sqlx::Error::Database(e) if e.code().as_deref() == Some("57014"))
+}
+
+#[derive(Serialize)]
+pub struct NamedQueryStats {
+    pub name: &'static str,
+    pub count: u64,
+    pub avg_ms: f64,
+    pub max_ms: f64,
+}
+
+/// Every named query seen so far, slowest average first.
+pub fn snapshot() -> Vec<NamedQueryStats> {
+    let mut snapshot: Vec<_> = stats()
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|(name, stats)| NamedQueryStats {
+            name,
+            count: stats.count,
+            avg_ms: stats.total.as_secs_f64() * 1000.0 / stats.count as f64,
+            max_ms: stats.max.as_secs_f64() * 1000.0,
+        })
+        .collect();
+
+    snapshot.sort_by(|a, b| b.avg_ms.total_cmp(&a.avg_ms));
+    snapshot
+}
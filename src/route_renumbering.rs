@@ -0,0 +1,114 @@
+//! Matches route numbers across timetable periods: when HZ renumbers a
+//! service (same stations, same departure slot, new train number) at a
+//! timetable change, punctuality history for the old number goes cold even
+//! though the service itself kept running. [`detect_successors`] links a
+//! route number to its likely successor by stop pattern and departure slot,
+//! recording the link in `route_successors` so stats endpoints can
+//! optionally follow it via [`successor_chain`] instead of treating each
+//! renumbered service as a brand new one.
+use std::collections::HashSet;
+
+use sqlx::{prelude::FromRow, query, query_as, query_scalar, Pool, Postgres};
+use tracing::info;
+
+#[derive(FromRow)]
+struct Match {
+    route_number: i32,
+    successor_route_number: i32,
+}
+
+/// Finds route numbers whose most recent run's stop pattern and departure
+/// slot match another route number's earliest run that started afterwards,
+/// and records each as `route_number -> successor_route_number` in
+/// `route_successors` (skipping any `route_number` already linked, and any
+/// successor claimed by an earlier match in the same pass). Returns how many
+/// new links were recorded.
+#[tracing::instrument(err, skip(pool))]
+pub async fn detect_successors(pool: &Pool<Postgres>) -> Result<u64, anyhow::Error> {
+    let matches: Vec<Match> = query_as(
+        "WITH route_patterns AS (
+            SELECT
+                r.route_number,
+                r.expected_start_time,
+                array_agg(s.station_id ORDER BY s.sequence) AS stop_pattern,
+                r.expected_start_time::time AS departure_slot
+            FROM routes r
+            JOIN stops s
+                ON s.route_id = r.id AND s.route_expected_start_time = r.expected_start_time
+            GROUP BY r.route_number, r.expected_start_time
+         ),
+         latest_per_route AS (
+            SELECT DISTINCT ON (route_number)
+                route_number, stop_pattern, departure_slot, expected_start_time AS last_seen
+            FROM route_patterns
+            ORDER BY route_number, expected_start_time DESC
+         ),
+         earliest_per_route AS (
+            SELECT DISTINCT ON (route_number)
+                route_number, stop_pattern, departure_slot, expected_start_time AS first_seen
+            FROM route_patterns
+            ORDER BY route_number, expected_start_time ASC
+         )
+         SELECT old.route_number, new.route_number AS successor_route_number
+         FROM latest_per_route old
+         JOIN earliest_per_route new
+            ON new.route_number <> old.route_number
+            AND new.stop_pattern = old.stop_pattern
+            AND abs(new.departure_slot - old.departure_slot) <= interval '2 minutes'
+            AND new.first_seen > old.last_seen
+         WHERE old.route_number NOT IN (SELECT route_number FROM route_successors)",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut linked = 0;
+    let mut claimed_successors = HashSet::new();
+
+    for m in matches {
+        if !claimed_successors.insert(m.successor_route_number) {
+            continue;
+        }
+
+        query(
+            "INSERT INTO route_successors (route_number, successor_route_number) VALUES ($1, $2)
+             ON CONFLICT (route_number) DO NOTHING",
+        )
+        .bind(m.route_number)
+        .bind(m.successor_route_number)
+        .execute(pool)
+        .await?;
+
+        linked += 1;
+    }
+
+    info!(linked, "detected route renumbering links");
+
+    Ok(linked)
+}
+
+/// Follows `route_successors` forward from `route_number` as far as it goes,
+/// returning every route number in the chain (`route_number` itself first).
+/// Bounded defensively against a cycle, though the matcher never creates one
+/// — a route number can only be recorded as a predecessor once.
+pub async fn successor_chain(pool: &Pool<Postgres>, route_number: i32) -> Result<Vec<i32>, sqlx::Error> {
+    let mut chain = vec![route_number];
+    let mut current = route_number;
+
+    for _ in 0..32 {
+        let next: Option<i32> =
+            query_scalar("SELECT successor_route_number FROM route_successors WHERE route_number = $1")
+                .bind(current)
+                .fetch_optional(pool)
+                .await?;
+
+        match next {
+            Some(next) if !chain.contains(&next) => {
+                chain.push(next);
+                current = next;
+            }
+            _ => break,
+        }
+    }
+
+    Ok(chain)
+}
@@ -0,0 +1,38 @@
+//! Operator-assigned labels on a route number (e.g. "Zagreb commuter",
+//! "coastal seasonal", "replacement-bus-prone") used to group and filter
+//! stats/leaderboard queries by corridor, instead of hardcoding route-number
+//! lists into those queries.
+use sqlx::{query, query_scalar, Pool, Postgres};
+
+/// Tags `route_number` with `tag`. Tags are a set, not a log, so re-applying
+/// the same tag isn't an error.
+#[tracing::instrument(err, skip(pool))]
+pub async fn tag_route(pool: &Pool<Postgres>, route_number: i32, tag: &str) -> Result<(), sqlx::Error> {
+    query("INSERT INTO route_tags (route_number, tag) VALUES ($1, $2) ON CONFLICT DO NOTHING")
+        .bind(route_number)
+        .bind(tag)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+/// Removes `tag` from `route_number`. A no-op if it wasn't tagged.
+#[tracing::instrument(err, skip(pool))]
+pub async fn untag_route(pool: &Pool<Postgres>, route_number: i32, tag: &str) -> Result<(), sqlx::Error> {
+    query("DELETE FROM route_tags WHERE route_number = $1 AND tag = $2")
+        .bind(route_number)
+        .bind(tag)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+#[tracing::instrument(err, skip(pool))]
+pub async fn list_tags(pool: &Pool<Postgres>, route_number: i32) -> Result<Vec<String>, sqlx::Error> {
+    query_scalar("SELECT tag FROM route_tags WHERE route_number = $1 ORDER BY tag")
+        .bind(route_number)
+        .fetch_all(pool)
+        .await
+}
@@ -0,0 +1,142 @@
+//! Freezes immutable, checksummed exports of the dataset so a piece of
+//! research can cite the exact data it was run against.
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::{prelude::FromRow, query_as, Pool, Postgres};
+use tokio::fs;
+use tracing::info;
+
+/// Directory all snapshots are written under, one subdirectory per tag.
+pub const SNAPSHOTS_DIR: &str = "./snapshots";
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SnapshotManifest {
+    pub tag: String,
+    pub created_at: DateTime<Utc>,
+    pub files: Vec<SnapshotFile>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SnapshotFile {
+    pub name: String,
+    pub sha256: String,
+    pub size_bytes: u64,
+}
+
+#[derive(FromRow, Serialize)]
+struct RouteRow {
+    id: String,
+    route_number: i32,
+    source: String,
+    destination: String,
+    bikes_allowed: i16,
+    wheelchair_accessible: i16,
+    route_type: i16,
+    real_start_time: Option<DateTime<Utc>>,
+    expected_start_time: DateTime<Utc>,
+    real_end_time: Option<DateTime<Utc>>,
+    expected_end_time: DateTime<Utc>,
+}
+
+#[derive(FromRow, Serialize)]
+struct StopRow {
+    station_id: String,
+    route_id: String,
+    route_expected_start_time: DateTime<Utc>,
+    sequence: i16,
+    real_arrival: Option<DateTime<Utc>>,
+    expected_arrival: DateTime<Utc>,
+    real_departure: Option<DateTime<Utc>>,
+    expected_departure: DateTime<Utc>,
+}
+
+#[derive(FromRow, Serialize)]
+struct StationRow {
+    id: String,
+    code: i32,
+    name: String,
+    latitude: f64,
+    longitude: f64,
+}
+
+/// Exports the `stations`, `routes` and `stops` tables as of now into
+/// `{SNAPSHOTS_DIR}/{tag}/`, one JSON file per table, plus a `manifest.json`
+/// with a sha256 checksum for each file. Fails if the tag was already used.
+#[tracing::instrument(err, skip(pool))]
+pub async fn create_snapshot(pool: &Pool<Postgres>, tag: &str) -> anyhow::Result<()> {
+    let dir = format!("{SNAPSHOTS_DIR}/{tag}");
+
+    if fs::try_exists(&dir).await? {
+        anyhow::bail!("a snapshot tagged '{tag}' already exists");
+    }
+
+    fs::create_dir_all(&dir).await?;
+
+    let stations: Vec<StationRow> = query_as("SELECT * from stations").fetch_all(pool).await?;
+    let routes: Vec<RouteRow> = query_as("SELECT * from routes").fetch_all(pool).await?;
+    let stops: Vec<StopRow> = query_as("SELECT * from stops").fetch_all(pool).await?;
+
+    let mut files = vec![
+        write_table_file(&dir, "stations.json", &stations).await?,
+        write_table_file(&dir, "routes.json", &routes).await?,
+        write_table_file(&dir, "stops.json", &stops).await?,
+    ];
+    files.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let manifest = SnapshotManifest {
+        tag: tag.to_string(),
+        created_at: Utc::now(),
+        files,
+    };
+
+    fs::write(
+        format!("{dir}/manifest.json"),
+        serde_json::to_vec_pretty(&manifest)?,
+    )
+    .await?;
+
+    info!("wrote snapshot '{tag}' to {dir}");
+
+    Ok(())
+}
+
+async fn write_table_file(
+    dir: &str,
+    name: &str,
+    rows: &impl Serialize,
+) -> anyhow::Result<SnapshotFile> {
+    let contents = serde_json::to_vec(rows)?;
+    let sha256 = hex::encode(Sha256::digest(&contents));
+    let size_bytes = contents.len() as u64;
+
+    fs::write(format!("{dir}/{name}"), contents).await?;
+
+    Ok(SnapshotFile {
+        name: name.to_string(),
+        sha256,
+        size_bytes,
+    })
+}
+
+/// Lists the manifests of every snapshot taken so far, most recent first.
+pub async fn list_snapshots() -> anyhow::Result<Vec<SnapshotManifest>> {
+    let mut manifests = vec![];
+
+    let mut entries = match fs::read_dir(SNAPSHOTS_DIR).await {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(manifests),
+        Err(e) => return Err(e.into()),
+    };
+
+    while let Some(entry) = entries.next_entry().await? {
+        let manifest_path = entry.path().join("manifest.json");
+        if let Ok(contents) = fs::read(&manifest_path).await {
+            manifests.push(serde_json::from_slice(&contents)?);
+        }
+    }
+
+    manifests.sort_by(|a: &SnapshotManifest, b: &SnapshotManifest| b.created_at.cmp(&a.created_at));
+
+    Ok(manifests)
+}
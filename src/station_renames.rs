@@ -0,0 +1,112 @@
+//! Bulk station renames driven by a CSV file, for the mass renames HZ does at
+//! timetable changes — updates every row in one transaction instead of
+//! hand-written SQL, and records each old name so `station_aliases` keeps the
+//! history even after the rename lands.
+use anyhow::{bail, Context};
+use sqlx::{query, query_scalar, Pool, Postgres};
+use tracing::info;
+
+#[derive(Debug, PartialEq, Eq)]
+struct RenameRow {
+    station_id: String,
+    new_name: String,
+}
+
+/// Parses `station_id,new_name` rows, one per line, skipping a `station_id,new_name`
+/// header if present. There's no quoting/escaping support — station names and
+/// ids here don't contain commas in practice, and a dependency that handles
+/// that isn't available to this build.
+fn parse_renames_csv(csv: &str) -> Result<Vec<RenameRow>, anyhow::Error> {
+    let mut rows = vec![];
+
+    for (line_number, line) in csv.lines().enumerate() {
+        let line = line.trim();
+
+        if line.is_empty() || line == "station_id,new_name" {
+            continue;
+        }
+
+        let (station_id, new_name) = line
+            .split_once(',')
+            .with_context(|| format!("line {}: expected \"station_id,new_name\"", line_number + 1))?;
+
+        rows.push(RenameRow {
+            station_id: station_id.trim().to_string(),
+            new_name: new_name.trim().to_string(),
+        });
+    }
+
+    Ok(rows)
+}
+
+/// Applies every rename in `csv` atomically: if any station id doesn't
+/// exist, nothing is changed. Returns how many stations actually changed
+/// name (a row whose `new_name` matches the current name is left alone,
+/// and doesn't get a spurious alias entry).
+#[tracing::instrument(skip(pool, csv))]
+pub async fn apply_renames(pool: &Pool<Postgres>, csv: &str) -> Result<u64, anyhow::Error> {
+    let rows = parse_renames_csv(csv)?;
+
+    let mut transaction = pool.begin().await?;
+    let mut renamed = 0;
+
+    for row in rows {
+        let current_name: Option<String> =
+            query_scalar("SELECT name FROM stations WHERE id = $1")
+                .bind(&row.station_id)
+                .fetch_optional(&mut *transaction)
+                .await?;
+
+        let Some(current_name) = current_name else {
+            bail!("unknown station id {}", row.station_id);
+        };
+
+        if current_name == row.new_name {
+            continue;
+        }
+
+        query("INSERT INTO station_aliases (station_id, old_name) VALUES ($1, $2)")
+            .bind(&row.station_id)
+            .bind(&current_name)
+            .execute(&mut *transaction)
+            .await?;
+
+        query("UPDATE stations SET name = $1 WHERE id = $2")
+            .bind(&row.new_name)
+            .bind(&row.station_id)
+            .execute(&mut *transaction)
+            .await?;
+
+        renamed += 1;
+    }
+
+    transaction.commit().await?;
+
+    info!(renamed, "applied station renames");
+
+    Ok(renamed)
+}
+
+mod tests {
+    #[test]
+    fn parses_rows_and_skips_the_header() {
+        let csv = "station_id,new_name\nZG,Zagreb Glavni Kolodvor\nDS,Dugo Selo\n";
+
+        let rows = super::parse_renames_csv(csv).unwrap();
+
+        assert_eq!(
+            rows,
+            vec![
+                super::RenameRow { station_id: "ZG".to_string(), new_name: "Zagreb Glavni Kolodvor".to_string() },
+                super::RenameRow { station_id: "DS".to_string(), new_name: "Dugo Selo".to_string() },
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_a_line_without_a_comma() {
+        let csv = "ZG Zagreb Glavni Kolodvor";
+
+        assert!(super::parse_renames_csv(csv).is_err());
+    }
+}
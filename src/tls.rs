@@ -0,0 +1,90 @@
+//! Terminates TLS on the public listener directly, for small deployments
+//! with no reverse proxy in front of them — see [`crate::config::TlsConfig`].
+//! Unlike [`crate::mtls`], this doesn't verify a client certificate; it's a
+//! plain server-only TLS listener serving the same [`axum::Router`] as the
+//! existing plain-HTTP one. Built by hand with `tokio-rustls` and
+//! `hyper-util` for the same reason `mtls.rs` is: there's no
+//! `axum-server`-style convenience crate in this build.
+use std::{fs::File, io::BufReader, sync::Arc};
+
+use anyhow::{anyhow, Context, Result};
+use axum::Router;
+use hyper_util::{
+    rt::{TokioExecutor, TokioIo},
+    server::conn::auto,
+    service::TowerToHyperService,
+};
+use rustls::ServerConfig;
+use rustls_pemfile::{certs, private_key};
+use tokio::net::TcpListener;
+use tokio_rustls::TlsAcceptor;
+use tracing::{error, info, warn};
+
+use crate::config::TlsConfig;
+
+fn load_server_config(config: &TlsConfig) -> Result<ServerConfig> {
+    // rustls 0.23 needs a process-default crypto provider installed before
+    // any config can be built; ignore the error from a second install (e.g.
+    // sqlx's own rustls usage already installed one).
+    let _ = rustls::crypto::aws_lc_rs::default_provider().install_default();
+
+    let cert_chain = certs(&mut BufReader::new(
+        File::open(&config.cert_path).with_context(|| format!("opening {}", config.cert_path))?,
+    ))
+    .collect::<Result<Vec<_>, _>>()
+    .with_context(|| format!("parsing {}", config.cert_path))?;
+
+    let key = private_key(&mut BufReader::new(
+        File::open(&config.key_path).with_context(|| format!("opening {}", config.key_path))?,
+    ))
+    .with_context(|| format!("parsing {}", config.key_path))?
+    .ok_or_else(|| anyhow!("no private key found in {}", config.key_path))?;
+
+    ServerConfig::builder()
+        .with_no_client_auth()
+        .with_single_cert(cert_chain, key)
+        .context("building TLS server config")
+}
+
+/// Accepts connections on `config.bind_addr` and serves `app` over TLS.
+/// Runs until the process is torn down; a single connection's TLS handshake
+/// failing only drops that connection rather than the listener.
+pub async fn serve(app: Router, config: TlsConfig) -> Result<()> {
+    let tls_config = load_server_config(&config)?;
+    let acceptor = TlsAcceptor::from(Arc::new(tls_config));
+    let listener = TcpListener::bind(&config.bind_addr)
+        .await
+        .with_context(|| format!("binding TLS listener on {}", config.bind_addr))?;
+
+    info!(addr = %config.bind_addr, "TLS listener ready");
+
+    loop {
+        let (stream, peer_addr) = match listener.accept().await {
+            Ok(accepted) => accepted,
+            Err(e) => {
+                warn!("accepting TLS connection: {e}");
+                continue;
+            }
+        };
+
+        let acceptor = acceptor.clone();
+        let app = app.clone();
+
+        tokio::spawn(async move {
+            let tls_stream = match acceptor.accept(stream).await {
+                Ok(tls_stream) => tls_stream,
+                Err(e) => {
+                    warn!(%peer_addr, "TLS handshake failed: {e}");
+                    return;
+                }
+            };
+
+            if let Err(e) = auto::Builder::new(TokioExecutor::new())
+                .serve_connection(TokioIo::new(tls_stream), TowerToHyperService::new(app))
+                .await
+            {
+                error!(%peer_addr, "serving TLS connection: {e}");
+            }
+        });
+    }
+}
@@ -8,3 +8,20 @@ pub fn str_between_str<'a>(full_str: &'a str, str1: &str, str2: &str) -> Option<
 
     Some(result)
 }
+
+/// Builds a URL-friendly slug out of `parts`, e.g. `slugify(&["2111", "Zagreb", "Novska"])`
+/// returns `"2111-zagreb-novska"`. Used to give a route a human-readable id that stays
+/// stable even if the upstream's own route id format changes.
+pub fn slugify(parts: &[&str]) -> String {
+    parts
+        .iter()
+        .map(|part| {
+            part.to_lowercase()
+                .chars()
+                .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+                .collect::<String>()
+        })
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
@@ -0,0 +1,27 @@
+//! HŽ's planner and delay feeds don't carry weather data, and there's no
+//! in-process weather fetcher to enrich runs with automatically. Instead an
+//! operator records which days had bad weather (`weather_events`, keyed by
+//! date), and [`crate::api::stats::route_stats`] joins against it to split
+//! punctuality into fair-weather vs bad-weather buckets.
+use chrono::NaiveDate;
+use sqlx::{query, Pool, Postgres};
+
+/// Marks (or unmarks) `date` as a bad-weather day. Upserts so re-submitting a
+/// correction overwrites rather than erroring.
+#[tracing::instrument(err, skip(pool))]
+pub async fn record_weather_event(
+    pool: &Pool<Postgres>,
+    date: NaiveDate,
+    bad_weather: bool,
+) -> Result<(), sqlx::Error> {
+    query(
+        "INSERT INTO weather_events (date, bad_weather) VALUES ($1, $2)
+         ON CONFLICT (date) DO UPDATE SET bad_weather = excluded.bad_weather",
+    )
+    .bind(date)
+    .bind(bad_weather)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
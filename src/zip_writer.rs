@@ -0,0 +1,92 @@
+//! Minimal ZIP archive writer: there's no `zip` crate in this build, so this
+//! hand-writes the local file headers, central directory and end-of-central-
+//! directory record around `miniz_oxide`'s raw deflate stream, the same way
+//! [`crate::background_services::log_retention::gzip`] hand-writes a gzip
+//! container. See the ZIP APPNOTE.TXT for the format.
+use crc::{Crc, CRC_32_ISO_HDLC};
+
+const CRC32: Crc<u32> = Crc::<u32>::new(&CRC_32_ISO_HDLC);
+const LOCAL_FILE_HEADER_SIGNATURE: u32 = 0x04034b50;
+const CENTRAL_FILE_HEADER_SIGNATURE: u32 = 0x02014b50;
+const END_OF_CENTRAL_DIR_SIGNATURE: u32 = 0x06054b50;
+const DEFLATE_METHOD: u16 = 8;
+const VERSION_NEEDED: u16 = 20;
+
+struct CentralDirectoryEntry {
+    name: Vec<u8>,
+    crc32: u32,
+    compressed_size: u32,
+    uncompressed_size: u32,
+    local_header_offset: u32,
+}
+
+/// Builds a single ZIP archive containing `entries` (name, uncompressed
+/// bytes), each deflated independently, in the order given.
+pub fn build(entries: &[(&str, Vec<u8>)]) -> Vec<u8> {
+    let mut out = Vec::new();
+    let mut directory = Vec::with_capacity(entries.len());
+
+    for (name, contents) in entries {
+        let local_header_offset = out.len() as u32;
+        let crc32 = CRC32.checksum(contents);
+        let deflated = miniz_oxide::deflate::compress_to_vec(contents, 6);
+
+        out.extend_from_slice(&LOCAL_FILE_HEADER_SIGNATURE.to_le_bytes());
+        out.extend_from_slice(&VERSION_NEEDED.to_le_bytes());
+        out.extend_from_slice(&0u16.to_le_bytes()); // general purpose bit flag
+        out.extend_from_slice(&DEFLATE_METHOD.to_le_bytes());
+        out.extend_from_slice(&0u16.to_le_bytes()); // last mod file time
+        out.extend_from_slice(&0u16.to_le_bytes()); // last mod file date
+        out.extend_from_slice(&crc32.to_le_bytes());
+        out.extend_from_slice(&(deflated.len() as u32).to_le_bytes());
+        out.extend_from_slice(&(contents.len() as u32).to_le_bytes());
+        out.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        out.extend_from_slice(&0u16.to_le_bytes()); // extra field length
+        out.extend_from_slice(name.as_bytes());
+        out.extend_from_slice(&deflated);
+
+        directory.push(CentralDirectoryEntry {
+            name: name.as_bytes().to_vec(),
+            crc32,
+            compressed_size: deflated.len() as u32,
+            uncompressed_size: contents.len() as u32,
+            local_header_offset,
+        });
+    }
+
+    let central_directory_offset = out.len() as u32;
+
+    for entry in &directory {
+        out.extend_from_slice(&CENTRAL_FILE_HEADER_SIGNATURE.to_le_bytes());
+        out.extend_from_slice(&VERSION_NEEDED.to_le_bytes()); // version made by
+        out.extend_from_slice(&VERSION_NEEDED.to_le_bytes()); // version needed to extract
+        out.extend_from_slice(&0u16.to_le_bytes()); // general purpose bit flag
+        out.extend_from_slice(&DEFLATE_METHOD.to_le_bytes());
+        out.extend_from_slice(&0u16.to_le_bytes()); // last mod file time
+        out.extend_from_slice(&0u16.to_le_bytes()); // last mod file date
+        out.extend_from_slice(&entry.crc32.to_le_bytes());
+        out.extend_from_slice(&entry.compressed_size.to_le_bytes());
+        out.extend_from_slice(&entry.uncompressed_size.to_le_bytes());
+        out.extend_from_slice(&(entry.name.len() as u16).to_le_bytes());
+        out.extend_from_slice(&0u16.to_le_bytes()); // extra field length
+        out.extend_from_slice(&0u16.to_le_bytes()); // file comment length
+        out.extend_from_slice(&0u16.to_le_bytes()); // disk number start
+        out.extend_from_slice(&0u16.to_le_bytes()); // internal file attributes
+        out.extend_from_slice(&0u32.to_le_bytes()); // external file attributes
+        out.extend_from_slice(&entry.local_header_offset.to_le_bytes());
+        out.extend_from_slice(&entry.name);
+    }
+
+    let central_directory_size = out.len() as u32 - central_directory_offset;
+
+    out.extend_from_slice(&END_OF_CENTRAL_DIR_SIGNATURE.to_le_bytes());
+    out.extend_from_slice(&0u16.to_le_bytes()); // number of this disk
+    out.extend_from_slice(&0u16.to_le_bytes()); // disk with the start of the central directory
+    out.extend_from_slice(&(directory.len() as u16).to_le_bytes());
+    out.extend_from_slice(&(directory.len() as u16).to_le_bytes());
+    out.extend_from_slice(&central_directory_size.to_le_bytes());
+    out.extend_from_slice(&central_directory_offset.to_le_bytes());
+    out.extend_from_slice(&0u16.to_le_bytes()); // comment length
+
+    out
+}